@@ -0,0 +1,264 @@
+// Compares the per-message cost of the fixed size hash types against the nested messages
+// previously generated from `types.proto`. Both encode to the same bytes (see the tests in
+// `src/types.rs`), so the difference is in the size of the decoded messages and the cost of using
+// the hashes they contain.
+//
+// Run with `cargo bench --bench hash_types`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use prost::Message;
+use valorem_trade_interfaces::grpc_codegen::{
+    ConsiderationItem, EthSignature, OfferItem, Order, QuoteRequest, SignedOrder, H128, H160, H256,
+};
+
+// The previous codegen output for the hash types, and the messages built from them.
+mod legacy {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct H128 {
+        #[prost(uint64, tag = "1")]
+        pub hi: u64,
+        #[prost(uint64, tag = "2")]
+        pub lo: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct H160 {
+        #[prost(message, optional, tag = "1")]
+        pub hi: Option<H128>,
+        #[prost(uint32, tag = "2")]
+        pub lo: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct H256 {
+        #[prost(message, optional, tag = "1")]
+        pub hi: Option<H128>,
+        #[prost(message, optional, tag = "2")]
+        pub lo: Option<H128>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct QuoteRequest {
+        #[prost(message, optional, tag = "1")]
+        pub ulid: Option<H128>,
+        #[prost(message, optional, tag = "2")]
+        pub taker_address: Option<H160>,
+        #[prost(int32, tag = "3")]
+        pub item_type: i32,
+        #[prost(message, optional, tag = "4")]
+        pub token_address: Option<H160>,
+        #[prost(message, optional, tag = "5")]
+        pub identifier_or_criteria: Option<H256>,
+        #[prost(message, optional, tag = "6")]
+        pub amount: Option<H256>,
+        #[prost(int32, tag = "7")]
+        pub action: i32,
+        #[prost(message, optional, tag = "8")]
+        pub chain_id: Option<H256>,
+        #[prost(message, optional, tag = "9")]
+        pub seaport_address: Option<H160>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OfferItem {
+        #[prost(int32, tag = "1")]
+        pub item_type: i32,
+        #[prost(message, optional, tag = "2")]
+        pub token: Option<H160>,
+        #[prost(message, optional, tag = "3")]
+        pub identifier_or_criteria: Option<H256>,
+        #[prost(message, optional, tag = "4")]
+        pub start_amount: Option<H256>,
+        #[prost(message, optional, tag = "5")]
+        pub end_amount: Option<H256>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ConsiderationItem {
+        #[prost(int32, tag = "1")]
+        pub item_type: i32,
+        #[prost(message, optional, tag = "2")]
+        pub token: Option<H160>,
+        #[prost(message, optional, tag = "3")]
+        pub identifier_or_criteria: Option<H256>,
+        #[prost(message, optional, tag = "4")]
+        pub start_amount: Option<H256>,
+        #[prost(message, optional, tag = "5")]
+        pub end_amount: Option<H256>,
+        #[prost(message, optional, tag = "6")]
+        pub recipient: Option<H160>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Order {
+        #[prost(message, optional, tag = "1")]
+        pub offerer: Option<H160>,
+        #[prost(message, optional, tag = "2")]
+        pub zone: Option<H160>,
+        #[prost(message, repeated, tag = "3")]
+        pub offer: Vec<OfferItem>,
+        #[prost(message, repeated, tag = "4")]
+        pub consideration: Vec<ConsiderationItem>,
+        #[prost(int32, tag = "5")]
+        pub order_type: i32,
+        #[prost(message, optional, tag = "6")]
+        pub start_time: Option<H256>,
+        #[prost(message, optional, tag = "7")]
+        pub end_time: Option<H256>,
+        #[prost(message, optional, tag = "8")]
+        pub zone_hash: Option<H256>,
+        #[prost(message, optional, tag = "9")]
+        pub salt: Option<H256>,
+        #[prost(message, optional, tag = "10")]
+        pub conduit_key: Option<H256>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EthSignature {
+        #[prost(bytes = "vec", tag = "1")]
+        pub r: Vec<u8>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub s: Vec<u8>,
+        #[prost(bytes = "vec", tag = "3")]
+        pub v: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SignedOrder {
+        #[prost(message, optional, tag = "1")]
+        pub parameters: Option<Order>,
+        #[prost(message, optional, tag = "2")]
+        pub signature: Option<EthSignature>,
+    }
+
+    // The conversion previously done by `grpc_adapters` to get at the raw bytes of a hash.
+    pub fn to_bytes(value: &H256) -> [u8; 32] {
+        let hi = value.hi.clone().unwrap_or_default();
+        let lo = value.lo.clone().unwrap_or_default();
+        let mut bytes = [0u8; 32];
+        bytes[0..8].copy_from_slice(&hi.hi.to_be_bytes());
+        bytes[8..16].copy_from_slice(&hi.lo.to_be_bytes());
+        bytes[16..24].copy_from_slice(&lo.hi.to_be_bytes());
+        bytes[24..32].copy_from_slice(&lo.lo.to_be_bytes());
+        bytes
+    }
+}
+
+fn quote_request() -> QuoteRequest {
+    let address = H160::from([0x5a; 20]);
+    QuoteRequest {
+        ulid: Some(H128::from(0x0186_a4c8_3a1f_7d2e_9b40_11c3_5e2f_a9d7u128)),
+        taker_address: Some(address),
+        item_type: 3,
+        token_address: Some(H160::default()),
+        identifier_or_criteria: Some(H256::from([0xab; 32])),
+        amount: Some(H256::from(5u64)),
+        action: 1,
+        chain_id: Some(H256::from(42161u64)),
+        seaport_address: Some(address),
+    }
+}
+
+fn signed_order() -> SignedOrder {
+    let address = H160::from([0x5a; 20]);
+    let offer = OfferItem {
+        item_type: 3,
+        token: Some(address),
+        identifier_or_criteria: Some(H256::from([0xab; 32])),
+        start_amount: Some(H256::from(5u64)),
+        end_amount: Some(H256::from(5u64)),
+    };
+    let consideration = ConsiderationItem {
+        item_type: 1,
+        token: Some(address),
+        identifier_or_criteria: Some(H256::default()),
+        start_amount: Some(H256::from(1_000_000_000u64)),
+        end_amount: Some(H256::from(1_000_000_000u64)),
+        recipient: Some(address),
+    };
+
+    SignedOrder {
+        parameters: Some(Order {
+            offerer: Some(address),
+            zone: Some(H160::default()),
+            offer: vec![offer],
+            consideration: vec![consideration.clone(), consideration],
+            order_type: 0,
+            start_time: Some(H256::from(1_700_000_000u64)),
+            end_time: Some(H256::from(1_700_003_600u64)),
+            zone_hash: Some(H256::default()),
+            salt: Some(H256::from([0xcd; 32])),
+            conduit_key: Some(H256::default()),
+        }),
+        signature: Some(EthSignature {
+            r: vec![0x11; 32],
+            s: vec![0x22; 32],
+            v: vec![27],
+        }),
+    }
+}
+
+fn quote_requests(c: &mut Criterion) {
+    let request = quote_request();
+    let bytes = request.encode_to_vec();
+    let legacy_request = legacy::QuoteRequest::decode(bytes.as_slice()).unwrap();
+
+    let mut group = c.benchmark_group("QuoteRequest");
+
+    group.bench_function("encode", |b| b.iter(|| black_box(&request).encode_to_vec()));
+    group.bench_function("encode legacy", |b| {
+        b.iter(|| black_box(&legacy_request).encode_to_vec())
+    });
+
+    group.bench_function("decode", |b| {
+        b.iter(|| QuoteRequest::decode(black_box(bytes.as_slice())).unwrap())
+    });
+    group.bench_function("decode legacy", |b| {
+        b.iter(|| legacy::QuoteRequest::decode(black_box(bytes.as_slice())).unwrap())
+    });
+
+    // Decode and read the amount, as a maker does for every request it receives.
+    group.bench_function("decode amount", |b| {
+        b.iter(|| {
+            let request = QuoteRequest::decode(black_box(bytes.as_slice())).unwrap();
+            <[u8; 32]>::from(request.amount.unwrap())
+        })
+    });
+    group.bench_function("decode amount legacy", |b| {
+        b.iter(|| {
+            let request = legacy::QuoteRequest::decode(black_box(bytes.as_slice())).unwrap();
+            legacy::to_bytes(&request.amount.clone().unwrap())
+        })
+    });
+
+    group.finish();
+}
+
+fn signed_orders(c: &mut Criterion) {
+    let order = signed_order();
+    let bytes = order.encode_to_vec();
+    let legacy_order = legacy::SignedOrder::decode(bytes.as_slice()).unwrap();
+
+    let mut group = c.benchmark_group("SignedOrder");
+
+    group.bench_function("encode", |b| b.iter(|| black_box(&order).encode_to_vec()));
+    group.bench_function("encode legacy", |b| {
+        b.iter(|| black_box(&legacy_order).encode_to_vec())
+    });
+
+    group.bench_function("decode", |b| {
+        b.iter(|| SignedOrder::decode(black_box(bytes.as_slice())).unwrap())
+    });
+    group.bench_function("decode legacy", |b| {
+        b.iter(|| legacy::SignedOrder::decode(black_box(bytes.as_slice())).unwrap())
+    });
+
+    group.bench_function("clone", |b| b.iter(|| black_box(&order).clone()));
+    group.bench_function("clone legacy", |b| {
+        b.iter(|| black_box(&legacy_order).clone())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, quote_requests, signed_orders);
+criterion_main!(benches);
@@ -2,22 +2,141 @@ use prost::Message;
 use std::path::PathBuf;
 use std::{env, fs};
 
+// The fixed size hashes which are mapped to the hand written newtypes in `src/types.rs`.
+const EXTERN_HASH_TYPES: &[&str] = &["H128", "H160", "H256"];
+
+// The generated hash types, (de)serialized as 0x-prefixed hex strings.
+const HASH_TYPES: &[&str] = &["H40", "H96"];
+
+// Numeric `H256` fields which are (de)serialized as decimal strings rather than hex.
+const DECIMAL_FIELDS: &[&str] = &[
+    "QuoteRequest.identifier_or_criteria",
+    "QuoteRequest.amount",
+    "QuoteRequest.chain_id",
+    "QuoteResponse.chain_id",
+    "SoftQuoteResponse.chain_id",
+    "SiweSession.chain_id",
+    "OfferItem.identifier_or_criteria",
+    "OfferItem.start_amount",
+    "OfferItem.end_amount",
+    "ConsiderationItem.identifier_or_criteria",
+    "ConsiderationItem.start_amount",
+    "ConsiderationItem.end_amount",
+    "Order.start_time",
+    "Order.end_time",
+    "SpotPriceInfo.spot_price",
+];
+
+// Enum fields, which prost stores as an `i32`, paired with the module (de)serializing them by name.
+const ENUM_FIELDS: &[(&str, &str)] = &[
+    ("QuoteRequest.item_type", "item_type"),
+    ("QuoteRequest.action", "action"),
+    ("OfferItem.item_type", "item_type"),
+    ("ConsiderationItem.item_type", "item_type"),
+    ("Order.order_type", "order_type"),
+];
+
+// Bytes fields which are (de)serialized as 0x-prefixed hex strings.
+const BYTES_FIELDS: &[&str] = &["EthSignature.r", "EthSignature.s", "EthSignature.v"];
+
 // generated by `sqlx migrate build-script`
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Compile the proto files with ProtoX
-    let file_descriptors = protox::compile(
-        [
+    let mut config = prost_build::Config::new();
+    for hash in EXTERN_HASH_TYPES {
+        config.extern_path(
+            format!(".valorem.trade.v1.{hash}"),
+            format!("crate::types::{hash}"),
+        );
+    }
+    if env::var_os("CARGO_FEATURE_SERDE").is_some() {
+        configure_serde(&mut config);
+    }
+
+    // The Valorem Trade API protos, these import each other relative to their own directory.
+    compile_protos(
+        &[
             "../../proto/valorem/trade/v1/rfq.proto",
             "../../proto/valorem/trade/v1/auth.proto",
             "../../proto/valorem/trade/v1/soft_quote.proto",
+            "../../proto/valorem/trade/v1/fees.proto",
+            "../../proto/valorem/trade/v1/spot.proto",
         ],
-        ["../../proto/valorem/trade/v1/"],
-    )
-    .unwrap();
+        &["../../proto/valorem/trade/v1/"],
+        "valorem_interfaces.bin",
+        config,
+    );
+
+    // The upstream Google comments contain JSON and proto snippets which rustdoc would run as tests.
+    let mut config = prost_build::Config::new();
+    config.disable_comments([".google.rpc"]);
+
+    // The standard gRPC protos bundled with the repository which the Valorem Trade API also serves.
+    compile_protos(
+        &[
+            "../../proto/grpc/health/v1/health.proto",
+            "../../proto/grpc/reflection/v1alpha/reflection.proto",
+            "../../proto/google/rpc/status.proto",
+            "../../proto/google/rpc/error_details.proto",
+        ],
+        &["../../proto/"],
+        "grpc_interfaces.bin",
+        config,
+    );
+
+    // trigger recompilation when a new migration is added
+    println!("cargo:rerun-if-changed=migrations");
+    Ok(())
+}
+
+// Derive serde for every Valorem message, the helpers referenced here live in `src/serde_adapters.rs`.
+fn configure_serde(config: &mut prost_build::Config) {
+    config.type_attribute(
+        ".valorem.trade.v1",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
+    config.message_attribute(".valorem.trade.v1", "#[serde(default)]");
+
+    for hash in HASH_TYPES {
+        config.message_attribute(
+            format!(".valorem.trade.v1.{hash}"),
+            r#"#[serde(into = "crate::serde_adapters::HexString", try_from = "crate::serde_adapters::HexString")]"#,
+        );
+    }
+
+    for field in DECIMAL_FIELDS {
+        config.field_attribute(
+            format!(".valorem.trade.v1.{field}"),
+            r#"#[serde(with = "crate::serde_adapters::decimal")]"#,
+        );
+    }
+
+    for (field, module) in ENUM_FIELDS {
+        config.field_attribute(
+            format!(".valorem.trade.v1.{field}"),
+            format!(r#"#[serde(with = "crate::serde_adapters::{module}")]"#),
+        );
+    }
+
+    for field in BYTES_FIELDS {
+        config.field_attribute(
+            format!(".valorem.trade.v1.{field}"),
+            r#"#[serde(with = "crate::serde_adapters::bytes")]"#,
+        );
+    }
+}
+
+fn compile_protos(
+    protos: &[&str],
+    includes: &[&str],
+    file_descriptor_set: &str,
+    config: prost_build::Config,
+) {
+    // Compile the proto files with ProtoX
+    let file_descriptors = protox::compile(protos, includes).unwrap();
 
     // Get the path to the output directory for the file descriptor set
     let file_descriptor_path =
-        PathBuf::from(env::var("OUT_DIR").unwrap()).join("valorem_interfaces.bin");
+        PathBuf::from(env::var("OUT_DIR").unwrap()).join(file_descriptor_set);
 
     // Write the file descriptor set to the output directory
     fs::write(&file_descriptor_path, file_descriptors.encode_to_vec()).unwrap();
@@ -26,16 +145,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     dbg!(tonic_build::configure().build_server(true))
         .file_descriptor_set_path(file_descriptor_path)
         .skip_protoc_run()
-        .compile(
-            &[
-                "../../proto/valorem/trade/v1/rfq.proto",
-                "../../proto/valorem/trade/v1/auth.proto",
-                "../../proto/valorem/trade/v1/soft_quote.proto",
-            ],
-            &["../../proto/valorem/trade/v1/"], // specify the root location to search proto dependencies
-        )
+        .compile_with_config(config, protos, includes)
         .unwrap();
-    // trigger recompilation when a new migration is added
-    println!("cargo:rerun-if-changed=migrations");
-    Ok(())
 }
@@ -1,44 +1,45 @@
 use crate::rfq_request::{handle_rfq_request, validate_rfq};
 use crate::settings::Settings;
 use crate::soft_quote_request::{handle_soft_quote_request, validate_soft_quote};
+use crate::spot_prices::SpotPrices;
 use crate::token_approvals::approve_tokens;
 use ethers::prelude::{
-    Address, Http, Ipc, JsonRpcClient, LocalWallet, Middleware, Provider, Signer, SignerMiddleware,
-    Ws, U256,
+    Address, Http, Ipc, JsonRpcClient, Middleware, Provider, Signer, SignerMiddleware, Ws, U256,
 };
-use http::Uri;
 use log::{error, info, warn};
-use siwe::{TimeStamp, Version};
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::{env, process::exit, sync::Arc, time::Duration};
-use time::OffsetDateTime;
+use std::{env, process::exit, str::FromStr, sync::Arc, time::Duration};
 use tokio::select;
 use tokio::{sync::mpsc, time::sleep};
-use tonic::transport::{Channel, ClientTlsConfig};
-use valorem_trade_interfaces::utils::session_interceptor::SessionInterceptor;
+use valorem_trade_interfaces::client::ValoremClient;
+use valorem_trade_interfaces::error::ValoremError;
+use valorem_trade_interfaces::fees::{FeeCalculator, FeeItems, FeeSchedule, TradeValues};
+use valorem_trade_interfaces::remote_signer::RemoteSigner;
+use valorem_trade_interfaces::seaport::OrderSigner;
+use valorem_trade_interfaces::session_store::SessionStore;
 use valorem_trade_interfaces::{
     bindings, grpc_codegen,
-    grpc_codegen::{
-        auth_client::AuthClient, rfq_client::RfqClient, soft_quote_client::SoftQuoteClient, Empty,
-        QuoteRequest, QuoteResponse, SoftQuoteResponse, VerifyText,
-    },
+    grpc_codegen::{Empty, QuoteRequest, QuoteResponse, SoftQuoteResponse},
 };
 
 mod rfq_request;
 mod seaport_helper;
 mod settings;
 mod soft_quote_request;
+mod spot_prices;
 mod token_approvals;
 
+#[allow(clippy::enum_variant_names)]
 enum EthersProvider {
     HttpProvider(Provider<Http>),
     WsProvider(Provider<Ws>),
     IpcProvider(Provider<Ipc>),
 }
 
-const SESSION_COOKIE_KEY: &str = "set-cookie";
+// How often the session is checked, in case it ended without a stream reporting it.
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 
-const TOS_ACCEPTANCE: &str = "I accept the Valorem Terms of Service at https://app.valorem.xyz/tos and Privacy Policy at https://app.valorem.xyz/privacy";
+// How long to wait for the session to be renewed before recreating the streams.
+const SESSION_RENEWAL_WAIT: Duration = Duration::from_secs(5);
 
 /// An example Market Maker (MM) client interface to Valorem.
 ///
@@ -131,35 +132,116 @@ async fn connect_to_node_provider(node_endpoint: String) -> EthersProvider {
     }
 }
 
-// Main execution function. This is not expected to return.
+// Main execution function, which signs with the signer given in the settings. This is not
+// expected to return.
 async fn run<P: JsonRpcClient + 'static>(
     provider: Arc<Provider<P>>,
     settings: Settings,
 ) -> Option<()> {
-    let session_cookie = setup(
-        settings.valorem_endpoint.clone(),
-        settings.wallet.clone(),
-        settings.tls_config.clone(),
-        &provider,
-    )
-    .await?;
+    if let Some(remote_signer) = settings.remote_signer.clone() {
+        let client = match Http::from_str(&remote_signer) {
+            Ok(client) => client,
+            Err(error) => {
+                error!("Invalid remote signer URL {remote_signer}. Reported error {error:?}");
+                return None;
+            }
+        };
+
+        let signer = match RemoteSigner::connect(client).await {
+            Ok(signer) => signer,
+            Err(error) => {
+                error!("Unable to connect to the remote signer. Reported error: {error}");
+                return None;
+            }
+        };
+
+        run_with_signer(provider, settings, signer).await
+    } else {
+        // The settings always load a wallet when there is no remote signer.
+        let wallet = settings.wallet.clone().unwrap();
+        run_with_signer(provider, settings, wallet).await
+    }
+}
+
+async fn run_with_signer<P: JsonRpcClient + 'static, S: OrderSigner + Clone + 'static>(
+    provider: Arc<Provider<P>>,
+    settings: Settings,
+    wallet: S,
+) -> Option<()> {
+    // Connect and authenticate with Valorem, signing in on the chain of our node.
+    // A stored session is resumed when possible, since signing in ends any earlier session.
+    let chain_id = fetch_chain_id(&provider).await?.as_u64();
+    let signer = wallet.clone().with_chain_id(chain_id);
+    let valorem = match settings.session_store.clone() {
+        Some(store) => {
+            ValoremClient::connect_with_store(
+                settings.valorem_endpoint.clone(),
+                settings.tls_config.clone(),
+                &signer,
+                SessionStore::new(store),
+            )
+            .await
+        }
+        None => {
+            ValoremClient::connect(
+                settings.valorem_endpoint.clone(),
+                settings.tls_config.clone(),
+                &signer,
+            )
+            .await
+        }
+    };
+    let valorem = match valorem {
+        Ok(valorem) => valorem,
+        Err(error) => {
+            error!("Unable to authenticate with Valorem. Reported error:\n{error}");
+            return None;
+        }
+    };
+    info!("Maker has authenticated with Valorem");
+
+    // Sign in again whenever the session ends, so the streams can be recreated with the renewed
+    // session rather than reconnecting from scratch.
+    let _session_refresh = valorem.spawn_session_refresh(signer, SESSION_CHECK_INTERVAL);
 
     // Now there is a valid authenticated session, connect to the RFQ stream
-    let channel_builder = connect_to_valorem(
-        settings.valorem_endpoint.clone(),
-        settings.tls_config.clone(),
-    )
-    .await?;
-
-    let mut rfq_client = RfqClient::with_interceptor(
-        channel_builder.clone(),
-        SessionInterceptor {
-            session_cookie: session_cookie.clone(),
-        },
-    );
+    let mut rfq_client = valorem.rfq();
+    let mut soft_quote_client = valorem.soft_quote();
 
-    let mut soft_quote_client =
-        SoftQuoteClient::with_interceptor(channel_builder, SessionInterceptor { session_cookie });
+    // Fetch the fees we pay or rebates we receive as a maker, which are added to each offer.
+    let fee_structure = match valorem.fees().get_fee_structure(Empty::default()).await {
+        Ok(fee_structure) => fee_structure.into_inner(),
+        Err(error) => {
+            warn!(
+                "Unable to fetch the fee structure. Reported error {}",
+                ValoremError::from(error)
+            );
+            return None;
+        }
+    };
+    let fee_schedule = match FeeSchedule::try_from(fee_structure) {
+        Ok(fee_schedule) => fee_schedule,
+        Err(error) => {
+            warn!(
+                "Received an invalid fee structure. Reported error {}",
+                error
+            );
+            return None;
+        }
+    };
+    let fees = FeeCalculator::new(&fee_schedule, settings.usdc_address);
+
+    // Follow the spot prices of the tokens we write options on, which the spot fees are charged on.
+    let spot_prices = SpotPrices::subscribe(
+        &valorem,
+        chain_id,
+        vec![
+            settings.magic_address,
+            settings.weth_address,
+            settings.wbtc_address,
+            settings.gmx_address,
+        ],
+    );
 
     // Setup a signer so we can send transactions
     let settlement_engine = bindings::valorem_clear::SettlementEngine::new(
@@ -169,11 +251,8 @@ async fn run<P: JsonRpcClient + 'static>(
 
     // We do an unchecked unwrap since if an error is returned it was due to not being able to get the chain_id
     // from the provider.
-    let signer = match SignerMiddleware::new_with_provider_chain(
-        Arc::clone(&provider),
-        settings.wallet.clone(),
-    )
-    .await
+    let signer = match SignerMiddleware::new_with_provider_chain(Arc::clone(&provider), wallet)
+        .await
     {
         Ok(signer) => signer,
         Err(error) => {
@@ -210,7 +289,16 @@ async fn run<P: JsonRpcClient + 'static>(
         {
             Ok(soft_quote_stream) => soft_quote_stream,
             Err(error) => {
-                warn!("Unable to create the Maker Soft Quote stream. Reported error {error:?}");
+                let unauthenticated = valorem.report_status(&error);
+                warn!(
+                    "Unable to create the Maker Soft Quote stream. Reported error {}",
+                    ValoremError::from(error)
+                );
+                if unauthenticated {
+                    // Give the refresh task time to sign in again before recreating the streams.
+                    sleep(SESSION_RENEWAL_WAIT).await;
+                    continue;
+                }
                 return None;
             }
         };
@@ -226,7 +314,16 @@ async fn run<P: JsonRpcClient + 'static>(
         {
             Ok(maker_stream) => maker_stream,
             Err(error) => {
-                warn!("Unable to create the Maker RFQ stream. Reported error {error:?}");
+                let unauthenticated = valorem.report_status(&error);
+                warn!(
+                    "Unable to create the Maker RFQ stream. Reported error {}",
+                    ValoremError::from(error)
+                );
+                if unauthenticated {
+                    // Give the refresh task time to sign in again before recreating the streams.
+                    sleep(SESSION_RENEWAL_WAIT).await;
+                    continue;
+                }
                 return None;
             }
         };
@@ -248,7 +345,7 @@ async fn run<P: JsonRpcClient + 'static>(
                             continue;
                         }
 
-                        let chain_id: U256 = quote.chain_id.clone().unwrap().into();
+                        let chain_id: U256 = quote.chain_id.unwrap().into();
                         if chain_id != U256::from(421614_u64) && chain_id != U256::from(31337_u64) {
                             warn!("RFQ request was not on the testnet chain ({:?}). Ignoring the request", chain_id);
                             continue;
@@ -264,6 +361,8 @@ async fn run<P: JsonRpcClient + 'static>(
                                 &signer,
                                 &seaport,
                                 settings.usdc_address,
+                                &fees,
+                                &spot_prices,
                             )
                             .await?
                         };
@@ -274,6 +373,11 @@ async fn run<P: JsonRpcClient + 'static>(
                         }
                     } else {
                         warn!("Error while handling the RFQ stream");
+                        if let Err(status) = &quote {
+                            if valorem.report_status(status) {
+                                break;
+                            }
+                        }
                         return None;
                     }
                 },
@@ -287,10 +391,10 @@ async fn run<P: JsonRpcClient + 'static>(
                             continue;
                         }
 
-                        let chain_id: U256 = quote.chain_id.clone().unwrap().into();
+                        let chain_id: U256 = quote.chain_id.unwrap().into();
                         if chain_id != U256::from(421614_u64) && chain_id != U256::from(31337_u64) {
                             warn!("Soft Quote request was not on the testnet chain ({:?}). Ignoring the request", chain_id);
-                            continue; 
+                            continue;
                         }
 
                         let quote_offer = if validate_soft_quote(quote.clone()).is_none() {
@@ -303,6 +407,8 @@ async fn run<P: JsonRpcClient + 'static>(
                                 &signer,
                                 &seaport,
                                 settings.usdc_address,
+                                &fees,
+                                &spot_prices,
                             )
                             .await?
                         };
@@ -313,6 +419,11 @@ async fn run<P: JsonRpcClient + 'static>(
                         }
                     } else {
                         warn!("Error while handling the Soft Quote stream");
+                        if let Err(status) = &soft_quote {
+                            if valorem.report_status(status) {
+                                break;
+                            }
+                        }
                         return None;
                     }
                 }
@@ -322,144 +433,117 @@ async fn run<P: JsonRpcClient + 'static>(
 }
 
 // Create the "No offer" response data
-fn create_no_offer<P: JsonRpcClient + 'static>(
+fn create_no_offer<P: JsonRpcClient + 'static, S: Signer + 'static>(
     request_for_quote: &QuoteRequest,
-    signer: &SignerMiddleware<Arc<Provider<P>>, LocalWallet>,
+    signer: &SignerMiddleware<Arc<Provider<P>>, S>,
 ) -> QuoteResponse {
     QuoteResponse {
-        ulid: request_for_quote.ulid.clone(),
+        ulid: request_for_quote.ulid,
         maker_address: Some(grpc_codegen::H160::from(signer.address())),
         order: None,
-        chain_id: request_for_quote.chain_id.clone(),
-        seaport_address: request_for_quote.seaport_address.clone(),
+        chain_id: request_for_quote.chain_id,
+        seaport_address: request_for_quote.seaport_address,
     }
 }
 
 // Create the "No offer" response data
-fn create_soft_quote_no_offer<P: JsonRpcClient + 'static>(
+fn create_soft_quote_no_offer<P: JsonRpcClient + 'static, S: Signer + 'static>(
     request_for_quote: &QuoteRequest,
-    signer: &SignerMiddleware<Arc<Provider<P>>, LocalWallet>,
+    signer: &SignerMiddleware<Arc<Provider<P>>, S>,
 ) -> SoftQuoteResponse {
     SoftQuoteResponse {
-        ulid: request_for_quote.ulid.clone(),
+        ulid: request_for_quote.ulid,
         maker_address: Some(grpc_codegen::H160::from(signer.address())),
         order: None,
-        chain_id: request_for_quote.chain_id.clone(),
-        seaport_address: request_for_quote.seaport_address.clone(),
+        chain_id: request_for_quote.chain_id,
+        seaport_address: request_for_quote.seaport_address,
     }
 }
 
-// Connect and setup a valid session
-async fn setup<P: JsonRpcClient + 'static>(
-    valorem_uri: Uri,
-    wallet: LocalWallet,
-    tls_config: ClientTlsConfig,
-    provider: &Arc<Provider<P>>,
-) -> Option<String> {
-    // Connect and authenticate with Valorem
-    let channel_builder = connect_to_valorem(valorem_uri.clone(), tls_config.clone()).await?;
-    let mut client: AuthClient<Channel> = AuthClient::new(channel_builder);
-
-    let response = match client.nonce(Empty::default()).await {
-        Ok(response) => response,
-        Err(_) => {
-            error!("Unable to fetch Nonce from endpoint");
+// Helper function to calculate the fee items for a trade of `amount` options for `premium`.
+//
+// The spot fees are charged on the value of the option's non-USDC asset at its latest spot price.
+// Rebates cannot be part of the order, they are settled by Valorem from the fee address.
+async fn fetch_fee_items<P: JsonRpcClient + 'static>(
+    fees: &FeeCalculator,
+    spot_prices: &SpotPrices,
+    settlement_engine: &bindings::valorem_clear::SettlementEngine<Provider<P>>,
+    option_id: U256,
+    amount: U256,
+    premium: U256,
+) -> Option<FeeItems> {
+    let option = match settlement_engine.option(option_id).call().await {
+        Ok(option) => option,
+        Err(error) => {
+            warn!("Fee Items: Error while attempting to fetch the option type. Reported error {error:?}");
             return None;
         }
     };
 
-    // Fetch the session cookie for all future requests
-    let session_cookie = match response.metadata().get(SESSION_COOKIE_KEY) {
-        Some(session_cookie_raw) => match session_cookie_raw.to_str() {
-            Ok(session_cookie) => session_cookie.to_string(),
-            Err(_) => {
-                error!("Unable to fetch session cookie from Nonce response");
-                return None;
-            }
-        },
+    let notional = match TradeValues::notional_of(&option, amount, fees.usdc_address) {
+        Some(notional) => notional,
         None => {
-            error!("Session cookie was not returned in Nonce response");
+            warn!("Fee Items: Unable to value the option {option_id:?} in USDC");
             return None;
         }
     };
 
-    let nonce = response.into_inner().nonce;
-
-    // Verify & authenticate with Valorem before connecting to RFQ endpoint.
-    let channel_builder = connect_to_valorem(valorem_uri, tls_config).await?;
-    let mut client = AuthClient::with_interceptor(
-        channel_builder,
-        SessionInterceptor {
-            session_cookie: session_cookie.clone(),
-        },
-    );
-
-    let chain_id = fetch_chain_id(&provider).await?.as_u64();
-
-    // Create a sign in with ethereum message
-    let message = siwe::Message {
-        domain: "localhost.com".parse().unwrap(),
-        address: wallet.address().0,
-        statement: Some(TOS_ACCEPTANCE.into()),
-        uri: "http://localhost/".parse().unwrap(),
-        version: Version::V1,
-        chain_id,
-        nonce,
-        issued_at: TimeStamp::from(OffsetDateTime::now_utc()),
-        expiration_time: None,
-        not_before: None,
-        request_id: None,
-        resources: vec![],
-    };
-
-    // Generate a signature
-    let message_string = message.to_string();
-    let signature = wallet
-        .sign_message(message_string.as_bytes())
-        .await
-        .unwrap();
-
-    // Create the SignedMessage
-    let signature_string = signature.to_string();
-    let mut signed_message = serde_json::Map::new();
-    signed_message.insert(
-        "signature".to_string(),
-        serde_json::Value::from(signature_string),
-    );
-    signed_message.insert(
-        "message".to_string(),
-        serde_json::Value::from(message_string),
-    );
-    let body = serde_json::Value::from(signed_message).to_string();
+    // Without spot fees there is no need to wait for a spot price.
+    let spot = if fees.maker.spot.is_zero() && fees.taker.spot.is_zero() {
+        U256::zero()
+    } else {
+        let asset = if option.exercise_asset == fees.usdc_address {
+            option.underlying_asset
+        } else {
+            option.exercise_asset
+        };
+        let spot_price = match spot_prices.get(asset) {
+            Some(spot_price) => spot_price,
+            None => {
+                warn!("Fee Items: No spot price has been received for {asset:?} yet");
+                return None;
+            }
+        };
+        let decimals = match bindings::erc20::Erc20::new(asset, settlement_engine.client())
+            .decimals()
+            .call()
+            .await
+        {
+            Ok(decimals) => decimals,
+            Err(error) => {
+                warn!("Fee Items: Error while attempting to fetch the decimals of {asset:?}. Reported error {error:?}");
+                return None;
+            }
+        };
 
-    let response = client.verify(VerifyText { body }).await;
-    match response {
-        Ok(_) => (),
-        Err(error) => {
-            error!("Unable to verify client. Reported error:\n{error:?}");
-            return None;
+        match TradeValues::spot_of(
+            &option,
+            amount,
+            fees.usdc_address,
+            spot_price,
+            decimals.into(),
+        ) {
+            Some(spot) => spot,
+            None => {
+                warn!("Fee Items: Unable to value the option {option_id:?} at the spot price");
+                return None;
+            }
         }
-    }
+    };
 
-    // Check that we have an authenticated session
-    let response = client.authenticate(Empty::default()).await;
-    match response {
-        Ok(_) => (),
-        Err(error) => {
-            error!("Unable to check authentication with Valorem. Reported error:\n{error:?}");
-            return None;
-        }
+    let fee_items = fees.fee_items(&TradeValues {
+        notional,
+        premium,
+        spot,
+    });
+    if !fee_items.rebates.is_zero() {
+        info!(
+            "Fee Items: Rebates of {} (maker) and {} (taker) USDC dust are settled outside of the order",
+            fee_items.rebates.maker, fee_items.rebates.taker
+        );
     }
 
-    info!("Maker has authenticated with Valorem");
-    Some(session_cookie)
-}
-
-fn time_now() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
+    Some(fee_items)
 }
 
 // Helper function to fetch the chain id.
@@ -468,35 +552,7 @@ async fn fetch_chain_id<P: JsonRpcClient + 'static>(provider: &Provider<P>) -> O
         Ok(chain_id) => Some(chain_id),
         Err(error) => {
             warn!("ChainId Fetch: Error while attempting to get the chain_id. Reported error {error:?}");
-            return None;
-        }
-    }
-}
-
-// Helper function to connect to Valorem.
-async fn connect_to_valorem(
-    valorem_uri: Uri,
-    tls_config: ClientTlsConfig,
-) -> Option<tonic::transport::Channel> {
-    let builder = match Channel::builder(valorem_uri).tls_config(tls_config) {
-        Ok(builder) => builder,
-        Err(error) => {
-            panic!("Unable to add in TLS configuration to the channel builder. Error returned {error:?}")
-        }
-    };
-
-    match builder
-        .http2_keep_alive_interval(Duration::new(75, 0))
-        .keep_alive_timeout(Duration::new(10, 0))
-        .timeout(Duration::from_secs(10))
-        .connect_timeout(Duration::from_secs(10))
-        .connect()
-        .await
-    {
-        Ok(builder) => Some(builder),
-        Err(error) => {
-            warn!("Unable to connect to Valorem endpoint. Reported error {error:?}");
-            return None;
+            None
         }
     }
 }
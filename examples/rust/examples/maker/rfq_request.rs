@@ -1,20 +1,19 @@
 use crate::create_no_offer;
 use crate::fetch_chain_id;
+use crate::fetch_fee_items;
 use crate::seaport_helper::sign_order;
 use crate::seaport_helper::write_option;
-use crate::time_now;
-use ethers::prelude::{
-    rand::{thread_rng, Rng},
-    Address, JsonRpcClient, LocalWallet, Middleware, Provider, SignerMiddleware, U256,
-};
+use crate::spot_prices::SpotPrices;
+use ethers::prelude::{Address, JsonRpcClient, Middleware, Provider, SignerMiddleware, U256};
 use log::{info, warn};
 use std::{ops::Mul, sync::Arc};
 use valorem_trade_interfaces::{
-    bindings, grpc_codegen,
-    grpc_codegen::{
-        Action, ConsiderationItem, ItemType, OfferItem, Order, OrderType, QuoteRequest,
-        QuoteResponse, H256,
-    },
+    bindings,
+    fees::FeeCalculator,
+    grpc_codegen,
+    grpc_codegen::{Action, ItemType, QuoteRequest, QuoteResponse},
+    order_builder::OrderBuilder,
+    seaport::OrderSigner,
 };
 
 /// Validate the received RFQ is not malformed and supported.
@@ -47,15 +46,16 @@ pub fn validate_rfq(seaport_address: Address, rfq: QuoteRequest) -> Option<Quote
     }
 
     // Amount needs to be non-zero
-    if rfq.amount.is_none() {
-        warn!("Received a RFQ with an invalid amount. Amount was None.");
-        return None;
-    } else {
-        let amount: U256 = rfq.amount.clone().unwrap().into();
-        if amount.is_zero() {
+    match rfq.amount {
+        None => {
+            warn!("Received a RFQ with an invalid amount. Amount was None.");
+            return None;
+        }
+        Some(amount) if U256::from(amount).is_zero() => {
             warn!("Received a RFQ with an invalid amount. Amount was Zero.");
             return None;
         }
+        Some(_) => {}
     }
 
     // Action needs to be valid
@@ -66,7 +66,7 @@ pub fn validate_rfq(seaport_address: Address, rfq: QuoteRequest) -> Option<Quote
     }
 
     // Check the seaport address is against the one we support.
-    if let Some(rfq_seaport_address) = rfq.seaport_address.clone() {
+    if let Some(rfq_seaport_address) = rfq.seaport_address {
         if seaport_address != rfq_seaport_address.into() {
             warn!("Received an RFQ against a non-supported seaport address.");
             return None;
@@ -79,12 +79,14 @@ pub fn validate_rfq(seaport_address: Address, rfq: QuoteRequest) -> Option<Quote
     Some(rfq)
 }
 
-pub async fn handle_rfq_request<P: JsonRpcClient + 'static>(
+pub async fn handle_rfq_request<P: JsonRpcClient + 'static, S: OrderSigner + 'static>(
     request_for_quote: QuoteRequest,
     settlement_engine: &bindings::valorem_clear::SettlementEngine<Provider<P>>,
-    signer: &SignerMiddleware<Arc<Provider<P>>, LocalWallet>,
+    signer: &SignerMiddleware<Arc<Provider<P>>, S>,
     seaport: &bindings::seaport::Seaport<Provider<P>>,
     usdc_address: Address,
+    fees: &FeeCalculator,
+    spot_prices: &SpotPrices,
 ) -> Option<QuoteResponse> {
     // Return an offer with an hardcoded price in USDC.
     let fee = 10;
@@ -94,12 +96,14 @@ pub async fn handle_rfq_request<P: JsonRpcClient + 'static>(
         U256::from(fee).mul(U256::exp10(6usize))
     );
 
+    let builder = OrderBuilder::from_request(&request_for_quote);
+
     let request_action: Action = request_for_quote.action.into();
-    let (offered_item, consideration_item) = match request_action {
+    let option_id = match request_action {
         Action::Buy => {
             info!(
-                "Handling Buy Order for Option Type {:?}",
-                U256::from(request_for_quote.identifier_or_criteria.clone().unwrap())
+                "Handling Buy Order for Option Type {}",
+                request_for_quote.identifier_or_criteria.unwrap()
             );
             let (option_id, _claim_id) =
                 match write_option(&request_for_quote, settlement_engine, signer).await {
@@ -111,51 +115,13 @@ pub async fn handle_rfq_request<P: JsonRpcClient + 'static>(
                     }
                 };
 
-            // Option we are offering
-            let option = OfferItem {
-                item_type: i32::from(ItemType::Erc1155 as u8),
-                token: Some(settlement_engine.address().into()),
-                identifier_or_criteria: Some(option_id.into()),
-                start_amount: request_for_quote.amount.clone(),
-                end_amount: request_for_quote.amount.clone(),
-            };
-
-            // Price we want for the option
-            let price = ConsiderationItem {
-                item_type: i32::from(ItemType::Erc20 as u8),
-                token: Some(usdc_address.into()),
-                identifier_or_criteria: None,
-                start_amount: Some(U256::from(fee).mul(U256::exp10(6usize)).into()),
-                end_amount: Some(U256::from(fee).mul(U256::exp10(6usize)).into()),
-                recipient: Some(signer.address().into()),
-            };
-
-            (option, price)
+            // We offer the option we just wrote
+            option_id
         }
         Action::Sell => {
-            let option_id = U256::from(request_for_quote.identifier_or_criteria.clone().unwrap());
+            let option_id = U256::from(request_for_quote.identifier_or_criteria.unwrap());
             info!("Handling Sell Order for Option Id {:?}", option_id);
-
-            // We are offering the following price for the given option
-            let price = OfferItem {
-                item_type: i32::from(ItemType::Erc20 as u8),
-                token: Some(usdc_address.into()),
-                identifier_or_criteria: None,
-                start_amount: Some(U256::from(fee).mul(U256::exp10(6usize)).into()),
-                end_amount: Some(U256::from(fee).mul(U256::exp10(6usize)).into()),
-            };
-
-            // The option we want in return
-            let option = ConsiderationItem {
-                item_type: i32::from(ItemType::Erc1155 as u8),
-                token: Some(settlement_engine.address().into()),
-                identifier_or_criteria: Some(option_id.into()),
-                start_amount: request_for_quote.amount.clone(),
-                end_amount: request_for_quote.amount.clone(),
-                recipient: Some(signer.address().into()),
-            };
-
-            (price, option)
+            option_id
         }
         Action::Invalid => {
             info!("Received invalid action from the RFQ, returning no offer");
@@ -164,42 +130,40 @@ pub async fn handle_rfq_request<P: JsonRpcClient + 'static>(
         }
     };
 
-    // Offer is only valid for 30 minutes
-    let now: H256 = U256::from(time_now()).into();
-    let now_plus_30_minutes: H256 = (U256::from(time_now()) + U256::from(1200u64)).into();
-
-    // Reference https://docs.opensea.io/reference/seaport-overview
-    //           https://docs.opensea.io/reference/create-an-offer
-    // Arbitrary source of entropy for the order
-    let salt = U256::from(thread_rng().gen::<u128>());
-
-    // Valorem have a domain tag: 60DD32CF
-    let domain_tag = U256::from_str_radix("60DD32CF", 16_u32).unwrap();
-    let mask = U256::from(2_u8)
-        .pow(U256::from(32))
-        .saturating_sub(U256::one());
-    let salt = (salt & !mask) + domain_tag;
-
-    let parameters = Order {
-        zone: None,
-        zone_hash: None,
-        conduit_key: None,
-
-        // OpenSea: Must be open order
-        // Note: We use a FULL fill here as we don't want to allow partial fills of the order
-        //  this can change based on MM strategy
-        order_type: i32::from(OrderType::FullOpen as u8),
-
-        offerer: Some(signer.address().into()),
-        offer: vec![offered_item],
-        start_time: Some(now),
-        end_time: Some(now_plus_30_minutes),
-        consideration: vec![consideration_item],
-        salt: Some(salt.into()),
+    // The maker fee follows the leg we offer and the taker fee the leg we consider, a rebate
+    // results in no fee item and is settled outside of the order.
+    let price = U256::from(fee).mul(U256::exp10(6usize));
+    let amount = U256::from(request_for_quote.amount.unwrap());
+    let fee_items = match fetch_fee_items(
+        fees,
+        spot_prices,
+        settlement_engine,
+        option_id,
+        amount,
+        price,
+    )
+    .await
+    {
+        Some(fee_items) => fee_items,
+        None => {
+            let no_offer = create_no_offer(&request_for_quote, signer);
+            return Some(no_offer);
+        }
     };
 
-    let signed_order = sign_order(signer, parameters, seaport).await?;
-    let chain_id = fetch_chain_id(&signer.provider()).await?;
+    // The builder lays out the option and USDC legs for the action, and by default uses a Full
+    // Open order valid for 20 minutes with a Valorem tagged salt.
+    let builder = builder
+        .option_id(option_id)
+        .clear_address(settlement_engine.address())
+        .usdc_address(usdc_address)
+        .offerer(signer.address())
+        .price(price)
+        .additional_offer(fee_items.maker)
+        .additional_consideration(fee_items.taker);
+
+    let signed_order = sign_order(signer, builder, seaport).await?;
+    let chain_id = fetch_chain_id(signer.provider()).await?;
 
     Some(QuoteResponse {
         ulid: request_for_quote.ulid,
@@ -1,77 +1,23 @@
-use ethers::abi::{AbiEncode, RawLog};
+use ethers::abi::RawLog;
 use ethers::prelude::{
-    Address, EthLogDecode, JsonRpcClient, LocalWallet, Middleware, Provider, SignerMiddleware, U256,
+    EthLogDecode, JsonRpcClient, Middleware, Provider, Signer, SignerMiddleware, U256,
 };
-use ethers::utils::keccak256;
 use log::{info, warn};
 use std::{ops::Mul, sync::Arc};
 use valorem_trade_interfaces::{
     bindings,
-    grpc_codegen::{EthSignature, Order, QuoteRequest, SignedOrder, H256},
+    grpc_codegen::{QuoteRequest, SignedOrder},
+    order_builder::OrderBuilder,
+    seaport::{OrderSigner, SeaportDomain},
 };
 
-pub async fn sign_order<P: JsonRpcClient + 'static>(
-    signer: &SignerMiddleware<Arc<Provider<P>>, LocalWallet>,
-    order_parameters: Order,
+pub async fn sign_order<P: JsonRpcClient + 'static, S: OrderSigner + 'static>(
+    signer: &SignerMiddleware<Arc<Provider<P>>, S>,
+    order: OrderBuilder,
     seaport: &bindings::seaport::Seaport<Provider<P>>,
 ) -> Option<SignedOrder> {
-    // As we are going to be returning an Order, we clone the order parameters here, so we can
-    // then use them in the order and avoid the `as_ref` and `clone` calls throughout the
-    // transformation code (this has no performance impact, just reads a little better).
-    let order_parameters_copy = order_parameters.clone();
-
-    // In order to sign the seaport order, we firstly transform the OrderParameters
-    // into the ethers equivalents as we need to call the Seaport contract in order to get the
-    // order hash.
-    let mut offer = Vec::<valorem_trade_interfaces::bindings::seaport::OfferItem>::new();
-    for offer_item in order_parameters.offer {
-        offer.push(bindings::seaport::OfferItem {
-            item_type: offer_item.item_type as u8,
-            token: Address::from(offer_item.token.unwrap_or_default()),
-            identifier_or_criteria: U256::from(
-                offer_item.identifier_or_criteria.unwrap_or_default(),
-            ),
-            start_amount: U256::from(offer_item.start_amount.unwrap_or_default()),
-            end_amount: U256::from(offer_item.end_amount.unwrap_or_default()),
-        });
-    }
-
-    let mut consideration = Vec::<bindings::seaport::ConsiderationItem>::new();
-    for consideration_item in order_parameters.consideration {
-        consideration.push(bindings::seaport::ConsiderationItem {
-            item_type: consideration_item.item_type as u8,
-            token: Address::from(consideration_item.token.unwrap_or_default()),
-            identifier_or_criteria: U256::from(
-                consideration_item
-                    .identifier_or_criteria
-                    .unwrap_or_default(),
-            ),
-            start_amount: U256::from(consideration_item.start_amount.unwrap_or_default()),
-            end_amount: U256::from(consideration_item.end_amount.unwrap_or_default()),
-            recipient: Address::from(consideration_item.recipient.unwrap_or_default()),
-        });
-    }
-
-    let mut zone_hash: [u8; 32] = Default::default();
-    match order_parameters.zone_hash {
-        Some(zone_hash_param) if zone_hash_param != H256::default() => {
-            // We need to transform the H256 into a U256 in order for the encode into u8 to work
-            // as we expect.
-            zone_hash.copy_from_slice(U256::from(zone_hash_param).encode().as_slice());
-        }
-        _ => zone_hash.fill(0),
-    }
-
-    let mut conduit_key: [u8; 32] = Default::default();
-    match order_parameters.conduit_key {
-        Some(conduit_key_param) if conduit_key_param != H256::default() => {
-            // We need to transform the H256 into a U256 in order for the encode into u8 to work
-            // as we expect.
-            conduit_key.copy_from_slice(U256::from(conduit_key_param).encode().as_slice());
-        }
-        _ => conduit_key.fill(0),
-    }
-
+    // The order hash and domain separator are computed locally, so the only on-chain state we
+    // need is the counter for our address.
     let counter = match seaport.get_counter(signer.address()).await {
         Ok(counter) => counter,
         Err(error) => {
@@ -80,86 +26,29 @@ pub async fn sign_order<P: JsonRpcClient + 'static>(
         }
     };
 
-    let order_components = bindings::seaport::OrderComponents {
-        offerer: Address::from(order_parameters.offerer.unwrap()),
-        zone: Address::from(order_parameters.zone.unwrap_or_default()),
-        offer,
-        consideration,
-        order_type: order_parameters.order_type as u8,
-        start_time: U256::from(order_parameters.start_time.unwrap()),
-        end_time: U256::from(order_parameters.end_time.unwrap()),
-        zone_hash,
-        salt: U256::from(order_parameters.salt.unwrap()),
-        conduit_key,
-        counter,
+    let domain = SeaportDomain {
+        chain_id: signer.signer().chain_id().into(),
+        verifying_contract: seaport.address(),
     };
 
-    // Construct the required signature, this was taken from the Seaport tests:
-    // https://github.com/ProjectOpenSea/seaport/blob/main/test/foundry/utils/BaseConsiderationTest.sol#L208
-    let mut encoded_message = Vec::<u8>::new();
-    let order_hash = match seaport.get_order_hash(order_components).call().await {
-        Ok(order_hash) => order_hash,
-        Err(error) => {
-            warn!("Unable to fetch the order hash for the order from the Seaport contract. Reported error: {error:?}");
-            return None;
-        }
-    };
-    let (_, domain_separator, _) = match seaport.information().call().await {
-        Ok(seaport_information) => seaport_information,
+    match order.sign(signer.signer(), domain, counter).await {
+        Ok(signed_order) => Some(signed_order),
         Err(error) => {
-            warn!("Unable to retrieve on-chain Seaport information. Reported error: {error:?}");
-            return None;
+            warn!("Unable to sign the order. Reported error: {error}");
+            None
         }
-    };
-
-    // bytes2(0x1901)
-    for byte in &[25u8, 1u8] {
-        encoded_message.push(*byte);
-    }
-
-    for byte in &domain_separator {
-        encoded_message.push(*byte);
     }
-
-    for byte in &order_hash {
-        encoded_message.push(*byte);
-    }
-
-    let hash = keccak256(encoded_message.as_slice());
-    let signature = signer
-        .signer()
-        .sign_hash(ethers::types::H256::from(hash))
-        .unwrap();
-
-    // We don't want to directly encode v, as this will be encoded as a u64 where leading
-    // zeros matter (so it will be included). We know its only 1 byte, therefore only push 1 byte
-    // of data so the signature remains 65 bytes on the wire.
-    let eth_signature = EthSignature {
-        v: vec![signature.v.to_le_bytes()[0]],
-        r: signature.r.encode(),
-        s: signature.s.encode(),
-    };
-
-    Some(SignedOrder {
-        parameters: Some(order_parameters_copy),
-        signature: Some(eth_signature),
-    })
 }
 
 // This function will call "write" on the SettlementEngine contract for the Option Type
 // and start_amount given within the RFQ
-pub async fn write_option<P: JsonRpcClient + 'static>(
+pub async fn write_option<P: JsonRpcClient + 'static, S: Signer + 'static>(
     request_for_quote: &QuoteRequest,
     settlement_engine: &bindings::valorem_clear::SettlementEngine<Provider<P>>,
-    signer: &SignerMiddleware<Arc<Provider<P>>, LocalWallet>,
+    signer: &SignerMiddleware<Arc<Provider<P>>, S>,
 ) -> Option<(U256, U256)> {
-    let option_type: U256 = request_for_quote
-        .identifier_or_criteria
-        .as_ref()
-        .unwrap()
-        .clone()
-        .into();
-    let amount: U256 = request_for_quote.amount.as_ref().unwrap().clone().into();
+    let option_type: U256 = request_for_quote.identifier_or_criteria.unwrap().into();
+    let amount: U256 = request_for_quote.amount.unwrap().into();
 
     // Take gas estimation out of the equation which can be dicey on the Arbitrum testnet.
     // todo - this is true for now, in the future we should check the chain id
@@ -5,6 +5,7 @@ use rpassword::read_password;
 use serde::Deserialize;
 use std::fs::read_to_string;
 use std::io::{stdout, Write};
+use std::path::PathBuf;
 use std::str::FromStr;
 use tonic::transport::{Certificate, ClientTlsConfig};
 
@@ -15,6 +16,8 @@ struct InnerSettings {
     settlement_contract: String,
     keystore: Option<String>,
     private_key: Option<String>,
+    remote_signer: Option<String>,
+    session_store: Option<String>,
     ca_root: Option<String>,
     domain_name: Option<String>,
     approve_tokens: bool,
@@ -30,7 +33,9 @@ pub struct Settings {
     pub node_endpoint: String,
     pub valorem_endpoint: Uri,
     pub settlement_contract: Address,
-    pub wallet: LocalWallet,
+    pub wallet: Option<LocalWallet>,
+    pub remote_signer: Option<String>,
+    pub session_store: Option<PathBuf>,
     pub tls_config: ClientTlsConfig,
     pub approve_tokens: bool,
     pub magic_address: Address,
@@ -48,10 +53,13 @@ impl Settings {
             .unwrap();
         let inner: InnerSettings = settings.try_deserialize().unwrap();
 
-        let wallet = if let Some(keystore) = inner.keystore {
-            decrypt_keystore(&keystore)
+        // A wallet is only needed if the orders are not signed by a remote signer.
+        let wallet = if inner.remote_signer.is_some() {
+            None
+        } else if let Some(keystore) = inner.keystore {
+            Some(decrypt_keystore(&keystore))
         } else {
-            fetch_private_key(inner.private_key)
+            Some(fetch_private_key(inner.private_key))
         };
 
         // TLS Configuration, use default settings unless provided with an alternate
@@ -80,6 +88,8 @@ impl Settings {
             wbtc_address: inner.wbtc_address.parse::<Address>().unwrap(),
             gmx_address: inner.gmx_address.parse::<Address>().unwrap(),
             wallet,
+            remote_signer: inner.remote_signer,
+            session_store: inner.session_store.map(PathBuf::from),
             tls_config,
             approve_tokens: inner.approve_tokens,
         }
@@ -0,0 +1,81 @@
+use ethers::prelude::Address;
+use log::warn;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use valorem_trade_interfaces::client::ValoremClient;
+use valorem_trade_interfaces::error::ValoremError;
+use valorem_trade_interfaces::spot::{SpotPrice, SpotPriceSubscription};
+
+// How long to wait before subscribing again after the spot price stream ended.
+const SPOT_RESUBSCRIBE_WAIT: Duration = Duration::from_secs(5);
+
+/// The latest spot prices of the tokens the maker trades, which the spot fees are charged on.
+///
+/// The prices are kept up to date by a task subscribed to the Valorem Spot service, which is
+/// stopped when this is dropped.
+pub struct SpotPrices {
+    prices: Arc<RwLock<HashMap<Address, SpotPrice>>>,
+    task: JoinHandle<()>,
+}
+
+impl SpotPrices {
+    pub fn subscribe(valorem: &ValoremClient, chain_id: u64, tokens: Vec<Address>) -> Self {
+        let prices = Arc::new(RwLock::new(HashMap::new()));
+        let valorem = valorem.clone();
+        let task = tokio::spawn({
+            let prices = Arc::clone(&prices);
+            async move {
+                let mut client = valorem.spot();
+                loop {
+                    let subscription = SpotPriceSubscription::subscribe(
+                        &mut client,
+                        tokens.iter().map(|token| (chain_id, *token)),
+                    )
+                    .await;
+
+                    let status = match subscription {
+                        Ok(mut subscription) => loop {
+                            match subscription.next().await {
+                                Ok(Some(update)) if update.chain_id == chain_id => {
+                                    prices.write().unwrap().insert(update.token, update.price);
+                                }
+                                Ok(Some(_)) => {}
+                                Ok(None) => break None,
+                                Err(status) => break Some(status),
+                            }
+                        },
+                        Err(status) => Some(status),
+                    };
+
+                    match status {
+                        Some(status) => {
+                            valorem.report_status(&status);
+                            warn!(
+                                "Spot price stream failed, subscribing again. Reported error {}",
+                                ValoremError::from(status)
+                            );
+                        }
+                        None => warn!("Spot price stream ended, subscribing again"),
+                    }
+                    sleep(SPOT_RESUBSCRIBE_WAIT).await;
+                }
+            }
+        });
+
+        Self { prices, task }
+    }
+
+    /// The latest spot price of `token`, if one has been received.
+    pub fn get(&self, token: Address) -> Option<SpotPrice> {
+        self.prices.read().unwrap().get(&token).copied()
+    }
+}
+
+impl Drop for SpotPrices {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -1,13 +1,13 @@
 use crate::settings::Settings;
-use ethers::prelude::{JsonRpcClient, LocalWallet, Middleware, Provider, SignerMiddleware, U256};
+use ethers::prelude::{JsonRpcClient, Middleware, Provider, Signer, SignerMiddleware, U256};
 use log::info;
 use std::{ops::Mul, sync::Arc};
 use valorem_trade_interfaces::bindings;
 
-pub async fn approve_tokens<P: JsonRpcClient + 'static>(
+pub async fn approve_tokens<P: JsonRpcClient + 'static, S: Signer + 'static>(
     provider: &Arc<Provider<P>>,
     settings: &Settings,
-    signer: &SignerMiddleware<Arc<Provider<P>>, LocalWallet>,
+    signer: &SignerMiddleware<Arc<Provider<P>>, S>,
     settlement_contract: &bindings::valorem_clear::SettlementEngine<Provider<P>>,
     seaport_contract: &bindings::seaport::Seaport<Provider<P>>,
 ) {
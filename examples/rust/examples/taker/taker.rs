@@ -1,36 +1,27 @@
-use crate::seaport_helper::transform_to_seaport_order;
 use crate::settings::Settings;
 use crate::token_approvals::approve_test_tokens;
 use ethers::abi::RawLog;
 use ethers::prelude::{
-    Address, EthLogDecode, Http, JsonRpcClient, LocalWallet, Middleware, Provider, Signer,
-    SignerMiddleware, Ws, U256,
+    Address, BlockNumber, EthLogDecode, Http, JsonRpcClient, LocalWallet, Middleware, Provider,
+    Signer, SignerMiddleware, Ws, U256,
 };
-use http::Uri;
-use siwe::{TimeStamp, Version};
 use std::env;
 use std::ops::Mul;
 use std::process::exit;
 use std::sync::Arc;
-use time::OffsetDateTime;
 use tokio::sync::mpsc;
-use tonic::transport::{Channel, ClientTlsConfig};
 use valorem_trade_interfaces::bindings;
-use valorem_trade_interfaces::grpc_codegen::auth_client::AuthClient;
-use valorem_trade_interfaces::grpc_codegen::rfq_client::RfqClient;
-use valorem_trade_interfaces::grpc_codegen::soft_quote_client::SoftQuoteClient;
-use valorem_trade_interfaces::grpc_codegen::{Action, ItemType, QuoteRequest};
-use valorem_trade_interfaces::grpc_codegen::{Empty, VerifyText};
-use valorem_trade_interfaces::utils::session_interceptor::SessionInterceptor;
-
-mod seaport_helper;
+use valorem_trade_interfaces::chains::{Chain, SeaportVersion};
+use valorem_trade_interfaces::client::ValoremClient;
+use valorem_trade_interfaces::grpc_adapters::ConversionError;
+use valorem_trade_interfaces::grpc_codegen::{Action, Order, QuoteRequest};
+use valorem_trade_interfaces::seaport::{verify_signed_order_with_eip1271, SeaportDomain};
+
 mod settings;
 mod token_approvals;
 
-const SESSION_COOKIE_KEY: &str = "set-cookie";
 const SECONDS_IN_A_DAY: u64 = 86400u64;
 const SECONDS_IN_THIRTY_MINUTES: u64 = 1800u64;
-const TOS_ACCEPTANCE: &str = "I accept the Valorem Terms of Service at https://app.valorem.xyz/tos and Privacy Policy at https://app.valorem.xyz/privacy";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -72,38 +63,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// If there are any unexpected errors the function will print what information it has and then
 /// exit.
 async fn run<P: JsonRpcClient + 'static>(provider: Arc<Provider<P>>, settings: Settings) {
-    let session_cookie = setup_valorem_connection(
+    // Connect and authenticate with Valorem, signing in on the chain of our node.
+    let chain_id = provider.get_chainid().await.unwrap().as_u64();
+    let valorem = match ValoremClient::connect(
         settings.valorem_endpoint.clone(),
-        settings.wallet.clone(),
         settings.tls_config.clone(),
-        &provider,
+        &settings.wallet.clone().with_chain_id(chain_id),
     )
-    .await;
+    .await
+    {
+        Ok(valorem) => valorem,
+        Err(error) => {
+            eprintln!("Error: Unable to authenticate with Valorem. Reported error:\n{error}");
+            exit(1);
+        }
+    };
+    println!("Client has authenticated with Valorem");
 
     // Now there is a valid authenticated session, connect to the gRPC streams
-    let mut rfq_client = RfqClient::with_interceptor(
-        Channel::builder(settings.valorem_endpoint.clone())
-            .tls_config(settings.tls_config.clone())
-            .unwrap()
-            .http2_keep_alive_interval(std::time::Duration::from_secs(75))
-            .connect()
-            .await
-            .unwrap(),
-        SessionInterceptor {
-            session_cookie: session_cookie.clone(),
-        },
-    );
-
-    let mut quote_client = SoftQuoteClient::with_interceptor(
-        Channel::builder(settings.valorem_endpoint.clone())
-            .tls_config(settings.tls_config.clone())
-            .unwrap()
-            .http2_keep_alive_interval(std::time::Duration::from_secs(75))
-            .connect()
-            .await
-            .unwrap(),
-        SessionInterceptor { session_cookie },
-    );
+    let mut rfq_client = valorem.rfq();
+    let mut quote_client = valorem.soft_quote();
 
     // Valorem Settlement Engine
     let settlement_engine = bindings::valorem_clear::SettlementEngine::new(
@@ -116,9 +95,7 @@ async fn run<P: JsonRpcClient + 'static>(provider: Arc<Provider<P>>, settings: S
             .unwrap();
 
     // Seaport address
-    let seaport_contract_address = "0x00000000000000ADc04C56Bf30aC9d3c0aAF14dC"
-        .parse::<Address>()
-        .unwrap();
+    let seaport_contract_address = Address::from(SeaportVersion::V1_5.address());
     let seaport = bindings::seaport::Seaport::new(seaport_contract_address, Arc::clone(&provider));
 
     // Approve the tokens the example will be using on Arbitrum Testnet
@@ -151,50 +128,35 @@ async fn run<P: JsonRpcClient + 'static>(provider: Arc<Provider<P>>, settings: S
     let gas = U256::from(500000u64);
     let gas_price = U256::from(2000).mul(U256::exp10(8usize));
 
-    // Lets get a quote from the maker for the option we just created.
-    let quote = QuoteRequest {
-        ulid: None,
-        taker_address: Some(settings.wallet.address().into()),
-        item_type: ItemType::Erc1155 as i32,
-        token_address: Some(settings.settlement_contract.into()),
-        identifier_or_criteria: Some(option_id.into()),
-        amount: Some(U256::from(5u8).into()),
-        action: Action::Buy as i32,
-        chain_id: Some(U256::from(settings.chain_id).into()),
-        seaport_address: Some(seaport_contract_address.into()),
+    // Requests are for the chain and Clear deployment in the settings, which are checked against
+    // the registry unless the chain is outside of it, e.g. a local devnet.
+    let quote_request = || match Chain::from_id(settings.chain_id) {
+        Some(chain) => QuoteRequest::builder()
+            .chain(chain)
+            .clear_address(settings.settlement_contract),
+        None => {
+            QuoteRequest::builder().devnet_chain(settings.chain_id, settings.settlement_contract)
+        }
     };
 
-    // Send a quote
-    println!();
-    println!("Sending quote to Maker for 5 Options of {option_id:?}");
-    tx_quote.send(quote.clone()).await.unwrap();
+    // Lets get a quote from the maker for the option we just created.
+    let quote = quote_request()
+        .taker_address(settings.wallet.address())
+        .option_id(option_id)
+        .amount(U256::from(5u8))
+        .action(Action::Buy)
+        .build()
+        .unwrap();
 
+    // Ask for a soft quote until the maker provides one, skipping responses we cannot read since
+    // they come from an untrusted maker.
     loop {
-        match quote_stream.message().await {
-            Ok(Some(quote_response)) => {
-                if quote_response.order.is_none() {
-                    println!("Maker did not wish to provide a quote on the Order.");
-                    println!();
-                    println!("Sending quote to Maker for 5 Options of {option_id:?}");
-                    tx_quote.send(quote.clone()).await.unwrap();
-                    continue;
-                }
-
-                let order_parameters = quote_response.order.unwrap();
-                println!(
-                    "Received quote from Maker. {:?} ({:?}) for {:?} options",
-                    U256::from(
-                        order_parameters.consideration[0]
-                            .start_amount
-                            .clone()
-                            .unwrap()
-                    ),
-                    Address::from(order_parameters.consideration[0].token.clone().unwrap()),
-                    U256::from(order_parameters.offer[0].start_amount.clone().unwrap()),
-                );
-                println!("We like it!");
-                break;
-            }
+        println!();
+        println!("Sending quote to Maker for 5 Options of {option_id:?}");
+        tx_quote.send(quote.clone()).await.unwrap();
+
+        let quote_response = match quote_stream.message().await {
+            Ok(Some(quote_response)) => quote_response,
             Ok(None) => {
                 panic!("Error: Soft Quote stream ended unexpectedly.");
             }
@@ -204,97 +166,47 @@ async fn run<P: JsonRpcClient + 'static>(provider: Arc<Provider<P>>, settings: S
                     error
                 );
             }
-        }
+        };
+
+        let order = match quote_response.order.as_ref() {
+            Some(order) => order,
+            None => {
+                println!("Maker did not wish to provide a quote on the Order.");
+                continue;
+            }
+        };
+        let (premium, options) = match first_items(order) {
+            Ok(items) => items,
+            Err(error) => {
+                eprintln!("Received a malformed quote from the Maker, skipping it. Reported error: {error}");
+                continue;
+            }
+        };
+
+        println!(
+            "Received quote from Maker. {:?} ({:?}) for {:?} options",
+            premium.start_amount, premium.token, options.start_amount,
+        );
+        println!("We like it!");
+        break;
     }
 
+    // Send the RFQ buy order, and once it is filled a sell order for the same options.
+    let mut rfq = quote.clone();
     let mut sell_rfq = false;
 
-    // Send the RFQ buy order
-    let rfq = quote.clone();
-    println!();
-    println!("Sending Buy RFQ to Maker for Option Type {:?}", option_id);
-    tx_rfq.send(rfq.clone()).await.unwrap();
-
     loop {
-        // We expect the message to be returned on the stream back
-        match rfq_stream.message().await {
-            Ok(Some(offer)) => {
-                if offer.order.is_none() {
-                    println!("Maker did not wish to make a quote on the Order.");
-                    println!();
-                    println!("Sending Buy RFQ to Maker for Option Type {:?}", option_id);
-                    tx_rfq.send(rfq.clone()).await.unwrap();
-                    continue;
-                }
-
-                let offered_order = offer.order.unwrap();
-                let offer_parameters = offered_order.parameters.clone().unwrap();
-                println!(
-                    "Received offer from Maker. {:?} ({:?}) for {:?} options",
-                    U256::from(
-                        offer_parameters.consideration[0]
-                            .start_amount
-                            .clone()
-                            .unwrap()
-                    ),
-                    Address::from(offer_parameters.consideration[0].token.clone().unwrap()),
-                    U256::from(offer_parameters.offer[0].start_amount.clone().unwrap()),
-                );
+        println!();
+        println!(
+            "Sending {} RFQ to Maker for Option Id {:?}",
+            if sell_rfq { "Sell" } else { "Buy" },
+            option_id
+        );
+        tx_rfq.send(rfq.clone()).await.unwrap();
 
-                let order = transform_to_seaport_order(&offered_order, offer_parameters);
-                let option_id = order.parameters.offer[0].identifier_or_criteria;
-
-                let mut order_tx = seaport.fulfill_order(order, [0u8; 32]).tx;
-                order_tx.set_gas(gas);
-                order_tx.set_gas_price(gas_price);
-                let pending_tx = match signer.send_transaction(order_tx, None).await {
-                    Ok(pending_tx) => pending_tx,
-                    Err(error) => {
-                        eprintln!("Error: Unable to send fulfill order transaction to Seaport for fulfillment.");
-                        eprintln!("Reported error: {:?}", error);
-                        exit(1);
-                    }
-                };
-
-                // Wait until the tx has been handled by the sequencer.
-                pending_tx.await.unwrap();
-
-                if !sell_rfq {
-                    let owned_tokens = settlement_engine
-                        .balance_of(signer.address(), option_id)
-                        .call()
-                        .await
-                        .unwrap();
-                    assert_eq!(owned_tokens, U256::from(5u8));
-
-                    // Now sell all the options right back
-                    // Sell Order
-                    let rfq = QuoteRequest {
-                        ulid: None,
-                        taker_address: Some(settings.wallet.address().into()),
-                        item_type: ItemType::Erc1155 as i32,
-                        token_address: Some(settlement_engine.address().into()),
-                        identifier_or_criteria: Some(option_id.into()),
-                        amount: Some(U256::from(5u8).into()),
-                        action: Action::Sell as i32,
-                        chain_id: Some(U256::from(settings.chain_id).into()),
-                        seaport_address: Some(seaport_contract_address.into()),
-                    };
-                    println!("Sending Sell RFQ to Maker for Option Id {:?}", option_id);
-                    sell_rfq = true;
-                    tx_rfq.send(rfq).await.unwrap();
-                } else {
-                    let owned_tokens = settlement_engine
-                        .balance_of(signer.address(), option_id)
-                        .call()
-                        .await
-                        .unwrap();
-                    assert_eq!(owned_tokens, U256::zero());
-                    println!("Sold all options back to Maker");
-                    println!("Test case successfully finished.");
-                    exit(1);
-                }
-            }
+        // We expect the message to be returned on the stream back
+        let offer = match rfq_stream.message().await {
+            Ok(Some(offer)) => offer,
             Ok(None) => {
                 panic!("Error: RFQ stream ended unexpectedly.");
             }
@@ -304,114 +216,163 @@ async fn run<P: JsonRpcClient + 'static>(provider: Arc<Provider<P>>, settings: S
                     error
                 );
             }
-        }
-    }
-}
+        };
 
-// Create and setup the connection to Valorem
-async fn setup_valorem_connection<P: JsonRpcClient + 'static>(
-    valorem_uri: Uri,
-    wallet: LocalWallet,
-    tls_config: ClientTlsConfig,
-    provider: &Arc<Provider<P>>,
-) -> String {
-    // Connect and authenticate with Valorem
-    let mut client: AuthClient<Channel> = AuthClient::new(
-        Channel::builder(valorem_uri.clone())
-            .tls_config(tls_config.clone())
-            .unwrap()
-            .connect()
+        let offered_order = match offer.order {
+            Some(offered_order) => offered_order,
+            None => {
+                println!("Maker did not wish to make a quote on the Order.");
+                continue;
+            }
+        };
+        let order = match bindings::seaport::Order::try_from(&offered_order) {
+            Ok(order) => order,
+            Err(error) => {
+                eprintln!("Received a malformed order from the Maker, skipping it. Reported error: {error}");
+                continue;
+            }
+        };
+        // The conversion above succeeds only if the parameters are set.
+        let offer_parameters = match offered_order.parameters.as_ref() {
+            Some(offer_parameters) => offer_parameters,
+            None => continue,
+        };
+
+        // Seaport interpolates the amounts over the order's time window, so price the
+        // order as of the latest block rather than by its start amounts.
+        let timestamp = provider
+            .get_block(BlockNumber::Latest)
             .await
-            .unwrap(),
-    );
-    let response = client
-        .nonce(Empty::default())
-        .await
-        .expect("Unable to fetch Nonce");
-
-    // Fetch the session cookie for all future requests
-    let session_cookie = response
-        .metadata()
-        .get(SESSION_COOKIE_KEY)
-        .expect("Session cookie was not returned in Nonce response")
-        .to_str()
-        .expect("Unable to fetch session cookie from Nonce response")
-        .to_string();
-
-    let nonce = response.into_inner().nonce;
-
-    // Verify & authenticate with Valorem before connecting to RFQ endpoint.
-    let mut client = AuthClient::with_interceptor(
-        Channel::builder(valorem_uri)
-            .tls_config(tls_config)
             .unwrap()
-            .connect()
+            .unwrap()
+            .timestamp;
+        let amounts = match offer_parameters.current_amounts(timestamp) {
+            Ok(amounts) => amounts,
+            Err(error) => {
+                eprintln!("Received an order from the Maker which cannot be filled, skipping it. Reported error: {error}");
+                continue;
+            }
+        };
+        let (premium_item, options_item) = match first_items(offer_parameters) {
+            Ok(items) => items,
+            Err(error) => {
+                eprintln!("Received a malformed order from the Maker, skipping it. Reported error: {error}");
+                continue;
+            }
+        };
+        let (premium, options) = match (amounts.consideration.first(), amounts.offer.first()) {
+            (Some(premium), Some(options)) => (*premium, *options),
+            _ => {
+                eprintln!("Received an order without an offer or consideration from the Maker, skipping it.");
+                continue;
+            }
+        };
+        let seconds_remaining = offer_parameters
+            .status(timestamp)
+            .map(|status| status.seconds_remaining())
+            .unwrap_or_default();
+        println!(
+            "Received offer from Maker. {:?} ({:?}) for {:?} options, valid for {} seconds",
+            premium, premium_item.token, options, seconds_remaining,
+        );
+
+        // Check the order was signed by the maker before we spend gas on it, an invalid
+        // signature would only be rejected once the transaction is mined.
+        let counter = seaport
+            .get_counter(order.parameters.offerer)
+            .call()
             .await
-            .unwrap(),
-        SessionInterceptor {
-            session_cookie: session_cookie.clone(),
-        },
-    );
-
-    // Create a sign in with ethereum message
-    let message = siwe::Message {
-        domain: "localhost.com".parse().unwrap(),
-        address: wallet.address().0,
-        statement: Some(TOS_ACCEPTANCE.into()),
-        uri: "http://localhost/".parse().unwrap(),
-        version: Version::V1,
-        chain_id: provider.get_chainid().await.unwrap().as_u64(),
-        nonce,
-        issued_at: TimeStamp::from(OffsetDateTime::now_utc()),
-        expiration_time: None,
-        not_before: None,
-        request_id: None,
-        resources: vec![],
-    };
-
-    // Generate a signature
-    let message_string = message.to_string();
-    let signature = wallet
-        .sign_message(message_string.as_bytes())
+            .unwrap();
+        let domain = SeaportDomain {
+            chain_id: U256::from(settings.chain_id),
+            verifying_contract: seaport.address(),
+        };
+        if let Err(error) = verify_signed_order_with_eip1271(
+            Arc::clone(&provider),
+            &offered_order,
+            domain,
+            counter,
+            offer.maker_address.map(Address::from),
+        )
         .await
-        .unwrap();
+        {
+            eprintln!("Received an order with an invalid signature from the Maker, skipping it. Reported error: {error}");
+            continue;
+        }
 
-    // Create the SignedMessage
-    let signature_string = signature.to_string();
-    let mut signed_message = serde_json::Map::new();
-    signed_message.insert(
-        "signature".to_string(),
-        serde_json::Value::from(signature_string),
-    );
-    signed_message.insert(
-        "message".to_string(),
-        serde_json::Value::from(message_string),
-    );
-    let body = serde_json::Value::from(signed_message).to_string();
+        let option_id = options_item.identifier_or_criteria;
+        let mut order_tx = seaport.fulfill_order(order, [0u8; 32]).tx;
+        order_tx.set_gas(gas);
+        order_tx.set_gas_price(gas_price);
+        let pending_tx = match signer.send_transaction(order_tx, None).await {
+            Ok(pending_tx) => pending_tx,
+            Err(error) => {
+                eprintln!(
+                    "Error: Unable to send fulfill order transaction to Seaport for fulfillment."
+                );
+                eprintln!("Reported error: {:?}", error);
+                exit(1);
+            }
+        };
 
-    let response = client.verify(VerifyText { body }).await;
-    match response {
-        Ok(_) => (),
-        Err(error) => {
-            eprintln!("Error: Unable to verify client. Reported error:\n{error:?}");
-            exit(1);
-        }
-    }
+        // Wait until the tx has been handled by the sequencer.
+        pending_tx.await.unwrap();
 
-    // Check that we have an authenticated session
-    let response = client.authenticate(Empty::default()).await;
-    match response {
-        Ok(_) => (),
-        Err(error) => {
-            eprintln!(
-                "Error: Unable to check authentication with Valorem. Reported error:\n{error:?}"
-            );
+        if !sell_rfq {
+            let owned_tokens = settlement_engine
+                .balance_of(signer.address(), option_id)
+                .call()
+                .await
+                .unwrap();
+            assert_eq!(owned_tokens, U256::from(5u8));
+
+            // Now sell all the options right back
+            rfq = quote_request()
+                .taker_address(settings.wallet.address())
+                .option_id(option_id)
+                .amount(U256::from(5u8))
+                .action(Action::Sell)
+                .build()
+                .unwrap();
+            sell_rfq = true;
+        } else {
+            let owned_tokens = settlement_engine
+                .balance_of(signer.address(), option_id)
+                .call()
+                .await
+                .unwrap();
+            assert_eq!(owned_tokens, U256::zero());
+            println!("Sold all options back to Maker");
+            println!("Test case successfully finished.");
             exit(1);
         }
     }
+}
 
-    println!("Client has authenticated with Valorem");
-    session_cookie
+// The first consideration and offer items of a quoted order, i.e. the premium and the options for
+// a buy. Quotes come from an untrusted maker, so the order is checked rather than indexed.
+fn first_items(
+    order: &Order,
+) -> Result<
+    (
+        bindings::seaport::ConsiderationItem,
+        bindings::seaport::OfferItem,
+    ),
+    ConversionError,
+> {
+    let order = bindings::seaport::OrderParameters::try_from(order)?;
+    let premium = order
+        .consideration
+        .first()
+        .cloned()
+        .ok_or(ConversionError::MissingField("Order.consideration"))?;
+    let options = order
+        .offer
+        .first()
+        .cloned()
+        .ok_or(ConversionError::MissingField("Order.offer"))?;
+
+    Ok((premium, options))
 }
 
 // Create the option that we'll send RFQs on.
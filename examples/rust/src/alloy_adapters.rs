@@ -0,0 +1,396 @@
+// Setup From traits allowing the conversion between proto types and alloy-primitives and
+// alloy-sol-types types, enabled with the `alloy` feature.
+use crate::grpc_adapters::{item_type, order_type, required, ConversionError};
+use crate::grpc_codegen::*;
+use alloy_primitives::{Address, Bytes, PrimitiveSignature, B256, U256};
+
+/// Solidity definitions of the Seaport 1.5 order structs, see `bindings::seaport` for the ethers
+/// equivalents.
+pub mod seaport {
+    alloy_sol_types::sol! {
+        #[derive(Debug, PartialEq, Eq)]
+        struct OfferItem {
+            uint8 itemType;
+            address token;
+            uint256 identifierOrCriteria;
+            uint256 startAmount;
+            uint256 endAmount;
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        struct ConsiderationItem {
+            uint8 itemType;
+            address token;
+            uint256 identifierOrCriteria;
+            uint256 startAmount;
+            uint256 endAmount;
+            address recipient;
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        struct OrderParameters {
+            address offerer;
+            address zone;
+            OfferItem[] offer;
+            ConsiderationItem[] consideration;
+            uint8 orderType;
+            uint256 startTime;
+            uint256 endTime;
+            bytes32 zoneHash;
+            uint256 salt;
+            bytes32 conduitKey;
+            uint256 totalOriginalConsiderationItems;
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        struct Order {
+            OrderParameters parameters;
+            bytes signature;
+        }
+    }
+}
+
+impl From<Address> for H160 {
+    fn from(value: Address) -> Self {
+        Self::from(value.into_array())
+    }
+}
+
+impl From<H160> for Address {
+    fn from(value: H160) -> Self {
+        Self::from(<[u8; 20]>::from(value))
+    }
+}
+
+impl From<B256> for H256 {
+    fn from(value: B256) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl From<H256> for B256 {
+    fn from(value: H256) -> Self {
+        Self::from(<[u8; 32]>::from(value))
+    }
+}
+
+impl From<U256> for H256 {
+    fn from(value: U256) -> Self {
+        Self::from(value.to_be_bytes::<32>())
+    }
+}
+
+impl From<H256> for U256 {
+    fn from(value: H256) -> Self {
+        Self::from_be_bytes(<[u8; 32]>::from(value))
+    }
+}
+
+impl TryFrom<&EthSignature> for PrimitiveSignature {
+    type Error = ConversionError;
+
+    fn try_from(value: &EthSignature) -> Result<Self, Self::Error> {
+        let (r, s, v) = value.components()?;
+        Ok(Self::new(
+            U256::from_be_bytes(r),
+            U256::from_be_bytes(s),
+            matches!(v, 1 | 28),
+        ))
+    }
+}
+
+impl TryFrom<EthSignature> for PrimitiveSignature {
+    type Error = ConversionError;
+
+    fn try_from(value: EthSignature) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+// `v` is sent as a single byte in its legacy 27/28 form, matching the ethers conversion.
+impl From<PrimitiveSignature> for EthSignature {
+    fn from(value: PrimitiveSignature) -> Self {
+        Self {
+            r: value.r().to_be_bytes::<32>().to_vec(),
+            s: value.s().to_be_bytes::<32>().to_vec(),
+            v: vec![27 + u8::from(value.v())],
+        }
+    }
+}
+
+// Orders are typically received from the network, so required fields are validated rather than
+// defaulted. Only the identifier or criteria may be omitted, in which case it is zero.
+impl TryFrom<&OfferItem> for seaport::OfferItem {
+    type Error = ConversionError;
+
+    fn try_from(value: &OfferItem) -> Result<Self, Self::Error> {
+        Ok(Self {
+            itemType: item_type(value.item_type)?,
+            token: required(value.token, "OfferItem.token")?.into(),
+            identifierOrCriteria: value.identifier_or_criteria.unwrap_or_default().into(),
+            startAmount: required(value.start_amount, "OfferItem.start_amount")?.into(),
+            endAmount: required(value.end_amount, "OfferItem.end_amount")?.into(),
+        })
+    }
+}
+
+impl From<seaport::OfferItem> for OfferItem {
+    fn from(value: seaport::OfferItem) -> Self {
+        Self {
+            item_type: value.itemType.into(),
+            token: Some(value.token.into()),
+            identifier_or_criteria: Some(value.identifierOrCriteria.into()),
+            start_amount: Some(value.startAmount.into()),
+            end_amount: Some(value.endAmount.into()),
+        }
+    }
+}
+
+// A missing consideration token is treated as the zero address, i.e. the native token.
+impl TryFrom<&ConsiderationItem> for seaport::ConsiderationItem {
+    type Error = ConversionError;
+
+    fn try_from(value: &ConsiderationItem) -> Result<Self, Self::Error> {
+        Ok(Self {
+            itemType: item_type(value.item_type)?,
+            token: value.token.unwrap_or_default().into(),
+            identifierOrCriteria: value.identifier_or_criteria.unwrap_or_default().into(),
+            startAmount: required(value.start_amount, "ConsiderationItem.start_amount")?.into(),
+            endAmount: required(value.end_amount, "ConsiderationItem.end_amount")?.into(),
+            recipient: required(value.recipient, "ConsiderationItem.recipient")?.into(),
+        })
+    }
+}
+
+impl From<seaport::ConsiderationItem> for ConsiderationItem {
+    fn from(value: seaport::ConsiderationItem) -> Self {
+        Self {
+            item_type: value.itemType.into(),
+            token: Some(value.token.into()),
+            identifier_or_criteria: Some(value.identifierOrCriteria.into()),
+            start_amount: Some(value.startAmount.into()),
+            end_amount: Some(value.endAmount.into()),
+            recipient: Some(value.recipient.into()),
+        }
+    }
+}
+
+// The zone, zone hash and conduit key are optional and default to zero. The total original
+// consideration items is taken from the order as received.
+impl TryFrom<&Order> for seaport::OrderParameters {
+    type Error = ConversionError;
+
+    fn try_from(value: &Order) -> Result<Self, Self::Error> {
+        Ok(Self {
+            offerer: required(value.offerer, "Order.offerer")?.into(),
+            zone: value.zone.unwrap_or_default().into(),
+            offer: value
+                .offer
+                .iter()
+                .map(seaport::OfferItem::try_from)
+                .collect::<Result<_, _>>()?,
+            consideration: value
+                .consideration
+                .iter()
+                .map(seaport::ConsiderationItem::try_from)
+                .collect::<Result<_, _>>()?,
+            orderType: order_type(value.order_type)?,
+            startTime: required(value.start_time, "Order.start_time")?.into(),
+            endTime: required(value.end_time, "Order.end_time")?.into(),
+            zoneHash: value.zone_hash.unwrap_or_default().into(),
+            salt: required(value.salt, "Order.salt")?.into(),
+            conduitKey: value.conduit_key.unwrap_or_default().into(),
+            totalOriginalConsiderationItems: U256::from(value.consideration.len()),
+        })
+    }
+}
+
+impl From<seaport::OrderParameters> for Order {
+    fn from(value: seaport::OrderParameters) -> Self {
+        Self {
+            offerer: Some(value.offerer.into()),
+            zone: Some(value.zone.into()),
+            offer: value.offer.into_iter().map(Into::into).collect(),
+            consideration: value.consideration.into_iter().map(Into::into).collect(),
+            order_type: value.orderType.into(),
+            start_time: Some(value.startTime.into()),
+            end_time: Some(value.endTime.into()),
+            zone_hash: Some(value.zoneHash.into()),
+            salt: Some(value.salt.into()),
+            conduit_key: Some(value.conduitKey.into()),
+        }
+    }
+}
+
+impl TryFrom<&SignedOrder> for seaport::Order {
+    type Error = ConversionError;
+
+    fn try_from(value: &SignedOrder) -> Result<Self, Self::Error> {
+        let signature = PrimitiveSignature::try_from(required(
+            value.signature.as_ref(),
+            "SignedOrder.signature",
+        )?)?;
+
+        Ok(Self {
+            parameters: required(value.parameters.as_ref(), "SignedOrder.parameters")?
+                .try_into()?,
+            signature: Bytes::from(signature.as_bytes()),
+        })
+    }
+}
+
+// Both 65 byte and 64 byte EIP-2098 compact signatures are accepted, the signature is always
+// sent on as 65 bytes.
+impl TryFrom<seaport::Order> for SignedOrder {
+    type Error = ConversionError;
+
+    fn try_from(value: seaport::Order) -> Result<Self, Self::Error> {
+        let signature = PrimitiveSignature::try_from(&EthSignature {
+            r: value.signature.get(..32).unwrap_or_default().to_vec(),
+            s: value.signature.get(32..64).unwrap_or_default().to_vec(),
+            v: value.signature.get(64..).unwrap_or_default().to_vec(),
+        })?;
+
+        Ok(Self {
+            parameters: Some(value.parameters.into()),
+            signature: Some(signature.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R: [u8; 32] = [0x11; 32];
+    const S: [u8; 32] = [0x22; 32];
+
+    fn order() -> Order {
+        Order {
+            offerer: Some(H160::from([0x01; 20])),
+            zone: Some(H160::from([0x02; 20])),
+            offer: vec![OfferItem {
+                item_type: ItemType::Erc20 as i32,
+                token: Some(H160::from([0x03; 20])),
+                identifier_or_criteria: Some(H256::from(0u64)),
+                start_amount: Some(H256::from(1_000u64)),
+                end_amount: Some(H256::from(1_000u64)),
+            }],
+            consideration: vec![ConsiderationItem {
+                item_type: ItemType::Erc1155 as i32,
+                token: Some(H160::from([0x04; 20])),
+                identifier_or_criteria: Some(H256::from(7u64)),
+                start_amount: Some(H256::from(2u64)),
+                end_amount: Some(H256::from(2u64)),
+                recipient: Some(H160::from([0x01; 20])),
+            }],
+            order_type: OrderType::FullRestricted as i32,
+            start_time: Some(H256::from(1_700_000_000u64)),
+            end_time: Some(H256::from(1_700_001_200u64)),
+            zone_hash: Some(H256::from([0x05; 32])),
+            salt: Some(H256::from(42u64)),
+            conduit_key: Some(H256::from([0x06; 32])),
+        }
+    }
+
+    fn signed_order(r: [u8; 32], s: [u8; 32], v: &[u8]) -> SignedOrder {
+        SignedOrder {
+            parameters: Some(order()),
+            signature: Some(EthSignature {
+                r: r.to_vec(),
+                s: s.to_vec(),
+                v: v.to_vec(),
+            }),
+        }
+    }
+
+    // The top bit of `vs` in a compact signature is the y parity.
+    fn vs() -> [u8; 32] {
+        let mut vs = S;
+        vs[0] |= 0x80;
+        vs
+    }
+
+    #[test]
+    fn order_round_trip() {
+        let order = order();
+        let parameters = seaport::OrderParameters::try_from(&order).unwrap();
+        assert_eq!(parameters.totalOriginalConsiderationItems, U256::from(1));
+        assert_eq!(Order::from(parameters), order);
+    }
+
+    #[test]
+    fn signed_order_round_trip() {
+        for v in [27, 28] {
+            let signed = signed_order(R, S, &[v]);
+            let seaport_order = seaport::Order::try_from(&signed).unwrap();
+            assert_eq!(
+                seaport_order.signature.to_vec(),
+                [&R[..], &S, &[v]].concat()
+            );
+            assert_eq!(SignedOrder::try_from(seaport_order).unwrap(), signed);
+        }
+    }
+
+    #[test]
+    fn signatures_are_sent_as_65_bytes() {
+        let expected = [&R[..], &S, &[28]].concat();
+        for signed in [signed_order(R, vs(), &[]), signed_order(R, S, &[1])] {
+            let seaport_order = seaport::Order::try_from(&signed).unwrap();
+            assert_eq!(seaport_order.signature.to_vec(), expected);
+            assert_eq!(
+                SignedOrder::try_from(seaport_order).unwrap(),
+                signed_order(R, S, &[28])
+            );
+        }
+
+        let compact = seaport::Order {
+            parameters: seaport::OrderParameters::try_from(&order()).unwrap(),
+            signature: Bytes::from([R, vs()].concat()),
+        };
+        assert_eq!(
+            SignedOrder::try_from(compact).unwrap(),
+            signed_order(R, S, &[28])
+        );
+    }
+
+    #[test]
+    fn malformed_signatures() {
+        for length in [0, 63, 66] {
+            let order = seaport::Order {
+                parameters: seaport::OrderParameters::try_from(&order()).unwrap(),
+                signature: Bytes::from(vec![0x1b; length]),
+            };
+            assert!(SignedOrder::try_from(order).is_err(), "{length} bytes");
+        }
+
+        assert!(seaport::Order::try_from(&signed_order(R, S, &[2])).is_err());
+    }
+
+    // Both adapters pass the same signature bytes to Seaport, whatever the form received.
+    #[cfg(feature = "ethers")]
+    #[test]
+    fn matches_ethers_adapters() {
+        use crate::bindings::seaport as ethers_seaport;
+
+        for signed in [
+            signed_order(R, S, &[0]),
+            signed_order(R, S, &[1]),
+            signed_order(R, S, &[27]),
+            signed_order(R, S, &[28]),
+            signed_order(R, S, &[]),
+            signed_order(R, vs(), &[]),
+        ] {
+            let alloy_order = seaport::Order::try_from(&signed).unwrap();
+            let ethers_order = ethers_seaport::Order::try_from(&signed).unwrap();
+            assert_eq!(
+                alloy_order.signature.to_vec(),
+                ethers_order.signature.to_vec()
+            );
+            assert_eq!(
+                SignedOrder::try_from(alloy_order).unwrap(),
+                SignedOrder::try_from(ethers_order).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use ethers::contract::abigen;
+
+abigen!(
+    Erc1271,
+    "../abi/IERC1271.json",
+    derives(serde::Deserialize, serde::Serialize)
+);
@@ -1,4 +1,5 @@
 pub mod erc1155;
+pub mod erc1271;
 pub mod erc20;
 pub mod seaport;
 pub mod seaport_counduit_controller;
@@ -0,0 +1,145 @@
+// Registry of the chains supported by the Valorem Trade API, and the contracts the API expects
+// to be used on each of them. See the "Trading Valorem Clear Options" section of the repository
+// README for the addresses.
+use crate::grpc_adapters::ConversionError;
+use crate::grpc_codegen::{H160, H256};
+use arrayref::array_ref;
+use std::fmt;
+
+/// A chain supported by the Valorem Trade API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Chain {
+    ArbitrumOne,
+    ArbitrumSepolia,
+}
+
+impl Chain {
+    /// Every supported chain.
+    pub const ALL: [Chain; 2] = [Chain::ArbitrumOne, Chain::ArbitrumSepolia];
+
+    /// The EIP-155 chain id.
+    pub const fn id(&self) -> u64 {
+        match self {
+            Chain::ArbitrumOne => 42161,
+            Chain::ArbitrumSepolia => 421614,
+        }
+    }
+
+    /// The supported chain with the EIP-155 `id`, if any.
+    pub fn from_id(id: u64) -> Option<Self> {
+        Self::ALL.into_iter().find(|chain| chain.id() == id)
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Chain::ArbitrumOne => "Arbitrum One",
+            Chain::ArbitrumSepolia => "Arbitrum Sepolia",
+        }
+    }
+
+    pub const fn is_testnet(&self) -> bool {
+        matches!(self, Chain::ArbitrumSepolia)
+    }
+
+    /// The Valorem Clear contract, whose ERC-1155 option tokens are traded via the RFQ.
+    pub const fn clear_address(&self) -> H160 {
+        // Deployed to the same address on every supported chain.
+        H160([
+            0x40, 0x2a, 0x40, 0x1b, 0x19, 0x44, 0xeb, 0xb5, 0xa3, 0x03, 0x0f, 0x36, 0xaa, 0x70,
+            0xd6, 0xb5, 0x79, 0x41, 0x90, 0xc9,
+        ])
+    }
+
+    /// The Seaport contract orders are settled on.
+    pub const fn seaport_address(&self, version: SeaportVersion) -> H160 {
+        version.address()
+    }
+
+    /// The USDC token premiums and fees are paid in. This is bridged USDC.e on Arbitrum One, and
+    /// a token with an open mint on testnets.
+    pub const fn usdc_address(&self) -> H160 {
+        match self {
+            Chain::ArbitrumOne => H160([
+                0xff, 0x97, 0x0a, 0x61, 0xa0, 0x4b, 0x1c, 0xa1, 0x48, 0x34, 0xa4, 0x3f, 0x5d, 0xe4,
+                0x53, 0x3e, 0xbd, 0xdb, 0x5c, 0xc8,
+            ]),
+            Chain::ArbitrumSepolia => H160([
+                0x8a, 0xe0, 0xee, 0xed, 0xd3, 0x5d, 0xbe, 0xfe, 0x46, 0x0d, 0xf1, 0x2a, 0x20, 0x82,
+                0x3e, 0xfd, 0xe9, 0xe0, 0x34, 0x58,
+            ]),
+        }
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.id())
+    }
+}
+
+impl TryFrom<u64> for Chain {
+    type Error = ConversionError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Self::from_id(value).ok_or(ConversionError::OutOfRange {
+            field: "chain_id",
+            value: value.to_string(),
+        })
+    }
+}
+
+// Chain ids are sent as an `H256` on the wire.
+impl TryFrom<H256> for Chain {
+    type Error = ConversionError;
+
+    fn try_from(value: H256) -> Result<Self, Self::Error> {
+        let out_of_range = || ConversionError::OutOfRange {
+            field: "chain_id",
+            value: format!("{value:#x}"),
+        };
+
+        if value.0[..24].iter().any(|byte| *byte != 0) {
+            return Err(out_of_range());
+        }
+        Self::from_id(u64::from_be_bytes(*array_ref!(value.0, 24, 8))).ok_or_else(out_of_range)
+    }
+}
+
+impl From<Chain> for H256 {
+    fn from(value: Chain) -> Self {
+        H256::from(value.id())
+    }
+}
+
+/// A Seaport release supported by the Valorem Trade API.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SeaportVersion {
+    #[default]
+    V1_5,
+}
+
+impl SeaportVersion {
+    /// The version, as used in the EIP-712 domain.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            SeaportVersion::V1_5 => "1.5",
+        }
+    }
+
+    /// The address the release is deployed at, which is the same on every chain.
+    pub const fn address(&self) -> H160 {
+        match self {
+            SeaportVersion::V1_5 => H160([
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xad, 0xc0, 0x4c, 0x56, 0xbf, 0x30, 0xac,
+                0x9d, 0x3c, 0x0a, 0xaf, 0x14, 0xdc,
+            ]),
+        }
+    }
+}
+
+impl fmt::Display for SeaportVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Seaport {}", self.as_str())
+    }
+}
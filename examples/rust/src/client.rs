@@ -0,0 +1,825 @@
+// An authenticated connection to the Valorem Trade API.
+//
+// Authentication follows the "Authentication" section of the repository README: a nonce is
+// fetched from `Auth::Nonce`, which also starts the session and returns its cookie, a Sign-In with
+// Ethereum (EIP-4361) message containing the nonce is signed and sent to `Auth::Verify`, and the
+// session is then checked with `Auth::Authenticate`. The message is built from a `SiweConfig`, and
+// the address returned by `Auth::Verify` and the `SiweSession` of the new session are checked
+// against the signer. All of the service clients handed out by `ValoremClient` share one channel
+// and send the session cookie with every request.
+//
+// A session ends when it expires or `Auth::SignOut` is called, after which every call fails with
+// `Unauthenticated`. `ValoremClient::spawn_session_refresh` starts a task which signs in again when
+// that happens and swaps the cookie of the shared `Session`, so the existing clients and any live
+// streams are kept. When the session cookie carries an expiry, the task signs in again shortly
+// before the session expires rather than waiting for calls to fail.
+//
+// With a `SessionStore`, each new session is saved along with its `SiweSession`, and a stored
+// session is resumed on connecting if `Auth::Authenticate` and `Auth::Session` show it is still
+// valid for the signer, so a restart does not need to sign in again.
+use crate::error::ValoremError;
+use crate::grpc_adapters::{required, ConversionError};
+use crate::grpc_codegen::{
+    auth_client::AuthClient, fees_client::FeesClient, rfq_client::RfqClient,
+    soft_quote_client::SoftQuoteClient, spot_client::SpotClient, Empty, VerifyText,
+};
+use crate::session_store::{SessionStore, SessionStoreError, StoredSession};
+use crate::siwe_config::{SiweConfig, SiweError};
+use crate::utils::cookie::{CookieError, SessionCookie};
+use crate::utils::session_interceptor::{Session, SessionInterceptor};
+use ethers::signers::Signer;
+use ethers::types::{Address, U256};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tonic::codegen::InterceptedService;
+use tonic::transport::{Channel, ClientTlsConfig, Uri};
+
+const SESSION_COOKIE_KEY: &str = "set-cookie";
+
+// How long before the session expires the refresh task signs in again, at most. Short sessions
+// are renewed once three quarters of their lifetime has passed instead.
+const SESSION_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+// The least time between sign ins by the refresh task, so a server issuing sessions which expire
+// straight away is not flooded with sign ins.
+const MIN_SESSION_REFRESH: Duration = Duration::from_secs(5);
+
+/// A channel which sends the session cookie with every request.
+pub type SessionChannel = InterceptedService<Channel, SessionInterceptor>;
+
+/// An error returned when connecting or authenticating with Valorem.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The channel to the endpoint could not be configured or established.
+    Transport(tonic::transport::Error),
+    /// A call to the Auth service failed.
+    Api(ValoremError),
+    /// The nonce response did not start a session.
+    MissingSessionCookie,
+    /// The session cookie returned in the nonce response could not be parsed.
+    InvalidSessionCookie(CookieError),
+    /// The SIWE message could not be signed.
+    Signing(Box<dyn std::error::Error + Send + Sync>),
+    /// The SIWE message was invalid, or the server authenticated another address or chain.
+    Siwe(SiweError),
+    /// The `SiweSession` returned by the server was incomplete.
+    Conversion(ConversionError),
+    /// The new session could not be saved to the session store.
+    SessionStore(SessionStoreError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Transport(error) => write!(f, "Unable to connect to Valorem: {error}"),
+            ClientError::Api(error) => write!(f, "Unable to authenticate with Valorem: {error}"),
+            ClientError::MissingSessionCookie => {
+                write!(f, "Session cookie was not returned in the Nonce response")
+            }
+            ClientError::InvalidSessionCookie(error) => {
+                write!(f, "Invalid session cookie in the Nonce response: {error}")
+            }
+            ClientError::Signing(error) => write!(f, "Unable to sign the SIWE message: {error}"),
+            ClientError::Siwe(error) => write!(f, "Unable to sign in: {error}"),
+            ClientError::Conversion(error) => write!(f, "Invalid SIWE session: {error}"),
+            ClientError::SessionStore(error) => write!(f, "Unable to store the session: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<tonic::transport::Error> for ClientError {
+    fn from(value: tonic::transport::Error) -> Self {
+        ClientError::Transport(value)
+    }
+}
+
+impl From<tonic::Status> for ClientError {
+    fn from(value: tonic::Status) -> Self {
+        ClientError::Api(value.into())
+    }
+}
+
+impl From<ConversionError> for ClientError {
+    fn from(value: ConversionError) -> Self {
+        ClientError::Conversion(value)
+    }
+}
+
+impl From<SiweError> for ClientError {
+    fn from(value: SiweError) -> Self {
+        ClientError::Siwe(value)
+    }
+}
+
+impl From<SessionStoreError> for ClientError {
+    fn from(value: SessionStoreError) -> Self {
+        ClientError::SessionStore(value)
+    }
+}
+
+/// A connection to Valorem with an authenticated session.
+#[derive(Clone, Debug)]
+pub struct ValoremClient {
+    channel: Channel,
+    session: Session,
+    address: Address,
+    unauthenticated: Arc<Notify>,
+    siwe: SiweConfig,
+    store: Option<StoreKey>,
+}
+
+// The store new sessions are saved to, and the endpoint they are saved under.
+#[derive(Clone, Debug)]
+struct StoreKey {
+    store: SessionStore,
+    endpoint: Uri,
+}
+
+impl ValoremClient {
+    /// Connects to the Valorem Trade API at `endpoint` and signs in as `signer`, on the chain
+    /// given by the chain id of `signer`, with the default SIWE message for `endpoint`.
+    pub async fn connect<S>(
+        endpoint: Uri,
+        tls_config: ClientTlsConfig,
+        signer: &S,
+    ) -> Result<Self, ClientError>
+    where
+        S: Signer,
+        S::Error: 'static,
+    {
+        let siwe = SiweConfig::for_endpoint(&endpoint);
+        let channel = open_channel(endpoint, tls_config).await?;
+        Self::connect_with_channel(channel, signer, siwe).await
+    }
+
+    /// Connects to the Valorem Trade API at `endpoint` as `signer`, resuming the session stored
+    /// for them in `store` if it is still valid and otherwise signing in. New sessions, including
+    /// those from `sign_in`, are saved to `store`.
+    pub async fn connect_with_store<S>(
+        endpoint: Uri,
+        tls_config: ClientTlsConfig,
+        signer: &S,
+        store: SessionStore,
+    ) -> Result<Self, ClientError>
+    where
+        S: Signer,
+        S::Error: 'static,
+    {
+        let siwe = SiweConfig::for_endpoint(&endpoint);
+        let channel = open_channel(endpoint.clone(), tls_config).await?;
+        Self::connect_with_channel_and_store(channel, endpoint, signer, store, siwe).await
+    }
+
+    /// Signs in as `signer` over an existing `channel`, e.g. one with a custom configuration,
+    /// with the SIWE message given by `siwe`.
+    pub async fn connect_with_channel<S>(
+        channel: Channel,
+        signer: &S,
+        siwe: SiweConfig,
+    ) -> Result<Self, ClientError>
+    where
+        S: Signer,
+        S::Error: 'static,
+    {
+        let client = Self::new(channel, signer, siwe, None);
+        client.sign_in(signer).await?;
+
+        Ok(client)
+    }
+
+    /// As `connect_with_store`, over an existing `channel` to `endpoint` and with the SIWE message
+    /// given by `siwe`.
+    pub async fn connect_with_channel_and_store<S>(
+        channel: Channel,
+        endpoint: Uri,
+        signer: &S,
+        store: SessionStore,
+        siwe: SiweConfig,
+    ) -> Result<Self, ClientError>
+    where
+        S: Signer,
+        S::Error: 'static,
+    {
+        let client = Self::new(channel, signer, siwe, Some(StoreKey { store, endpoint }));
+        if !client.resume(signer).await {
+            client.sign_in(signer).await?;
+        }
+
+        Ok(client)
+    }
+
+    fn new<S: Signer>(
+        channel: Channel,
+        signer: &S,
+        siwe: SiweConfig,
+        store: Option<StoreKey>,
+    ) -> Self {
+        Self {
+            channel,
+            session: Session::default(),
+            address: signer.address(),
+            unauthenticated: Arc::new(Notify::new()),
+            siwe,
+            store,
+        }
+    }
+
+    // Resumes the stored session, returning whether it is still authenticated for the address and
+    // chain id of `signer`. A session which cannot be loaded is treated as missing, as signing in
+    // replaces it.
+    async fn resume<S: Signer>(&self, signer: &S) -> bool {
+        let stored = match &self.store {
+            Some(StoreKey { store, endpoint }) => match store.load(endpoint, self.address) {
+                Ok(Some(stored)) => stored,
+                _ => return false,
+            },
+            None => return false,
+        };
+        if stored.chain_id != U256::from(signer.chain_id())
+            || stored.cookie.is_expired_at(SystemTime::now())
+        {
+            return false;
+        }
+
+        let session = Session::new(stored.cookie.clone());
+        let mut auth = AuthClient::with_interceptor(self.channel(), SessionInterceptor { session });
+        if auth.authenticate(Empty::default()).await.is_err() {
+            return false;
+        }
+        let siwe_session = match auth.session(Empty::default()).await {
+            Ok(siwe_session) => siwe_session.into_inner(),
+            Err(_) => return false,
+        };
+        if siwe_session.address != Some(self.address.into())
+            || siwe_session.chain_id != Some(stored.chain_id.into())
+        {
+            return false;
+        }
+
+        self.session.set_cookie(stored.cookie);
+        true
+    }
+
+    /// Signs in as `signer` again, replacing the cookie of the session once the new session is
+    /// authenticated. Note a new nonce ends the current session.
+    pub async fn sign_in<S>(&self, signer: &S) -> Result<(), ClientError>
+    where
+        S: Signer,
+        S::Error: 'static,
+    {
+        // The nonce response starts the session, so it carries the cookie for all later requests.
+        let response = AuthClient::new(self.channel())
+            .nonce(Empty::default())
+            .await?;
+        let session_cookie = response
+            .metadata()
+            .get(SESSION_COOKIE_KEY)
+            .and_then(|session_cookie| session_cookie.to_str().ok())
+            .ok_or(ClientError::MissingSessionCookie)?
+            .parse::<SessionCookie>()
+            .map_err(ClientError::InvalidSessionCookie)?;
+        let nonce = response.into_inner().nonce;
+
+        let message = self
+            .siwe
+            .message(signer.address(), signer.chain_id(), &nonce)?
+            .to_string();
+        let signature = signer
+            .sign_message(message.as_bytes())
+            .await
+            .map_err(|error| ClientError::Signing(Box::new(error)))?;
+
+        let body = serde_json::json!({
+            "signature": signature.to_string(),
+            "message": message,
+        })
+        .to_string();
+
+        // The new session is only shared with the other clients once it is authenticated.
+        let session = Session::new(session_cookie.clone());
+        let mut auth = AuthClient::with_interceptor(self.channel(), SessionInterceptor { session });
+        let verified = auth.verify(VerifyText { body }).await?.into_inner();
+        check_address(signer.address(), verified.into())?;
+        auth.authenticate(Empty::default()).await?;
+
+        // The session the server holds must be for the address and chain we signed in with.
+        let siwe_session = auth.session(Empty::default()).await?.into_inner();
+        let stored = StoredSession {
+            cookie: session_cookie.clone(),
+            address: required(siwe_session.address, "SiweSession.address")?.into(),
+            chain_id: required(siwe_session.chain_id, "SiweSession.chain_id")?.into(),
+        };
+        check_address(signer.address(), stored.address)?;
+        if stored.chain_id != U256::from(signer.chain_id()) {
+            return Err(SiweError::ChainMismatch {
+                expected: signer.chain_id().into(),
+                actual: stored.chain_id,
+            }
+            .into());
+        }
+
+        if let Some(StoreKey { store, endpoint }) = &self.store {
+            store.save(endpoint, &stored)?;
+        }
+
+        self.session.set_cookie(session_cookie);
+        Ok(())
+    }
+
+    /// Reports a status returned by one of the clients, waking the session refresh task if the
+    /// session is no longer authenticated. Returns whether the session is no longer authenticated.
+    pub fn report_status(&self, status: &tonic::Status) -> bool {
+        let unauthenticated = is_unauthenticated(status);
+        if unauthenticated {
+            self.unauthenticated.notify_one();
+        }
+
+        unauthenticated
+    }
+
+    /// Starts a task which signs in again as `signer` whenever the session is no longer
+    /// authenticated, as found by checking `Auth::Authenticate` every `interval` or as reported
+    /// with `report_status`, or when the session cookie is about to expire. The task is stopped
+    /// when the returned handle is dropped.
+    pub fn spawn_session_refresh<S>(&self, signer: S, interval: Duration) -> SessionRefresh
+    where
+        S: Signer + 'static,
+        S::Error: 'static,
+    {
+        let client = self.clone();
+        SessionRefresh(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = client.unauthenticated.notified() => {}
+                    _ = tokio::time::sleep(client.next_session_check(interval)) => {
+                        // A session about to expire is renewed without checking it first.
+                        if !client.session_expiring() {
+                            match client.auth().authenticate(Empty::default()).await {
+                                Err(status) if is_unauthenticated(&status) => {}
+                                _ => continue,
+                            }
+                        }
+                    }
+                }
+
+                // A failed sign in is retried after the next interval.
+                match client.sign_in(&signer).await {
+                    Ok(()) => tokio::time::sleep(MIN_SESSION_REFRESH).await,
+                    Err(_) => tokio::time::sleep(interval.max(MIN_SESSION_REFRESH)).await,
+                }
+            }
+        }))
+    }
+
+    /// The address the session is authenticated as.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// The fields of the SIWE message signed when signing in.
+    pub fn siwe_config(&self) -> &SiweConfig {
+        &self.siwe
+    }
+
+    /// The session shared by all of the clients.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// The channel shared by all of the clients, without the session cookie.
+    pub fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    pub fn auth(&self) -> AuthClient<SessionChannel> {
+        AuthClient::with_interceptor(self.channel(), self.interceptor())
+    }
+
+    pub fn rfq(&self) -> RfqClient<SessionChannel> {
+        RfqClient::with_interceptor(self.channel(), self.interceptor())
+    }
+
+    pub fn soft_quote(&self) -> SoftQuoteClient<SessionChannel> {
+        SoftQuoteClient::with_interceptor(self.channel(), self.interceptor())
+    }
+
+    pub fn fees(&self) -> FeesClient<SessionChannel> {
+        FeesClient::with_interceptor(self.channel(), self.interceptor())
+    }
+
+    pub fn spot(&self) -> SpotClient<SessionChannel> {
+        SpotClient::with_interceptor(self.channel(), self.interceptor())
+    }
+
+    // The time until the session should next be checked, which is at most `interval` and no later
+    // than the margin before the session expires.
+    fn next_session_check(&self, interval: Duration) -> Duration {
+        match self.renew_at() {
+            Some(renew_at) => renew_at
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+                .min(interval),
+            None => interval,
+        }
+    }
+
+    // Whether the session expires within the margin, and so should be renewed.
+    fn session_expiring(&self) -> bool {
+        self.renew_at()
+            .is_some_and(|renew_at| renew_at <= SystemTime::now())
+    }
+
+    // When the session should be renewed, a quarter of its lifetime before it expires and at most
+    // `SESSION_EXPIRY_MARGIN` before.
+    fn renew_at(&self) -> Option<SystemTime> {
+        let expires_at = self.session.expires_at()?;
+        let margin = self
+            .session
+            .lifetime()
+            .map_or(SESSION_EXPIRY_MARGIN, |lifetime| {
+                (lifetime / 4).min(SESSION_EXPIRY_MARGIN)
+            });
+        Some(expires_at.checked_sub(margin).unwrap_or(expires_at))
+    }
+
+    fn interceptor(&self) -> SessionInterceptor {
+        SessionInterceptor {
+            session: self.session.clone(),
+        }
+    }
+}
+
+/// The task started by `ValoremClient::spawn_session_refresh`, which is stopped when dropped.
+#[derive(Debug)]
+pub struct SessionRefresh(JoinHandle<()>);
+
+impl Drop for SessionRefresh {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+async fn open_channel(
+    endpoint: Uri,
+    tls_config: ClientTlsConfig,
+) -> Result<Channel, tonic::transport::Error> {
+    Channel::builder(endpoint)
+        .tls_config(tls_config)?
+        .http2_keep_alive_interval(Duration::from_secs(75))
+        .keep_alive_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(10))
+        .connect_timeout(Duration::from_secs(10))
+        .connect()
+        .await
+}
+
+fn check_address(expected: Address, actual: Address) -> Result<(), SiweError> {
+    if actual != expected {
+        return Err(SiweError::AddressMismatch { expected, actual });
+    }
+
+    Ok(())
+}
+
+fn is_unauthenticated(status: &tonic::Status) -> bool {
+    status.code() == tonic::Code::Unauthenticated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc_codegen::auth_server::{Auth, AuthServer};
+    use crate::grpc_codegen::{NonceText, SiweSession, H160};
+    use ethers::signers::LocalWallet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::UNIX_EPOCH;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::ReceiverStream;
+    use tonic::transport::Server;
+    use tonic::{Request, Response, Status};
+
+    // An Auth service which verifies any message, returning the address and session given. Each
+    // nonce starts a session with the cookie `id=session<n>` and the cookie attributes given.
+    #[derive(Clone, Debug)]
+    struct MockAuth {
+        verified: Address,
+        session: SiweSession,
+        cookie_attributes: &'static str,
+        nonces: Arc<AtomicUsize>,
+    }
+
+    impl MockAuth {
+        fn new(verified: Address, session: SiweSession) -> Self {
+            Self {
+                verified,
+                session,
+                cookie_attributes: "Path=/; HttpOnly",
+                nonces: Arc::default(),
+            }
+        }
+
+        fn nonces(&self) -> usize {
+            self.nonces.load(Ordering::SeqCst)
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Auth for MockAuth {
+        async fn nonce(&self, _: Request<Empty>) -> Result<Response<NonceText>, Status> {
+            let nonce = self.nonces.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut response = Response::new(NonceText {
+                nonce: "abcdefgh12345678".into(),
+            });
+            response.metadata_mut().insert(
+                SESSION_COOKIE_KEY,
+                format!("id=session{nonce}; {}", self.cookie_attributes)
+                    .parse()
+                    .unwrap(),
+            );
+            Ok(response)
+        }
+
+        async fn verify(&self, _: Request<VerifyText>) -> Result<Response<H160>, Status> {
+            Ok(Response::new(self.verified.into()))
+        }
+
+        async fn authenticate(&self, request: Request<Empty>) -> Result<Response<H160>, Status> {
+            match request
+                .metadata()
+                .get("cookie")
+                .map(|cookie| cookie.to_str())
+            {
+                Some(Ok(cookie)) if cookie.starts_with("id=session") => {
+                    Ok(Response::new(self.verified.into()))
+                }
+                _ => Err(Status::unauthenticated("unknown session")),
+            }
+        }
+
+        async fn geofenced(&self, _: Request<Empty>) -> Result<Response<bool>, Status> {
+            Ok(Response::new(false))
+        }
+
+        async fn session(&self, _: Request<Empty>) -> Result<Response<SiweSession>, Status> {
+            Ok(Response::new(self.session.clone()))
+        }
+
+        async fn sign_out(&self, _: Request<Empty>) -> Result<Response<Empty>, Status> {
+            Ok(Response::new(Empty::default()))
+        }
+    }
+
+    // Serves `auth` on a local port, returning a channel to it and its endpoint.
+    async fn serve(auth: MockAuth) -> (Channel, Uri) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint: Uri = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(async move {
+            while sender
+                .send(listener.accept().await.map(|(stream, _)| stream))
+                .await
+                .is_ok()
+            {}
+        });
+        tokio::spawn(
+            Server::builder()
+                .add_service(AuthServer::new(auth))
+                .serve_with_incoming(ReceiverStream::new(receiver)),
+        );
+
+        let channel = Channel::builder(endpoint.clone()).connect().await.unwrap();
+        (channel, endpoint)
+    }
+
+    // Signs in as `wallet` against `auth`, returning the client whatever the result.
+    async fn sign_in(
+        auth: MockAuth,
+        wallet: &LocalWallet,
+    ) -> (ValoremClient, Result<(), ClientError>) {
+        let (channel, endpoint) = serve(auth).await;
+        let client = ValoremClient::new(channel, wallet, SiweConfig::for_endpoint(&endpoint), None);
+        let result = client.sign_in(wallet).await;
+        (client, result)
+    }
+
+    // Connects as `wallet` against `auth`, resuming the session stored in `store` if possible.
+    async fn connect_with_store(
+        auth: MockAuth,
+        wallet: &LocalWallet,
+        store: &SessionStore,
+    ) -> ValoremClient {
+        let (channel, endpoint) = serve(auth).await;
+        let siwe = SiweConfig::for_endpoint(&endpoint);
+        ValoremClient::connect_with_channel_and_store(
+            channel,
+            "https://trade.valorem.xyz".parse().unwrap(),
+            wallet,
+            store.clone(),
+            siwe,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn store(name: &str) -> SessionStore {
+        SessionStore::new(
+            std::env::temp_dir().join(format!("valorem-client-{name}-{}", std::process::id())),
+        )
+    }
+
+    fn wallet() -> LocalWallet {
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(421_613u64)
+    }
+
+    fn session(address: Address, chain_id: u64) -> SiweSession {
+        SiweSession {
+            address: Some(address.into()),
+            chain_id: Some(U256::from(chain_id).into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn signs_in() {
+        let wallet = wallet();
+        let auth = MockAuth::new(wallet.address(), session(wallet.address(), 421_613));
+        let (client, result) = sign_in(auth, &wallet).await;
+        result.unwrap();
+        assert_eq!(
+            client.session().cookie().unwrap().header_value(),
+            "id=session1"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_another_verified_address() {
+        let wallet = wallet();
+        let other = Address::repeat_byte(0x11);
+        let auth = MockAuth::new(other, session(wallet.address(), 421_613));
+        let (client, result) = sign_in(auth, &wallet).await;
+        match result {
+            Err(ClientError::Siwe(SiweError::AddressMismatch { expected, actual })) => {
+                assert_eq!((expected, actual), (wallet.address(), other))
+            }
+            result => panic!("unexpected {result:?}"),
+        }
+        assert!(client.session().cookie().is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_a_session_for_another_address() {
+        let wallet = wallet();
+        let other = Address::repeat_byte(0x11);
+        let auth = MockAuth::new(wallet.address(), session(other, 421_613));
+        let (client, result) = sign_in(auth, &wallet).await;
+        match result {
+            Err(ClientError::Siwe(SiweError::AddressMismatch { expected, actual })) => {
+                assert_eq!((expected, actual), (wallet.address(), other))
+            }
+            result => panic!("unexpected {result:?}"),
+        }
+        assert!(client.session().cookie().is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_a_session_on_another_chain() {
+        let wallet = wallet();
+        let auth = MockAuth::new(wallet.address(), session(wallet.address(), 1));
+        let (client, result) = sign_in(auth, &wallet).await;
+        match result {
+            Err(ClientError::Siwe(SiweError::ChainMismatch { expected, actual })) => {
+                assert_eq!((expected, actual), (U256::from(421_613), U256::one()))
+            }
+            result => panic!("unexpected {result:?}"),
+        }
+        assert!(client.session().cookie().is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_an_incomplete_session() {
+        let wallet = wallet();
+        let auth = MockAuth::new(
+            wallet.address(),
+            SiweSession {
+                address: Some(wallet.address().into()),
+                chain_id: None,
+            },
+        );
+        let (client, result) = sign_in(auth, &wallet).await;
+        assert!(matches!(result, Err(ClientError::Conversion(_))));
+        assert!(client.session().cookie().is_none());
+    }
+
+    #[tokio::test]
+    async fn renews_short_sessions_before_they_expire() {
+        let wallet = wallet();
+        let (channel, endpoint) = serve(MockAuth::new(
+            wallet.address(),
+            session(wallet.address(), 421_613),
+        ))
+        .await;
+        let client =
+            ValoremClient::new(channel, &wallet, SiweConfig::for_endpoint(&endpoint), None);
+        let interval = Duration::from_secs(3_600);
+
+        // A session lasting less than the margin is renewed after three quarters of its lifetime.
+        client
+            .session()
+            .set_cookie("id=session; Max-Age=20".parse().unwrap());
+        let next_check = client.next_session_check(interval);
+        assert!(next_check > Duration::from_secs(14) && next_check <= Duration::from_secs(15));
+        assert!(!client.session_expiring());
+
+        // A longer session is renewed the margin before it expires.
+        client
+            .session()
+            .set_cookie("id=session; Max-Age=600".parse().unwrap());
+        let next_check = client.next_session_check(interval);
+        assert!(next_check > Duration::from_secs(539) && next_check <= Duration::from_secs(540));
+        assert!(!client.session_expiring());
+
+        client.session().set_cookie("id=session".parse().unwrap());
+        assert_eq!(client.next_session_check(interval), interval);
+        assert!(!client.session_expiring());
+    }
+
+    #[tokio::test]
+    async fn does_not_sign_in_repeatedly_for_sessions_which_expire_immediately() {
+        let wallet = wallet();
+        let mut auth = MockAuth::new(wallet.address(), session(wallet.address(), 421_613));
+        auth.cookie_attributes = "Max-Age=0";
+        let (client, result) = sign_in(auth.clone(), &wallet).await;
+        result.unwrap();
+        assert!(client.session_expiring());
+
+        let _refresh = client.spawn_session_refresh(wallet, Duration::from_secs(3_600));
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(auth.nonces(), 2);
+    }
+
+    #[tokio::test]
+    async fn resumes_a_stored_session() {
+        let wallet = wallet();
+        let store = store("resume");
+        let auth = MockAuth::new(wallet.address(), session(wallet.address(), 421_613));
+
+        let client = connect_with_store(auth.clone(), &wallet, &store).await;
+        assert_eq!(auth.nonces(), 1);
+        let cookie = client.session().cookie().unwrap();
+
+        // The stored session is still authenticated, so it is resumed without a new nonce.
+        let client = connect_with_store(auth.clone(), &wallet, &store).await;
+        assert_eq!(auth.nonces(), 1);
+        assert_eq!(client.session().cookie(), Some(cookie));
+
+        std::fs::remove_dir_all(store.directory()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn signs_in_when_the_stored_session_cannot_be_resumed() {
+        let wallet = wallet();
+        let store = store("stale");
+        let endpoint: Uri = "https://trade.valorem.xyz".parse().unwrap();
+        let auth = MockAuth::new(wallet.address(), session(wallet.address(), 421_613));
+        let stored = |cookie: SessionCookie| StoredSession {
+            cookie,
+            address: wallet.address(),
+            chain_id: U256::from(421_613),
+        };
+
+        // A session the server no longer authenticates.
+        store
+            .save(&endpoint, &stored("id=stale".parse().unwrap()))
+            .unwrap();
+        let client = connect_with_store(auth.clone(), &wallet, &store).await;
+        assert_eq!(auth.nonces(), 1);
+        assert_eq!(
+            client.session().cookie().unwrap().header_value(),
+            "id=session1"
+        );
+        let resumed = store.load(&endpoint, wallet.address()).unwrap().unwrap();
+        assert_eq!(resumed.cookie.header_value(), "id=session1");
+
+        // An expired session.
+        let mut expired: SessionCookie = "id=session1".parse().unwrap();
+        expired.expires_at = Some(UNIX_EPOCH);
+        store.save(&endpoint, &stored(expired)).unwrap();
+        let client = connect_with_store(auth.clone(), &wallet, &store).await;
+        assert_eq!(auth.nonces(), 2);
+        assert_eq!(
+            client.session().cookie().unwrap().header_value(),
+            "id=session2"
+        );
+
+        std::fs::remove_dir_all(store.directory()).unwrap();
+    }
+}
@@ -0,0 +1,378 @@
+// Typed errors for the Valorem Trade API.
+//
+// The API uses the gRPC richer error model, where a `google.rpc.Status` containing additional
+// error details is attached to the binary `grpc-status-details-bin` trailer. `ValoremError`
+// decodes these details from a `tonic::Status` so clients can react to them programmatically.
+use crate::rpc_codegen::{
+    self, BadRequest, DebugInfo, ErrorInfo, Help, LocalizedMessage, PreconditionFailure,
+    QuotaFailure, RequestInfo, ResourceInfo, RetryInfo,
+};
+use prost::Message;
+use std::fmt;
+use std::time::Duration;
+use tonic::Code;
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+/// A single decoded `google.rpc` error detail.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorDetail {
+    ErrorInfo(ErrorInfo),
+    RetryInfo(RetryInfo),
+    DebugInfo(DebugInfo),
+    QuotaFailure(QuotaFailure),
+    PreconditionFailure(PreconditionFailure),
+    BadRequest(BadRequest),
+    RequestInfo(RequestInfo),
+    ResourceInfo(ResourceInfo),
+    Help(Help),
+    LocalizedMessage(LocalizedMessage),
+    /// A detail with an unrecognised type url, or one which failed to decode.
+    Unknown(prost_types::Any),
+}
+
+impl ErrorDetail {
+    fn decode(any: prost_types::Any) -> Self {
+        let type_name = any
+            .type_url
+            .strip_prefix(TYPE_URL_PREFIX)
+            .unwrap_or(&any.type_url);
+        let value = any.value.as_slice();
+
+        let detail = match type_name {
+            "google.rpc.ErrorInfo" => ErrorInfo::decode(value).map(ErrorDetail::ErrorInfo),
+            "google.rpc.RetryInfo" => RetryInfo::decode(value).map(ErrorDetail::RetryInfo),
+            "google.rpc.DebugInfo" => DebugInfo::decode(value).map(ErrorDetail::DebugInfo),
+            "google.rpc.QuotaFailure" => QuotaFailure::decode(value).map(ErrorDetail::QuotaFailure),
+            "google.rpc.PreconditionFailure" => {
+                PreconditionFailure::decode(value).map(ErrorDetail::PreconditionFailure)
+            }
+            "google.rpc.BadRequest" => BadRequest::decode(value).map(ErrorDetail::BadRequest),
+            "google.rpc.RequestInfo" => RequestInfo::decode(value).map(ErrorDetail::RequestInfo),
+            "google.rpc.ResourceInfo" => ResourceInfo::decode(value).map(ErrorDetail::ResourceInfo),
+            "google.rpc.Help" => Help::decode(value).map(ErrorDetail::Help),
+            "google.rpc.LocalizedMessage" => {
+                LocalizedMessage::decode(value).map(ErrorDetail::LocalizedMessage)
+            }
+            _ => return ErrorDetail::Unknown(any),
+        };
+
+        detail.unwrap_or(ErrorDetail::Unknown(any))
+    }
+}
+
+/// A `google.rpc` error detail message, which can be looked up with `ErrorStatus::find_detail`.
+pub trait ErrorDetailType: Sized {
+    /// The message held by `detail`, if it is of this type.
+    fn from_detail(detail: &ErrorDetail) -> Option<&Self>;
+}
+
+// Macro implementing `ErrorDetailType` for the messages with an `ErrorDetail` variant of the same
+// name.
+macro_rules! error_detail_type {
+    ($($detail:ident),*) => {
+        $(
+            impl ErrorDetailType for $detail {
+                fn from_detail(detail: &ErrorDetail) -> Option<&Self> {
+                    match detail {
+                        ErrorDetail::$detail(detail) => Some(detail),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+error_detail_type!(
+    ErrorInfo,
+    RetryInfo,
+    DebugInfo,
+    QuotaFailure,
+    PreconditionFailure,
+    BadRequest,
+    RequestInfo,
+    ResourceInfo,
+    Help,
+    LocalizedMessage
+);
+
+/// The code, message and decoded details of a status returned by the Valorem Trade API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorStatus {
+    pub code: Code,
+    pub message: String,
+    pub details: Vec<ErrorDetail>,
+}
+
+impl ErrorStatus {
+    /// The first detail of type `T`, e.g. `status.find_detail::<BadRequest>()`.
+    pub fn find_detail<T: ErrorDetailType>(&self) -> Option<&T> {
+        self.details.iter().find_map(T::from_detail)
+    }
+
+    /// The delay the server asked clients to wait before retrying, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let delay = self.find_detail::<RetryInfo>()?.retry_delay.as_ref()?;
+        let seconds = u64::try_from(delay.seconds).ok()?;
+        let nanos = u32::try_from(delay.nanos).ok()?;
+        Some(Duration::new(seconds, nanos))
+    }
+}
+
+impl From<tonic::Status> for ErrorStatus {
+    fn from(status: tonic::Status) -> Self {
+        // The details are optional, if they are missing or malformed we still keep the code and
+        // message from the status itself.
+        let details = rpc_codegen::Status::decode(status.details())
+            .map(|rpc_status| {
+                rpc_status
+                    .details
+                    .into_iter()
+                    .map(ErrorDetail::decode)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            code: status.code(),
+            message: status.message().to_string(),
+            details,
+        }
+    }
+}
+
+/// An error returned by the Valorem Trade API, categorised by its gRPC status code.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValoremError {
+    /// The request was malformed, see the `BadRequest` detail for the offending fields.
+    InvalidArgument(ErrorStatus),
+    /// The request was rejected as the system is not in a state required for the request.
+    FailedPrecondition(ErrorStatus),
+    /// The session is not authenticated, the client should re-authenticate.
+    Unauthenticated(ErrorStatus),
+    /// The authenticated user is not allowed to perform the request.
+    PermissionDenied(ErrorStatus),
+    NotFound(ErrorStatus),
+    /// A rate limit or quota was hit, see the `QuotaFailure` detail.
+    ResourceExhausted(ErrorStatus),
+    /// The service is currently unavailable.
+    Unavailable(ErrorStatus),
+    DeadlineExceeded(ErrorStatus),
+    Aborted(ErrorStatus),
+    Internal(ErrorStatus),
+    /// Any other status code.
+    Other(ErrorStatus),
+}
+
+impl ValoremError {
+    pub fn status(&self) -> &ErrorStatus {
+        match self {
+            ValoremError::InvalidArgument(status)
+            | ValoremError::FailedPrecondition(status)
+            | ValoremError::Unauthenticated(status)
+            | ValoremError::PermissionDenied(status)
+            | ValoremError::NotFound(status)
+            | ValoremError::ResourceExhausted(status)
+            | ValoremError::Unavailable(status)
+            | ValoremError::DeadlineExceeded(status)
+            | ValoremError::Aborted(status)
+            | ValoremError::Internal(status)
+            | ValoremError::Other(status) => status,
+        }
+    }
+
+    pub fn code(&self) -> Code {
+        self.status().code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.status().message
+    }
+
+    pub fn details(&self) -> &[ErrorDetail] {
+        &self.status().details
+    }
+
+    /// The first detail of type `T`, see `ErrorStatus::find_detail`.
+    pub fn find_detail<T: ErrorDetailType>(&self) -> Option<&T> {
+        self.status().find_detail()
+    }
+
+    /// The delay the server asked clients to wait before retrying, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.status().retry_after()
+    }
+
+    /// Returns true if the request may succeed if retried unchanged, either because the status
+    /// code indicates a transient failure or because the server attached `RetryInfo`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ValoremError::Unavailable(_)
+            | ValoremError::ResourceExhausted(_)
+            | ValoremError::DeadlineExceeded(_)
+            | ValoremError::Aborted(_) => true,
+            _ => self.find_detail::<RetryInfo>().is_some(),
+        }
+    }
+}
+
+impl From<tonic::Status> for ValoremError {
+    fn from(status: tonic::Status) -> Self {
+        let status = ErrorStatus::from(status);
+        match status.code {
+            Code::InvalidArgument | Code::OutOfRange => ValoremError::InvalidArgument(status),
+            Code::FailedPrecondition => ValoremError::FailedPrecondition(status),
+            Code::Unauthenticated => ValoremError::Unauthenticated(status),
+            Code::PermissionDenied => ValoremError::PermissionDenied(status),
+            Code::NotFound => ValoremError::NotFound(status),
+            Code::ResourceExhausted => ValoremError::ResourceExhausted(status),
+            Code::Unavailable => ValoremError::Unavailable(status),
+            Code::DeadlineExceeded => ValoremError::DeadlineExceeded(status),
+            Code::Aborted => ValoremError::Aborted(status),
+            Code::Internal => ValoremError::Internal(status),
+            _ => ValoremError::Other(status),
+        }
+    }
+}
+
+impl fmt::Display for ValoremError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code(), self.message())?;
+
+        if let Some(error_info) = self.find_detail::<ErrorInfo>() {
+            write!(
+                f,
+                " (reason: {}, domain: {})",
+                error_info.reason, error_info.domain
+            )?;
+        }
+
+        if let Some(bad_request) = self.find_detail::<BadRequest>() {
+            for violation in &bad_request.field_violations {
+                write!(f, "; {}: {}", violation.field, violation.description)?;
+            }
+        }
+
+        if let Some(precondition_failure) = self.find_detail::<PreconditionFailure>() {
+            for violation in &precondition_failure.violations {
+                write!(
+                    f,
+                    "; {} {}: {}",
+                    violation.r#type, violation.subject, violation.description
+                )?;
+            }
+        }
+
+        if let Some(quota_failure) = self.find_detail::<QuotaFailure>() {
+            for violation in &quota_failure.violations {
+                write!(f, "; {}: {}", violation.subject, violation.description)?;
+            }
+        }
+
+        if let Some(retry_after) = self.retry_after() {
+            write!(f, "; retry after {retry_after:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValoremError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_codegen::bad_request::FieldViolation;
+
+    fn any(type_name: &str, value: Vec<u8>) -> prost_types::Any {
+        prost_types::Any {
+            type_url: format!("{TYPE_URL_PREFIX}{type_name}"),
+            value,
+        }
+    }
+
+    // A status with the `details` packed into its `grpc-status-details-bin` as the server does.
+    fn status(code: Code, details: Vec<prost_types::Any>) -> tonic::Status {
+        let rpc_status = rpc_codegen::Status {
+            code: code as i32,
+            message: "Invalid quote".to_string(),
+            details,
+        };
+        tonic::Status::with_details(code, "Invalid quote", rpc_status.encode_to_vec().into())
+    }
+
+    #[test]
+    fn packed_details() {
+        let error_info = ErrorInfo {
+            reason: "INVALID_AMOUNT".to_string(),
+            domain: "valorem.xyz".to_string(),
+            metadata: Default::default(),
+        };
+        let bad_request = BadRequest {
+            field_violations: vec![FieldViolation {
+                field: "amount".to_string(),
+                description: "must not be zero".to_string(),
+            }],
+        };
+        let retry_info = RetryInfo {
+            retry_delay: Some(prost_types::Duration {
+                seconds: 2,
+                nanos: 500_000_000,
+            }),
+        };
+        let unknown = any("valorem.Unknown", vec![1, 2, 3]);
+        let malformed = any("google.rpc.Help", vec![0xff]);
+
+        let error = ValoremError::from(status(
+            Code::InvalidArgument,
+            vec![
+                any("google.rpc.ErrorInfo", error_info.encode_to_vec()),
+                any("google.rpc.BadRequest", bad_request.encode_to_vec()),
+                any("google.rpc.RetryInfo", retry_info.encode_to_vec()),
+                unknown.clone(),
+                malformed.clone(),
+            ],
+        ));
+
+        assert!(matches!(error, ValoremError::InvalidArgument(_)));
+        assert_eq!(error.message(), "Invalid quote");
+        assert_eq!(error.find_detail::<ErrorInfo>(), Some(&error_info));
+        assert_eq!(error.find_detail::<BadRequest>(), Some(&bad_request));
+        assert_eq!(error.find_detail::<RetryInfo>(), Some(&retry_info));
+        assert_eq!(error.find_detail::<Help>(), None);
+        assert_eq!(error.find_detail::<QuotaFailure>(), None);
+        assert_eq!(
+            error.details()[3..],
+            [
+                ErrorDetail::Unknown(unknown),
+                ErrorDetail::Unknown(malformed)
+            ]
+        );
+        assert_eq!(error.retry_after(), Some(Duration::from_millis(2_500)));
+        assert!(error.is_retryable());
+        assert_eq!(
+            error.to_string(),
+            "InvalidArgument: Invalid quote (reason: INVALID_AMOUNT, domain: valorem.xyz); \
+             amount: must not be zero; retry after 2.5s"
+        );
+    }
+
+    #[test]
+    fn missing_details() {
+        let error = ValoremError::from(tonic::Status::unavailable("Try again"));
+        assert!(matches!(error, ValoremError::Unavailable(_)));
+        assert!(error.details().is_empty());
+        assert!(error.is_retryable());
+        assert_eq!(error.to_string(), "Unavailable: Try again");
+
+        let error = ValoremError::from(tonic::Status::with_details(
+            Code::PermissionDenied,
+            "Denied",
+            vec![0xff].into(),
+        ));
+        assert!(matches!(error, ValoremError::PermissionDenied(_)));
+        assert!(error.details().is_empty());
+        assert!(!error.is_retryable());
+    }
+}
@@ -0,0 +1,454 @@
+// Setup From traits allowing the conversion between proto types and ethers types, enabled with
+// the `ethers` feature.
+use crate::bindings::seaport;
+use crate::grpc_adapters::{item_type, order_type, required, ConversionError};
+use crate::grpc_codegen::*;
+use ethers::abi::AbiEncode;
+
+// Macro allowing for proto types to be converted into numbers (and vice versa), moving
+// through the fixed hash type first.
+macro_rules! into_from {
+    ($proto:ty, $hash:ty, $num:ty) => {
+        impl From<$num> for $proto {
+            fn from(value: $num) -> Self {
+                Self::from(<$hash>::from(<[u8; <$hash>::len_bytes()]>::from(value)))
+            }
+        }
+
+        impl From<$proto> for $num {
+            fn from(value: $proto) -> Self {
+                Self::from(<$hash>::from(value).0)
+            }
+        }
+    };
+}
+
+into_from!(H128, ethers::types::H128, ethers::types::U128);
+into_from!(H256, ethers::types::H256, ethers::types::U256);
+
+impl From<ethers::types::H128> for H128 {
+    fn from(value: ethers::types::H128) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl From<H128> for ethers::types::H128 {
+    fn from(value: H128) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<ethers::types::H160> for H160 {
+    fn from(value: ethers::types::H160) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl From<H160> for ethers::types::H160 {
+    fn from(value: H160) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<ethers::types::H256> for H256 {
+    fn from(value: ethers::types::H256) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl From<H256> for ethers::types::H256 {
+    fn from(value: H256) -> Self {
+        Self(value.into())
+    }
+}
+
+impl TryFrom<&EthSignature> for ethers::types::Signature {
+    type Error = ConversionError;
+
+    fn try_from(value: &EthSignature) -> Result<Self, Self::Error> {
+        let (r, s, v) = value.components()?;
+        Ok(Self {
+            r: r.into(),
+            s: s.into(),
+            v: v.into(),
+        })
+    }
+}
+
+impl TryFrom<EthSignature> for ethers::types::Signature {
+    type Error = ConversionError;
+
+    fn try_from(value: EthSignature) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl From<ethers::types::Signature> for EthSignature {
+    fn from(value: ethers::types::Signature) -> Self {
+        // We don't want to directly encode v, as this will be encoded as a u64 where leading
+        // zeros matter (so it will be included). We know its only 1 byte, therefore only push 1 byte
+        // of data so the signature remains 65 bytes on the wire.
+        Self {
+            v: vec![value.v.to_le_bytes()[0]],
+            r: value.r.encode(),
+            s: value.s.encode(),
+        }
+    }
+}
+
+// Conversions to and from the Seaport contract bindings. These match `alloy_adapters`, producing
+// the same fields and signature bytes: orders are typically received from the network, so
+// required fields are validated rather than defaulted. Only the identifiers or criteria,
+// consideration tokens, zone, zone hash and conduit key may be omitted, in which case they are
+// zero.
+impl TryFrom<&OfferItem> for seaport::OfferItem {
+    type Error = ConversionError;
+
+    fn try_from(value: &OfferItem) -> Result<Self, Self::Error> {
+        Ok(Self {
+            item_type: item_type(value.item_type)?,
+            token: required(value.token, "OfferItem.token")?.into(),
+            identifier_or_criteria: value.identifier_or_criteria.unwrap_or_default().into(),
+            start_amount: required(value.start_amount, "OfferItem.start_amount")?.into(),
+            end_amount: required(value.end_amount, "OfferItem.end_amount")?.into(),
+        })
+    }
+}
+
+impl From<seaport::OfferItem> for OfferItem {
+    fn from(value: seaport::OfferItem) -> Self {
+        Self {
+            item_type: value.item_type.into(),
+            token: Some(value.token.into()),
+            identifier_or_criteria: Some(value.identifier_or_criteria.into()),
+            start_amount: Some(value.start_amount.into()),
+            end_amount: Some(value.end_amount.into()),
+        }
+    }
+}
+
+impl TryFrom<&ConsiderationItem> for seaport::ConsiderationItem {
+    type Error = ConversionError;
+
+    fn try_from(value: &ConsiderationItem) -> Result<Self, Self::Error> {
+        Ok(Self {
+            item_type: item_type(value.item_type)?,
+            token: value.token.unwrap_or_default().into(),
+            identifier_or_criteria: value.identifier_or_criteria.unwrap_or_default().into(),
+            start_amount: required(value.start_amount, "ConsiderationItem.start_amount")?.into(),
+            end_amount: required(value.end_amount, "ConsiderationItem.end_amount")?.into(),
+            recipient: required(value.recipient, "ConsiderationItem.recipient")?.into(),
+        })
+    }
+}
+
+impl From<seaport::ConsiderationItem> for ConsiderationItem {
+    fn from(value: seaport::ConsiderationItem) -> Self {
+        Self {
+            item_type: value.item_type.into(),
+            token: Some(value.token.into()),
+            identifier_or_criteria: Some(value.identifier_or_criteria.into()),
+            start_amount: Some(value.start_amount.into()),
+            end_amount: Some(value.end_amount.into()),
+            recipient: Some(value.recipient.into()),
+        }
+    }
+}
+
+// The total original consideration items is taken from the order as received.
+impl TryFrom<&Order> for seaport::OrderParameters {
+    type Error = ConversionError;
+
+    fn try_from(value: &Order) -> Result<Self, Self::Error> {
+        Ok(Self {
+            offerer: required(value.offerer, "Order.offerer")?.into(),
+            zone: value.zone.unwrap_or_default().into(),
+            offer: value
+                .offer
+                .iter()
+                .map(seaport::OfferItem::try_from)
+                .collect::<Result<_, _>>()?,
+            consideration: value
+                .consideration
+                .iter()
+                .map(seaport::ConsiderationItem::try_from)
+                .collect::<Result<_, _>>()?,
+            order_type: order_type(value.order_type)?,
+            start_time: required(value.start_time, "Order.start_time")?.into(),
+            end_time: required(value.end_time, "Order.end_time")?.into(),
+            zone_hash: value.zone_hash.unwrap_or_default().into(),
+            salt: required(value.salt, "Order.salt")?.into(),
+            conduit_key: value.conduit_key.unwrap_or_default().into(),
+            total_original_consideration_items: value.consideration.len().into(),
+        })
+    }
+}
+
+impl From<seaport::OrderParameters> for Order {
+    fn from(value: seaport::OrderParameters) -> Self {
+        Self {
+            offerer: Some(value.offerer.into()),
+            zone: Some(value.zone.into()),
+            offer: value.offer.into_iter().map(Into::into).collect(),
+            consideration: value.consideration.into_iter().map(Into::into).collect(),
+            order_type: value.order_type.into(),
+            start_time: Some(value.start_time.into()),
+            end_time: Some(value.end_time.into()),
+            zone_hash: Some(value.zone_hash.into()),
+            salt: Some(value.salt.into()),
+            conduit_key: Some(value.conduit_key.into()),
+        }
+    }
+}
+
+// The counter is not part of the proto order, it is read from Seaport for the offerer.
+impl TryFrom<(&Order, ethers::types::U256)> for seaport::OrderComponents {
+    type Error = ConversionError;
+
+    fn try_from((order, counter): (&Order, ethers::types::U256)) -> Result<Self, Self::Error> {
+        let parameters = seaport::OrderParameters::try_from(order)?;
+        Ok(Self {
+            offerer: parameters.offerer,
+            zone: parameters.zone,
+            offer: parameters.offer,
+            consideration: parameters.consideration,
+            order_type: parameters.order_type,
+            start_time: parameters.start_time,
+            end_time: parameters.end_time,
+            zone_hash: parameters.zone_hash,
+            salt: parameters.salt,
+            conduit_key: parameters.conduit_key,
+            counter,
+        })
+    }
+}
+
+// The counter is dropped, as the proto order has no equivalent field.
+impl From<seaport::OrderComponents> for Order {
+    fn from(value: seaport::OrderComponents) -> Self {
+        Self {
+            offerer: Some(value.offerer.into()),
+            zone: Some(value.zone.into()),
+            offer: value.offer.into_iter().map(Into::into).collect(),
+            consideration: value.consideration.into_iter().map(Into::into).collect(),
+            order_type: value.order_type.into(),
+            start_time: Some(value.start_time.into()),
+            end_time: Some(value.end_time.into()),
+            zone_hash: Some(value.zone_hash.into()),
+            salt: Some(value.salt.into()),
+            conduit_key: Some(value.conduit_key.into()),
+        }
+    }
+}
+
+// The signature as passed to Seaport, always 65 bytes with `v` in its 27/28 form as Seaport does
+// not accept 0/1. A compact signature is expanded, as in `alloy_adapters`.
+fn seaport_signature(value: &SignedOrder) -> Result<ethers::types::Bytes, ConversionError> {
+    let (r, s, v) = required(value.signature.as_ref(), "SignedOrder.signature")?.components()?;
+    Ok([r.as_slice(), s.as_slice(), &[27 + v % 27]].concat().into())
+}
+
+// Both 65 byte and 64 byte EIP-2098 compact signatures are accepted, the signature is always
+// sent on as 65 bytes.
+fn eth_signature(value: &[u8]) -> Result<EthSignature, ConversionError> {
+    let (r, s, v) = EthSignature {
+        r: value.get(..32).unwrap_or_default().to_vec(),
+        s: value.get(32..64).unwrap_or_default().to_vec(),
+        v: value.get(64..).unwrap_or_default().to_vec(),
+    }
+    .components()?;
+    Ok(EthSignature {
+        r: r.to_vec(),
+        s: s.to_vec(),
+        v: vec![27 + v % 27],
+    })
+}
+
+impl TryFrom<&SignedOrder> for seaport::Order {
+    type Error = ConversionError;
+
+    fn try_from(value: &SignedOrder) -> Result<Self, Self::Error> {
+        Ok(Self {
+            parameters: required(value.parameters.as_ref(), "SignedOrder.parameters")?
+                .try_into()?,
+            signature: seaport_signature(value)?,
+        })
+    }
+}
+
+impl TryFrom<seaport::Order> for SignedOrder {
+    type Error = ConversionError;
+
+    fn try_from(value: seaport::Order) -> Result<Self, Self::Error> {
+        Ok(Self {
+            parameters: Some(value.parameters.into()),
+            signature: Some(eth_signature(&value.signature)?),
+        })
+    }
+}
+
+// Signed orders are always filled in full, with no extra data for the zone.
+impl TryFrom<&SignedOrder> for seaport::AdvancedOrder {
+    type Error = ConversionError;
+
+    fn try_from(value: &SignedOrder) -> Result<Self, Self::Error> {
+        let order = seaport::Order::try_from(value)?;
+        Ok(Self {
+            parameters: order.parameters,
+            numerator: 1,
+            denominator: 1,
+            signature: order.signature,
+            extra_data: Default::default(),
+        })
+    }
+}
+
+// Partial fills and extra data cannot be represented by a signed order, so only orders filled in
+// full are accepted.
+impl TryFrom<seaport::AdvancedOrder> for SignedOrder {
+    type Error = ConversionError;
+
+    fn try_from(value: seaport::AdvancedOrder) -> Result<Self, Self::Error> {
+        if value.numerator != value.denominator || value.numerator == 0 {
+            return Err(ConversionError::OutOfRange {
+                field: "AdvancedOrder.numerator",
+                value: format!("{}/{}", value.numerator, value.denominator),
+            });
+        }
+        if !value.extra_data.is_empty() {
+            return Err(ConversionError::InvalidLength {
+                field: "AdvancedOrder.extra_data",
+                expected: 0,
+                actual: value.extra_data.len(),
+            });
+        }
+
+        Self::try_from(seaport::Order {
+            parameters: value.parameters,
+            signature: value.signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+
+    const R: [u8; 32] = [0x11; 32];
+    const S: [u8; 32] = [0x22; 32];
+
+    fn order() -> Order {
+        Order {
+            offerer: Some(H160::from([0x01; 20])),
+            zone: Some(H160::from([0x02; 20])),
+            offer: vec![OfferItem {
+                item_type: ItemType::Erc20 as i32,
+                token: Some(H160::from([0x03; 20])),
+                identifier_or_criteria: Some(H256::from(0u64)),
+                start_amount: Some(H256::from(1_000u64)),
+                end_amount: Some(H256::from(1_000u64)),
+            }],
+            consideration: vec![ConsiderationItem {
+                item_type: ItemType::Erc1155 as i32,
+                token: Some(H160::from([0x04; 20])),
+                identifier_or_criteria: Some(H256::from(7u64)),
+                start_amount: Some(H256::from(2u64)),
+                end_amount: Some(H256::from(2u64)),
+                recipient: Some(H160::from([0x01; 20])),
+            }],
+            order_type: OrderType::FullRestricted as i32,
+            start_time: Some(H256::from(1_700_000_000u64)),
+            end_time: Some(H256::from(1_700_001_200u64)),
+            zone_hash: Some(H256::from([0x05; 32])),
+            salt: Some(H256::from(42u64)),
+            conduit_key: Some(H256::from([0x06; 32])),
+        }
+    }
+
+    fn signed_order(r: [u8; 32], s: [u8; 32], v: &[u8]) -> SignedOrder {
+        SignedOrder {
+            parameters: Some(order()),
+            signature: Some(EthSignature {
+                r: r.to_vec(),
+                s: s.to_vec(),
+                v: v.to_vec(),
+            }),
+        }
+    }
+
+    #[test]
+    fn order_round_trip() {
+        let order = order();
+
+        let parameters = seaport::OrderParameters::try_from(&order).unwrap();
+        assert_eq!(parameters.total_original_consideration_items, U256::one());
+        assert_eq!(Order::from(parameters), order);
+
+        let components = seaport::OrderComponents::try_from((&order, U256::from(3u64))).unwrap();
+        assert_eq!(components.counter, U256::from(3u64));
+        assert_eq!(Order::from(components), order);
+    }
+
+    #[test]
+    fn signed_order_round_trip() {
+        for v in [27, 28] {
+            let signed = signed_order(R, S, &[v]);
+
+            let seaport_order = seaport::Order::try_from(&signed).unwrap();
+            assert_eq!(
+                seaport_order.signature.to_vec(),
+                [&R[..], &S, &[v]].concat()
+            );
+            assert_eq!(SignedOrder::try_from(seaport_order).unwrap(), signed);
+
+            let advanced_order = seaport::AdvancedOrder::try_from(&signed).unwrap();
+            assert_eq!(SignedOrder::try_from(advanced_order).unwrap(), signed);
+        }
+    }
+
+    #[test]
+    fn signatures_are_sent_as_65_bytes() {
+        let expected = [&R[..], &S, &[28]].concat();
+
+        // The top bit of `vs` in a compact signature is the y parity.
+        let mut vs = S;
+        vs[0] |= 0x80;
+        for signed in [signed_order(R, vs, &[]), signed_order(R, S, &[1])] {
+            let seaport_order = seaport::Order::try_from(&signed).unwrap();
+            assert_eq!(seaport_order.signature.to_vec(), expected);
+            assert_eq!(
+                SignedOrder::try_from(seaport_order).unwrap(),
+                signed_order(R, S, &[28])
+            );
+        }
+
+        let compact = seaport::Order {
+            parameters: seaport::OrderParameters::try_from(&order()).unwrap(),
+            signature: [R, vs].concat().into(),
+        };
+        assert_eq!(
+            SignedOrder::try_from(compact).unwrap(),
+            signed_order(R, S, &[28])
+        );
+    }
+
+    #[test]
+    fn malformed_signatures() {
+        for length in [0, 63, 66] {
+            let order = seaport::Order {
+                parameters: seaport::OrderParameters::try_from(&order()).unwrap(),
+                signature: vec![0x1b; length].into(),
+            };
+            assert!(SignedOrder::try_from(order).is_err(), "{length} bytes");
+        }
+
+        assert!(seaport::Order::try_from(&signed_order(R, S, &[2])).is_err());
+        let mut unsigned = signed_order(R, S, &[27]);
+        unsigned.signature = None;
+        assert_eq!(
+            seaport::Order::try_from(&unsigned),
+            Err(ConversionError::MissingField("SignedOrder.signature"))
+        );
+    }
+}
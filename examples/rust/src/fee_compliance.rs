@@ -0,0 +1,592 @@
+// Checks the fee items of a quote received from a maker before it is filled.
+//
+// The "Fees" section of the RFQ service in the repository README requires the maker fee to be the
+// second offer item and the taker fee the second consideration item, both paid in USDC. The
+// expected taker fee is calculated from the taker's own `FeeStructure`. The maker fee depends on the
+// maker's fee tier, which the taker does not know from its own `FeeStructure`, so its amount is only
+// checked when the maker's fees are given. The premium and fee amounts must be fixed, since Seaport
+// interpolates an item from its start to its end amount over the life of the order.
+use crate::bindings;
+use crate::fees::{FeeSchedule, TradeFeeSchedule, TradeValues};
+use crate::grpc_adapters::{required, ConversionError};
+use crate::grpc_codegen::{
+    Action, ConsiderationItem, ItemType, OfferItem, Order, QuoteRequest, H160, H256,
+};
+use crate::spot::SpotPrice;
+use ethers::types::{Address, I256, U256};
+use std::fmt;
+
+/// An error returned when a quote cannot be checked for fee compliance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeeComplianceError {
+    /// The order or request was malformed.
+    Conversion(ConversionError),
+    /// The action of the request was not `Buy` or `Sell`.
+    InvalidAction(Action),
+    /// The order does not contain a USDC premium leg for the action.
+    MissingPremium,
+    /// The premium leg changes over the life of the order, so has no single value to charge
+    /// fees on.
+    RampingPremium {
+        start_amount: U256,
+        end_amount: U256,
+    },
+    /// The trade could not be valued in USDC, either because neither asset of the option is
+    /// USDC or because of an overflow.
+    Valuation,
+}
+
+impl fmt::Display for FeeComplianceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeComplianceError::Conversion(error) => write!(f, "Invalid quote: {error}"),
+            FeeComplianceError::InvalidAction(action) => {
+                write!(f, "Quote action must be Buy or Sell, not {action:?}")
+            }
+            FeeComplianceError::MissingPremium => {
+                write!(f, "Order does not contain a USDC premium")
+            }
+            FeeComplianceError::RampingPremium {
+                start_amount,
+                end_amount,
+            } => write!(
+                f,
+                "Order premium must be fixed, not ramp from {start_amount} to {end_amount}"
+            ),
+            FeeComplianceError::Valuation => write!(f, "Unable to value the trade in USDC"),
+        }
+    }
+}
+
+impl std::error::Error for FeeComplianceError {}
+
+impl From<ConversionError> for FeeComplianceError {
+    fn from(value: ConversionError) -> Self {
+        FeeComplianceError::Conversion(value)
+    }
+}
+
+/// The side of the trade a fee item is charged to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeeSide {
+    Maker,
+    Taker,
+}
+
+/// A way in which the fee items of an order do not match the expected fees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeDiscrepancy {
+    /// A fee is due but the order has no fee item for it.
+    MissingItem { side: FeeSide, expected: U256 },
+    /// The fee item is not paid in USDC.
+    WrongToken {
+        side: FeeSide,
+        expected: Address,
+        found: Option<Address>,
+    },
+    /// The taker fee item is not paid to the fee address.
+    WrongRecipient {
+        expected: Address,
+        found: Option<Address>,
+    },
+    /// The fee item charges a different amount than expected, which is zero for a rebate.
+    WrongAmount {
+        side: FeeSide,
+        expected: U256,
+        found: U256,
+    },
+    /// The fee item amount changes over the life of the order, rather than being fixed.
+    RampingAmount {
+        side: FeeSide,
+        start_amount: U256,
+        end_amount: U256,
+    },
+}
+
+impl fmt::Display for FeeDiscrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeDiscrepancy::MissingItem { side, expected } => {
+                write!(f, "{side:?} fee of {expected} is missing")
+            }
+            FeeDiscrepancy::WrongToken {
+                side,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{side:?} fee must be paid in {expected:?}, not {found:?}"
+            ),
+            FeeDiscrepancy::WrongRecipient { expected, found } => {
+                write!(f, "Taker fee must be paid to {expected:?}, not {found:?}")
+            }
+            FeeDiscrepancy::WrongAmount {
+                side,
+                expected,
+                found,
+            } => write!(f, "{side:?} fee should be {expected}, not {found}"),
+            FeeDiscrepancy::RampingAmount {
+                side,
+                start_amount,
+                end_amount,
+            } => write!(
+                f,
+                "{side:?} fee must be fixed, not ramp from {start_amount} to {end_amount}"
+            ),
+        }
+    }
+}
+
+/// The result of checking the fee items of an order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeReport {
+    /// The premium or credit of the order, in USDC dust.
+    pub premium: U256,
+    /// The values of the trade the fees were calculated on.
+    pub values: TradeValues,
+    /// The maker fee item of the order, if any.
+    pub maker_fee: Option<OfferItem>,
+    /// The taker fee item of the order, if any.
+    pub taker_fee: Option<ConsiderationItem>,
+    /// The signed maker fee (positive) or rebate (negative) in USDC dust, if the maker's fees are
+    /// known.
+    pub expected_maker_fee: Option<I256>,
+    /// The signed taker fee (positive) or rebate (negative) in USDC dust.
+    pub expected_taker_fee: I256,
+    pub discrepancies: Vec<FeeDiscrepancy>,
+}
+
+impl FeeReport {
+    /// Whether the order charges the fees it is supposed to.
+    pub fn is_compliant(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Checks quotes received in response to a `QuoteRequest` against the taker's `FeeSchedule`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FeeValidator {
+    pub schedule: FeeSchedule,
+    /// The USDC token fees are paid in.
+    pub usdc_address: Address,
+    /// The maker's fees, if known, which the maker fee item is checked against.
+    pub maker: Option<TradeFeeSchedule>,
+}
+
+impl FeeValidator {
+    /// A validator for the taker fee `schedule`, paid in the USDC token at `usdc_address`.
+    pub fn new(schedule: &FeeSchedule, usdc_address: Address) -> Self {
+        Self {
+            schedule: *schedule,
+            usdc_address,
+            maker: None,
+        }
+    }
+
+    /// Also check the maker fee item against the `maker` fees of the maker's fee tier.
+    pub fn with_maker_fees(mut self, maker: TradeFeeSchedule) -> Self {
+        self.maker = Some(maker);
+        self
+    }
+
+    /// Checks the fee items of `order`, quoted in response to `request` for the Clear `option`.
+    /// The spot value of the trade is calculated from `spot_price`, the price of the non-USDC
+    /// asset of the option, which has `decimals` decimals.
+    pub fn validate(
+        &self,
+        order: &Order,
+        request: &QuoteRequest,
+        option: &bindings::valorem_clear::Option,
+        spot_price: SpotPrice,
+        decimals: usize,
+    ) -> Result<FeeReport, FeeComplianceError> {
+        let amount = U256::from(required(request.amount, "QuoteRequest.amount")?);
+        let action: Action = request.action.into();
+        let (premium, end_premium) = match action {
+            Action::Buy => order.consideration.first().map(|item| {
+                (
+                    item.item_type,
+                    item.token,
+                    item.start_amount,
+                    item.end_amount,
+                )
+            }),
+            Action::Sell => order.offer.first().map(|item| {
+                (
+                    item.item_type,
+                    item.token,
+                    item.start_amount,
+                    item.end_amount,
+                )
+            }),
+            Action::Invalid => return Err(FeeComplianceError::InvalidAction(action)),
+        }
+        .and_then(|(item_type, token, start_amount, end_amount)| {
+            self.usdc_amounts(item_type, token, start_amount, end_amount)
+        })
+        .ok_or(FeeComplianceError::MissingPremium)?;
+        if premium != end_premium {
+            return Err(FeeComplianceError::RampingPremium {
+                start_amount: premium,
+                end_amount: end_premium,
+            });
+        }
+
+        let values = TradeValues {
+            notional: TradeValues::notional_of(option, amount, self.usdc_address)
+                .ok_or(FeeComplianceError::Valuation)?,
+            premium,
+            spot: TradeValues::spot_of(option, amount, self.usdc_address, spot_price, decimals)
+                .ok_or(FeeComplianceError::Valuation)?,
+        };
+        let expected_maker_fee = self.maker.map(|maker| maker.amount(&values));
+        let expected_taker_fee = self.schedule.taker.amount(&values);
+
+        let maker_fee = order.offer.get(1).cloned();
+        let taker_fee = order.consideration.get(1).cloned();
+
+        let mut discrepancies = Vec::new();
+        let found = match &maker_fee {
+            Some(item) => {
+                self.check_token(
+                    FeeSide::Maker,
+                    item.item_type,
+                    item.token,
+                    &mut discrepancies,
+                );
+
+                let found = U256::from(required(item.start_amount, "OfferItem.start_amount")?);
+                check_fixed(
+                    FeeSide::Maker,
+                    found,
+                    required(item.end_amount, "OfferItem.end_amount")?.into(),
+                    &mut discrepancies,
+                );
+                Some(found)
+            }
+            None => None,
+        };
+        if let Some(expected) = expected_maker_fee {
+            check_amount(FeeSide::Maker, expected, found, &mut discrepancies);
+        }
+
+        let found = match &taker_fee {
+            Some(item) => {
+                self.check_token(
+                    FeeSide::Taker,
+                    item.item_type,
+                    item.token,
+                    &mut discrepancies,
+                );
+
+                let recipient = item.recipient.map(Address::from);
+                if recipient != Some(self.schedule.address) {
+                    discrepancies.push(FeeDiscrepancy::WrongRecipient {
+                        expected: self.schedule.address,
+                        found: recipient,
+                    });
+                }
+
+                let found = U256::from(required(
+                    item.start_amount,
+                    "ConsiderationItem.start_amount",
+                )?);
+                check_fixed(
+                    FeeSide::Taker,
+                    found,
+                    required(item.end_amount, "ConsiderationItem.end_amount")?.into(),
+                    &mut discrepancies,
+                );
+                Some(found)
+            }
+            None => None,
+        };
+        check_amount(
+            FeeSide::Taker,
+            expected_taker_fee,
+            found,
+            &mut discrepancies,
+        );
+
+        Ok(FeeReport {
+            premium,
+            values,
+            maker_fee,
+            taker_fee,
+            expected_maker_fee,
+            expected_taker_fee,
+            discrepancies,
+        })
+    }
+
+    // The start and end amounts of an item, if it is an amount of USDC.
+    fn usdc_amounts(
+        &self,
+        item_type: i32,
+        token: Option<H160>,
+        start_amount: Option<H256>,
+        end_amount: Option<H256>,
+    ) -> Option<(U256, U256)> {
+        let is_usdc = item_type == ItemType::Erc20 as i32
+            && token.map(Address::from) == Some(self.usdc_address);
+        is_usdc.then_some((start_amount?.into(), end_amount?.into()))
+    }
+
+    fn check_token(
+        &self,
+        side: FeeSide,
+        item_type: i32,
+        token: Option<H160>,
+        discrepancies: &mut Vec<FeeDiscrepancy>,
+    ) {
+        let token = token.map(Address::from);
+        if item_type != ItemType::Erc20 as i32 || token != Some(self.usdc_address) {
+            discrepancies.push(FeeDiscrepancy::WrongToken {
+                side,
+                expected: self.usdc_address,
+                found: token,
+            });
+        }
+    }
+}
+
+// Checks the `found` amount of a fee item against the `expected` fee, where a rebate is expected to
+// have no fee item.
+fn check_amount(
+    side: FeeSide,
+    expected: I256,
+    found: Option<U256>,
+    discrepancies: &mut Vec<FeeDiscrepancy>,
+) {
+    let expected = if expected.is_positive() {
+        expected.into_raw()
+    } else {
+        U256::zero()
+    };
+    match found {
+        Some(found) if found != expected => discrepancies.push(FeeDiscrepancy::WrongAmount {
+            side,
+            expected,
+            found,
+        }),
+        None if !expected.is_zero() => {
+            discrepancies.push(FeeDiscrepancy::MissingItem { side, expected })
+        }
+        _ => {}
+    }
+}
+
+fn check_fixed(
+    side: FeeSide,
+    start_amount: U256,
+    end_amount: U256,
+    discrepancies: &mut Vec<FeeDiscrepancy>,
+) {
+    if start_amount != end_amount {
+        discrepancies.push(FeeDiscrepancy::RampingAmount {
+            side,
+            start_amount,
+            end_amount,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::BasisPoints;
+    use std::iter;
+
+    const USDC: Address = Address::repeat_byte(0xaa);
+    const WETH: Address = Address::repeat_byte(0xee);
+    const FEE_ADDRESS: Address = Address::repeat_byte(0xfe);
+
+    // A taker fee of 1% of the premium.
+    fn validator() -> FeeValidator {
+        FeeValidator {
+            schedule: FeeSchedule {
+                taker: TradeFeeSchedule {
+                    premium: BasisPoints(100),
+                    ..Default::default()
+                },
+                address: FEE_ADDRESS,
+                ..Default::default()
+            },
+            usdc_address: USDC,
+            maker: None,
+        }
+    }
+
+    // A call on 1 WETH with a strike of 2 000 USDC.
+    fn option() -> bindings::valorem_clear::Option {
+        bindings::valorem_clear::Option {
+            underlying_asset: WETH,
+            underlying_amount: 10u128.pow(18),
+            exercise_asset: USDC,
+            exercise_amount: 2_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn request() -> QuoteRequest {
+        QuoteRequest {
+            amount: Some(5u64.into()),
+            action: Action::Buy as i32,
+            ..Default::default()
+        }
+    }
+
+    fn usdc_item(start_amount: u64, end_amount: u64, recipient: Address) -> ConsiderationItem {
+        ConsiderationItem {
+            item_type: ItemType::Erc20 as i32,
+            token: Some(USDC.into()),
+            identifier_or_criteria: None,
+            start_amount: Some(start_amount.into()),
+            end_amount: Some(end_amount.into()),
+            recipient: Some(recipient.into()),
+        }
+    }
+
+    // An order selling the options for 10 USDC, with a taker fee of `taker_fee`.
+    fn order(taker_fee: Option<ConsiderationItem>) -> Order {
+        let maker = Address::repeat_byte(0x01);
+        Order {
+            offer: vec![OfferItem {
+                item_type: ItemType::Erc1155 as i32,
+                token: Some(Address::repeat_byte(0xc1).into()),
+                identifier_or_criteria: Some(1u64.into()),
+                start_amount: Some(5u64.into()),
+                end_amount: Some(5u64.into()),
+            }],
+            consideration: iter::once(usdc_item(10_000_000, 10_000_000, maker))
+                .chain(taker_fee)
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn validate(order: &Order) -> Result<FeeReport, FeeComplianceError> {
+        let spot_price = SpotPrice::from_integer(2_000u64.into()).unwrap();
+        validator().validate(order, &request(), &option(), spot_price, 18)
+    }
+
+    #[test]
+    fn compliant_order() {
+        let report = validate(&order(Some(usdc_item(100_000, 100_000, FEE_ADDRESS)))).unwrap();
+
+        assert!(report.is_compliant(), "{:?}", report.discrepancies);
+        assert_eq!(report.premium, U256::from(10_000_000u64));
+        assert_eq!(report.values.notional, U256::from(10_000_000_000u64));
+        assert_eq!(report.values.spot, U256::from(10_000_000_000u64));
+        assert_eq!(report.expected_taker_fee, I256::from(100_000));
+    }
+
+    #[test]
+    fn missing_fee() {
+        let report = validate(&order(None)).unwrap();
+
+        assert_eq!(
+            report.discrepancies,
+            vec![FeeDiscrepancy::MissingItem {
+                side: FeeSide::Taker,
+                expected: 100_000u64.into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn underpaid_fee() {
+        let report = validate(&order(Some(usdc_item(99_999, 99_999, FEE_ADDRESS)))).unwrap();
+
+        assert_eq!(
+            report.discrepancies,
+            vec![FeeDiscrepancy::WrongAmount {
+                side: FeeSide::Taker,
+                expected: 100_000u64.into(),
+                found: 99_999u64.into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn ramping_fee() {
+        // The fee starts out right, but decays to nothing by the end of the order.
+        let report = validate(&order(Some(usdc_item(100_000, 0, FEE_ADDRESS)))).unwrap();
+
+        assert_eq!(
+            report.discrepancies,
+            vec![FeeDiscrepancy::RampingAmount {
+                side: FeeSide::Taker,
+                start_amount: 100_000u64.into(),
+                end_amount: U256::zero(),
+            }]
+        );
+    }
+
+    // Appends a maker fee of `amount` to `order`.
+    fn with_maker_fee(mut order: Order, amount: u64) -> Order {
+        order.offer.push(OfferItem {
+            item_type: ItemType::Erc20 as i32,
+            token: Some(USDC.into()),
+            identifier_or_criteria: None,
+            start_amount: Some(amount.into()),
+            end_amount: Some(amount.into()),
+        });
+        order
+    }
+
+    #[test]
+    fn maker_fee() {
+        // A maker fee of 0.5% of the premium.
+        let validator = validator().with_maker_fees(TradeFeeSchedule {
+            premium: BasisPoints(50),
+            ..Default::default()
+        });
+        let spot_price = SpotPrice::from_integer(2_000u64.into()).unwrap();
+        let order = order(Some(usdc_item(100_000, 100_000, FEE_ADDRESS)));
+
+        let report = validator
+            .validate(
+                &with_maker_fee(order.clone(), 50_000),
+                &request(),
+                &option(),
+                spot_price,
+                18,
+            )
+            .unwrap();
+        assert!(report.is_compliant(), "{:?}", report.discrepancies);
+        assert_eq!(report.expected_maker_fee, Some(I256::from(50_000)));
+
+        let overpaid = with_maker_fee(order, 50_001);
+        let report = validator
+            .validate(&overpaid, &request(), &option(), spot_price, 18)
+            .unwrap();
+        assert_eq!(
+            report.discrepancies,
+            vec![FeeDiscrepancy::WrongAmount {
+                side: FeeSide::Maker,
+                expected: 50_000u64.into(),
+                found: 50_001u64.into(),
+            }]
+        );
+
+        // Without the maker's fees the maker fee amount is not checked.
+        let report = validate(&overpaid).unwrap();
+        assert!(report.is_compliant(), "{:?}", report.discrepancies);
+        assert_eq!(report.expected_maker_fee, None);
+    }
+
+    #[test]
+    fn ramping_premium() {
+        let mut order = order(Some(usdc_item(100_000, 100_000, FEE_ADDRESS)));
+        order.consideration[0].end_amount = Some(1u64.into());
+
+        assert_eq!(
+            validate(&order),
+            Err(FeeComplianceError::RampingPremium {
+                start_amount: 10_000_000u64.into(),
+                end_amount: U256::one(),
+            })
+        );
+    }
+}
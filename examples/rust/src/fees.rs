@@ -0,0 +1,526 @@
+// Typed wrappers around the `FeeStructure` returned by the Valorem `Fees` service.
+//
+// Fees are expressed as positive integers while rebates are expressed as negative integers,
+// see the "Fees" section of the repository README for more details.
+use crate::bindings;
+use crate::grpc_adapters::ConversionError;
+use crate::grpc_codegen;
+use crate::spot::{SpotPrice, SPOT_PRICE_DECIMALS};
+use ethers::types::{Address, I256, U256, U512};
+
+/// The number of basis points in one whole unit (i.e. 100%).
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// The number of decimals of USDC, in which fees are paid.
+pub const USDC_DECIMALS: usize = 6;
+
+/// A fee or rebate expressed in basis points (1 bps = 0.01%).
+///
+/// A positive value represents a fee, a negative value represents a rebate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BasisPoints(pub i32);
+
+impl BasisPoints {
+    pub fn is_fee(&self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_rebate(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Apply the basis points to `value`, returning the signed fee (positive) or rebate
+    /// (negative) amount in the same units as `value`. The result is truncated towards zero and
+    /// saturates at `I256::MAX` in magnitude.
+    pub fn apply(&self, value: U256) -> I256 {
+        let magnitude =
+            value.full_mul(U256::from(self.0.unsigned_abs())) / U512::from(BPS_DENOMINATOR);
+        let magnitude = U256::try_from(magnitude)
+            .ok()
+            .and_then(|magnitude| I256::try_from(magnitude).ok())
+            .unwrap_or(I256::MAX);
+
+        if self.is_rebate() {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<i32> for BasisPoints {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<BasisPoints> for i32 {
+    fn from(value: BasisPoints) -> Self {
+        value.0
+    }
+}
+
+/// A flat relayer fee or rebate expressed in 1e-6 USDC (dust), used for non-valued
+/// offers/considerations such as NFTs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FlatFee(pub i32);
+
+impl FlatFee {
+    pub fn is_fee(&self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_rebate(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The signed fee (positive) or rebate (negative) amount in USDC dust.
+    pub fn amount(&self) -> I256 {
+        I256::from(self.0)
+    }
+}
+
+impl From<i32> for FlatFee {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<FlatFee> for i32 {
+    fn from(value: FlatFee) -> Self {
+        value.0
+    }
+}
+
+/// The fees or rebates applied to one side (maker or taker) of a trade.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TradeFeeSchedule {
+    /// Fee or rebate on the notional value traded.
+    pub notional: BasisPoints,
+    /// Fee or rebate on the premium or credit value traded.
+    pub premium: BasisPoints,
+    /// Fee or rebate on the spot value traded.
+    pub spot: BasisPoints,
+    /// Flat relayer fee or rebate.
+    pub flat: FlatFee,
+}
+
+impl TradeFeeSchedule {
+    /// Returns true if no fees or rebates apply.
+    pub fn is_zero(&self) -> bool {
+        self.notional.is_zero()
+            && self.premium.is_zero()
+            && self.spot.is_zero()
+            && self.flat.is_zero()
+    }
+}
+
+impl From<grpc_codegen::TradeFees> for TradeFeeSchedule {
+    fn from(value: grpc_codegen::TradeFees) -> Self {
+        Self {
+            notional: value.notional_bps.into(),
+            premium: value.premium_bps.into(),
+            spot: value.spot_bps.into(),
+            flat: value.flat.into(),
+        }
+    }
+}
+
+impl From<TradeFeeSchedule> for grpc_codegen::TradeFees {
+    fn from(value: TradeFeeSchedule) -> Self {
+        Self {
+            notional_bps: value.notional.into(),
+            premium_bps: value.premium.into(),
+            spot_bps: value.spot.into(),
+            flat: value.flat.into(),
+        }
+    }
+}
+
+/// The complete Valorem fee schedule for an authenticated user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FeeSchedule {
+    /// The fees or rebates applied when acting as a maker.
+    pub maker: TradeFeeSchedule,
+    /// The fees or rebates applied when acting as a taker.
+    pub taker: TradeFeeSchedule,
+    /// Fee or rebate on notional value written via Clear.
+    pub clear_write_notional: BasisPoints,
+    /// Fee or rebate on underlying asset notional value redeemed via Clear.
+    pub clear_redeemed_notional: BasisPoints,
+    /// Fee or rebate on notional value exercised via Clear.
+    pub clear_exercise_notional: BasisPoints,
+    /// The address fees must be paid to or rebates are received from.
+    pub address: Address,
+}
+
+// Missing sub-messages are rejected rather than defaulted, which would silently mean no fees or
+// fees paid to the zero address.
+impl TryFrom<grpc_codegen::FeeStructure> for FeeSchedule {
+    type Error = ConversionError;
+
+    fn try_from(value: grpc_codegen::FeeStructure) -> Result<Self, Self::Error> {
+        Ok(Self {
+            maker: value
+                .maker
+                .ok_or(ConversionError::MissingField("FeeStructure.maker"))?
+                .into(),
+            taker: value
+                .taker
+                .ok_or(ConversionError::MissingField("FeeStructure.taker"))?
+                .into(),
+            clear_write_notional: value.clear_write_notional_bps.into(),
+            clear_redeemed_notional: value.clear_redeemed_notional_bps.into(),
+            clear_exercise_notional: value.clear_exercise_notional_bps.into(),
+            address: value
+                .address
+                .ok_or(ConversionError::MissingField("FeeStructure.address"))?
+                .into(),
+        })
+    }
+}
+
+impl From<FeeSchedule> for grpc_codegen::FeeStructure {
+    fn from(value: FeeSchedule) -> Self {
+        Self {
+            maker: Some(value.maker.into()),
+            taker: Some(value.taker.into()),
+            clear_write_notional_bps: value.clear_write_notional.into(),
+            clear_redeemed_notional_bps: value.clear_redeemed_notional.into(),
+            clear_exercise_notional_bps: value.clear_exercise_notional.into(),
+            address: Some(value.address.into()),
+        }
+    }
+}
+
+/// The values of an option trade which fees are charged on, each in USDC dust (1e-6 USDC).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TradeValues {
+    /// The notional value of the options traded, i.e. their USDC leg.
+    pub notional: U256,
+    /// The premium or credit paid for the options.
+    pub premium: U256,
+    /// The spot value of the non-USDC asset of the options traded.
+    pub spot: U256,
+}
+
+impl TradeValues {
+    /// The notional value of `amount` options of the Clear `option`, i.e. the exercise amount for
+    /// a call or the underlying amount for a put. Returns `None` if neither asset of the option is
+    /// the USDC token at `usdc_address`, or on overflow.
+    pub fn notional_of(
+        option: &bindings::valorem_clear::Option,
+        amount: U256,
+        usdc_address: Address,
+    ) -> Option<U256> {
+        let usdc_amount = if option.exercise_asset == usdc_address {
+            option.exercise_amount
+        } else if option.underlying_asset == usdc_address {
+            option.underlying_amount
+        } else {
+            return None;
+        };
+
+        U256::from(usdc_amount).checked_mul(amount)
+    }
+
+    /// The spot value of `amount` options of the Clear `option`, i.e. the value of the non-USDC
+    /// asset at `spot_price`, where `decimals` are the decimals of that asset. Returns `None` if
+    /// neither asset of the option is the USDC token at `usdc_address`, or on overflow.
+    pub fn spot_of(
+        option: &bindings::valorem_clear::Option,
+        amount: U256,
+        usdc_address: Address,
+        spot_price: SpotPrice,
+        decimals: usize,
+    ) -> Option<U256> {
+        let asset_amount = if option.exercise_asset == usdc_address {
+            option.underlying_amount
+        } else if option.underlying_asset == usdc_address {
+            option.exercise_amount
+        } else {
+            return None;
+        };
+
+        // The value is in 60x18 fixed point USD, which is scaled down to USDC dust.
+        let asset_amount = U256::from(asset_amount).checked_mul(amount)?;
+        let value = spot_price.checked_mul_amount(asset_amount, decimals)?;
+        Some(value.into_raw() / U256::exp10(SPOT_PRICE_DECIMALS - USDC_DECIMALS))
+    }
+}
+
+impl TradeFeeSchedule {
+    /// The signed fee (positive) or rebate (negative) in USDC dust for a trade of `values`,
+    /// saturating at `I256::MAX` in magnitude.
+    pub fn amount(&self, values: &TradeValues) -> I256 {
+        [
+            self.notional.apply(values.notional),
+            self.premium.apply(values.premium),
+            self.spot.apply(values.spot),
+            self.flat.amount(),
+        ]
+        .into_iter()
+        .fold(I256::zero(), I256::saturating_add)
+    }
+}
+
+/// The rebates of a trade in USDC dust. Seaport items cannot carry a negative amount, so rebates
+/// are not part of the order and are settled separately with the fee address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FeeRebates {
+    /// The rebate owed to the maker.
+    pub maker: U256,
+    /// The rebate owed to the taker.
+    pub taker: U256,
+}
+
+impl FeeRebates {
+    /// Returns true if no rebates are owed.
+    pub fn is_zero(&self) -> bool {
+        self.maker.is_zero() && self.taker.is_zero()
+    }
+}
+
+/// The fee items of an order returned from the RFQ service.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeItems {
+    /// The maker fee, which follows the option or premium leg offered.
+    pub maker: Option<grpc_codegen::OfferItem>,
+    /// The taker fee, which follows the option or premium leg considered.
+    pub taker: Option<grpc_codegen::ConsiderationItem>,
+    /// The rebates, which have no fee item.
+    pub rebates: FeeRebates,
+}
+
+impl FeeItems {
+    /// Appends the fee items to `order`, which should only contain its option and premium legs,
+    /// returning the rebates which must be settled outside of the order.
+    #[must_use = "rebates are not part of the order and must be settled separately"]
+    pub fn append_to(self, order: &mut grpc_codegen::Order) -> FeeRebates {
+        order.offer.extend(self.maker);
+        order.consideration.extend(self.taker);
+        self.rebates
+    }
+}
+
+/// Calculates the fee items required in orders returned from the RFQ service, see the "Fees"
+/// section of the RFQ service in the repository README.
+///
+/// The layout is the same for a buy or sell, the maker fee is the second offer item and the taker
+/// fee the second consideration item, paid to the fee address. Seaport items cannot carry a
+/// negative amount, so a rebate results in no fee item and is returned in `FeeRebates` instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FeeCalculator {
+    pub maker: TradeFeeSchedule,
+    pub taker: TradeFeeSchedule,
+    /// The address fees are paid to.
+    pub fee_address: Address,
+    /// The USDC token fees are paid in.
+    pub usdc_address: Address,
+}
+
+impl FeeCalculator {
+    /// A calculator for the maker and taker fees of `schedule`, paid in the USDC token at
+    /// `usdc_address`.
+    pub fn new(schedule: &FeeSchedule, usdc_address: Address) -> Self {
+        Self {
+            maker: schedule.maker,
+            taker: schedule.taker,
+            fee_address: schedule.address,
+            usdc_address,
+        }
+    }
+
+    /// The signed maker fee (positive) or rebate (negative) in USDC dust.
+    pub fn maker_fee(&self, values: &TradeValues) -> I256 {
+        self.maker.amount(values)
+    }
+
+    /// The signed taker fee (positive) or rebate (negative) in USDC dust.
+    pub fn taker_fee(&self, values: &TradeValues) -> I256 {
+        self.taker.amount(values)
+    }
+
+    /// The fee items and rebates for a trade of `values`.
+    pub fn fee_items(&self, values: &TradeValues) -> FeeItems {
+        let usdc = Some(grpc_codegen::H160::from(self.usdc_address));
+        let maker_fee = self.maker_fee(values);
+        let taker_fee = self.taker_fee(values);
+        FeeItems {
+            maker: fee_amount(maker_fee).map(|amount| grpc_codegen::OfferItem {
+                item_type: grpc_codegen::ItemType::Erc20 as i32,
+                token: usdc,
+                identifier_or_criteria: None,
+                start_amount: Some(amount),
+                end_amount: Some(amount),
+            }),
+            taker: fee_amount(taker_fee).map(|amount| grpc_codegen::ConsiderationItem {
+                item_type: grpc_codegen::ItemType::Erc20 as i32,
+                token: usdc,
+                identifier_or_criteria: None,
+                start_amount: Some(amount),
+                end_amount: Some(amount),
+                recipient: Some(self.fee_address.into()),
+            }),
+            rebates: FeeRebates {
+                maker: rebate_amount(maker_fee),
+                taker: rebate_amount(taker_fee),
+            },
+        }
+    }
+
+    /// Appends the fee items for a trade of `values` to `order`, which should only contain its
+    /// option and premium legs, returning the rebates which must be settled outside of the order.
+    #[must_use = "rebates are not part of the order and must be settled separately"]
+    pub fn append_fee_items(
+        &self,
+        order: &mut grpc_codegen::Order,
+        values: &TradeValues,
+    ) -> FeeRebates {
+        self.fee_items(values).append_to(order)
+    }
+}
+
+// The amount of a fee item, if `fee` is a fee rather than a rebate.
+fn fee_amount(fee: I256) -> Option<grpc_codegen::H256> {
+    fee.is_positive().then(|| fee.into_raw().into())
+}
+
+// The amount of the rebate, zero if `fee` is a fee rather than a rebate.
+fn rebate_amount(fee: I256) -> U256 {
+    if fee.is_negative() {
+        fee.unsigned_abs()
+    } else {
+        U256::zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculator(maker_bps: i32, taker_bps: i32) -> FeeCalculator {
+        FeeCalculator {
+            maker: TradeFeeSchedule {
+                premium: BasisPoints(maker_bps),
+                ..Default::default()
+            },
+            taker: TradeFeeSchedule {
+                premium: BasisPoints(taker_bps),
+                ..Default::default()
+            },
+            fee_address: Address::repeat_byte(0xfe),
+            usdc_address: Address::repeat_byte(0xaa),
+        }
+    }
+
+    fn values(premium: u64) -> TradeValues {
+        TradeValues {
+            premium: premium.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fees_become_items() {
+        let items = calculator(10, 25).fee_items(&values(1_000_000));
+
+        let maker = items.maker.unwrap();
+        assert_eq!(maker.start_amount, Some(1_000u64.into()));
+        assert_eq!(maker.end_amount, maker.start_amount);
+        let taker = items.taker.unwrap();
+        assert_eq!(taker.start_amount, Some(2_500u64.into()));
+        assert_eq!(taker.recipient, Some(Address::repeat_byte(0xfe).into()));
+        assert!(items.rebates.is_zero());
+    }
+
+    #[test]
+    fn rebates_are_returned() {
+        let calculator = calculator(-10, 25);
+        let items = calculator.fee_items(&values(1_000_000));
+
+        assert_eq!(items.maker, None);
+        assert!(items.taker.is_some());
+        assert_eq!(
+            items.rebates,
+            FeeRebates {
+                maker: 1_000u64.into(),
+                taker: U256::zero(),
+            }
+        );
+
+        let mut order = grpc_codegen::Order::default();
+        let rebates = calculator.append_fee_items(&mut order, &values(1_000_000));
+        assert_eq!(rebates, items.rebates);
+        assert!(order.offer.is_empty());
+        assert_eq!(order.consideration.len(), 1);
+    }
+
+    #[test]
+    fn fee_structure_round_trip() {
+        let schedule = FeeSchedule {
+            maker: TradeFeeSchedule {
+                premium: BasisPoints(-5),
+                flat: FlatFee(100),
+                ..Default::default()
+            },
+            taker: TradeFeeSchedule {
+                notional: BasisPoints(25),
+                ..Default::default()
+            },
+            clear_write_notional: BasisPoints(3),
+            clear_redeemed_notional: BasisPoints(2),
+            clear_exercise_notional: BasisPoints(1),
+            address: Address::repeat_byte(0xfe),
+        };
+        let structure = grpc_codegen::FeeStructure::from(schedule);
+        assert_eq!(FeeSchedule::try_from(structure), Ok(schedule));
+    }
+
+    #[test]
+    fn fee_structure_requires_all_fields() {
+        let structure = grpc_codegen::FeeStructure::from(FeeSchedule::default());
+        for (field, structure) in [
+            (
+                "FeeStructure.maker",
+                grpc_codegen::FeeStructure {
+                    maker: None,
+                    ..structure.clone()
+                },
+            ),
+            (
+                "FeeStructure.taker",
+                grpc_codegen::FeeStructure {
+                    taker: None,
+                    ..structure.clone()
+                },
+            ),
+            (
+                "FeeStructure.address",
+                grpc_codegen::FeeStructure {
+                    address: None,
+                    ..structure.clone()
+                },
+            ),
+        ] {
+            assert_eq!(
+                FeeSchedule::try_from(structure),
+                Err(ConversionError::MissingField(field))
+            );
+        }
+    }
+
+    #[test]
+    fn zero_fees_have_no_items() {
+        let items = calculator(0, 0).fee_items(&values(1_000_000));
+        assert_eq!(items, FeeItems::default());
+    }
+}
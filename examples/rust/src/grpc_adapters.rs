@@ -1,110 +1,445 @@
-// Setup From traits allowing the conversion between proto types and ethers types.
+// Setup From traits allowing the conversion between proto types and byte arrays, integers and
+// strings. Conversions to and from the ethers and alloy types live in `ethers_adapters` and
+// `alloy_adapters` respectively, each behind its own cargo feature.
 // Reference: https://github.com/ledgerwatch/interfaces/blob/master/src/lib.rs
 use crate::grpc_codegen::*;
 use arrayref::array_ref;
-use ethers::abi::AbiEncode;
-
-// Macro allowing for proto types to be converted into numbers (and vice versa), moving
-// through the fixed hash type first.
-macro_rules! into_from {
-    ($proto:ty, $hash:ty, $num:ty) => {
-        impl From<$num> for $proto {
-            fn from(value: $num) -> Self {
-                Self::from(<$hash>::from(<[u8; <$hash>::len_bytes()]>::from(value)))
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Errors which can occur when converting proto types, typically received from the network, into
+/// their ethers or alloy equivalents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    /// A required sub-message or field was not set.
+    MissingField(&'static str),
+    /// A bytes field did not have the expected length.
+    InvalidLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// A value was outside of the range allowed for the field.
+    OutOfRange { field: &'static str, value: String },
+    /// A string was not valid hex of the expected length.
+    InvalidHex { field: &'static str, value: String },
+    /// A mixed-case address did not match its EIP-55 checksum.
+    InvalidChecksum(String),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::MissingField(field) => write!(f, "missing required field {field}"),
+            ConversionError::InvalidLength {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "invalid length for {field}, expected {expected} bytes but got {actual}"
+            ),
+            ConversionError::OutOfRange { field, value } => {
+                write!(f, "value {value} is out of range for {field}")
+            }
+            ConversionError::InvalidHex { field, value } => {
+                write!(f, "invalid hex value {value} for {field}")
+            }
+            ConversionError::InvalidChecksum(value) => {
+                write!(f, "invalid EIP-55 checksum for address {value}")
             }
         }
+    }
+}
 
-        impl From<$proto> for $num {
-            fn from(value: $proto) -> Self {
-                Self::from(<$hash>::from(value).0)
-            }
+impl std::error::Error for ConversionError {}
+
+// Allows infallible and fallible conversions to be used interchangeably with `?`.
+impl From<Infallible> for ConversionError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+fn check_length(field: &'static str, bytes: &[u8], expected: usize) -> Result<(), ConversionError> {
+    if bytes.len() != expected {
+        return Err(ConversionError::InvalidLength {
+            field,
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(())
+}
+
+// Shared by the adapters converting received orders into the Seaport types.
+#[cfg(any(feature = "ethers", feature = "alloy"))]
+pub(crate) fn required<T>(value: Option<T>, field: &'static str) -> Result<T, ConversionError> {
+    value.ok_or(ConversionError::MissingField(field))
+}
+
+#[cfg(any(feature = "ethers", feature = "alloy"))]
+pub(crate) fn item_type(value: i32) -> Result<u8, ConversionError> {
+    ItemType::from_i32(value)
+        .map(|item_type| item_type as u8)
+        .ok_or(ConversionError::OutOfRange {
+            field: "ItemType",
+            value: value.to_string(),
+        })
+}
+
+#[cfg(any(feature = "ethers", feature = "alloy"))]
+pub(crate) fn order_type(value: i32) -> Result<u8, ConversionError> {
+    OrderType::from_i32(value)
+        .map(|order_type| order_type as u8)
+        .ok_or(ConversionError::OutOfRange {
+            field: "Order.order_type",
+            value: value.to_string(),
+        })
+}
+
+impl From<[u8; 5]> for H40 {
+    fn from(value: [u8; 5]) -> Self {
+        Self {
+            hi: u32::from_be_bytes(*array_ref!(value, 0, 4)),
+            lo: value[4].into(),
         }
-    };
+    }
+}
+
+// `lo` is really a uint8 carried in a uint32, so anything above 255 is rejected.
+impl TryFrom<&H40> for [u8; 5] {
+    type Error = ConversionError;
+
+    fn try_from(value: &H40) -> Result<Self, Self::Error> {
+        let lo = u8::try_from(value.lo).map_err(|_| ConversionError::OutOfRange {
+            field: "H40.lo",
+            value: value.lo.to_string(),
+        })?;
+
+        let mut v = [0; 5];
+        v[..4].copy_from_slice(&value.hi.to_be_bytes());
+        v[4] = lo;
+        Ok(v)
+    }
 }
 
-into_from!(H128, ethers::types::H128, ethers::types::U128);
-into_from!(H256, ethers::types::H256, ethers::types::U256);
+impl TryFrom<H40> for [u8; 5] {
+    type Error = ConversionError;
 
-impl From<ethers::types::H128> for H128 {
-    fn from(value: ethers::types::H128) -> Self {
+    fn try_from(value: H40) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl From<[u8; 12]> for H96 {
+    fn from(value: [u8; 12]) -> Self {
         Self {
             hi: u64::from_be_bytes(*array_ref!(value, 0, 8)),
-            lo: u64::from_be_bytes(*array_ref!(value, 8, 8)),
+            lo: u32::from_be_bytes(*array_ref!(value, 8, 4)),
         }
     }
 }
 
-impl From<H128> for ethers::types::H128 {
-    fn from(value: H128) -> Self {
-        let mut v = [0; Self::len_bytes()];
+impl From<H96> for [u8; 12] {
+    fn from(value: H96) -> Self {
+        let mut v = [0; 12];
         v[..8].copy_from_slice(&value.hi.to_be_bytes());
         v[8..].copy_from_slice(&value.lo.to_be_bytes());
-        v.into()
+        v
     }
 }
 
-impl From<ethers::types::H160> for H160 {
-    fn from(value: ethers::types::H160) -> Self {
-        Self {
-            hi: Some(ethers::types::H128::from_slice(&value[..16]).into()),
-            lo: u32::from_be_bytes(*array_ref!(value, 16, 4)),
+// Macro allowing for the fixed size hash newtypes to be converted into byte arrays (and vice
+// versa).
+macro_rules! into_from_bytes {
+    ($proto:ident, $len:literal) => {
+        impl From<[u8; $len]> for $proto {
+            fn from(value: [u8; $len]) -> Self {
+                Self(value)
+            }
         }
+
+        impl From<$proto> for [u8; $len] {
+            fn from(value: $proto) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+into_from_bytes!(H128, 16);
+into_from_bytes!(H160, 20);
+into_from_bytes!(H256, 32);
+
+// Parses exactly `N` bytes of hex, with or without a 0x prefix.
+pub(crate) fn decode_hex<const N: usize>(
+    field: &'static str,
+    value: &str,
+) -> Result<[u8; N], ConversionError> {
+    let invalid = || ConversionError::InvalidHex {
+        field,
+        value: value.to_string(),
+    };
+
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() != N * 2 {
+        return Err(invalid());
     }
+
+    let mut bytes = [0; N];
+    hex::decode_to_slice(digits, &mut bytes).map_err(|_| invalid())?;
+    Ok(bytes)
 }
 
-impl From<H160> for ethers::types::H160 {
-    fn from(value: H160) -> Self {
-        type H = ethers::types::H128;
+pub(crate) fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut output = [0; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    hasher.finalize(&mut output);
+    output
+}
 
-        let mut v = [0; Self::len_bytes()];
-        v[..H::len_bytes()].copy_from_slice(H::from(value.hi.unwrap_or_default()).as_fixed_bytes());
-        v[H::len_bytes()..].copy_from_slice(&value.lo.to_be_bytes());
+// Encodes an address as 0x-prefixed hex with the EIP-55 mixed-case checksum.
+fn to_checksum(address: &[u8; 20]) -> String {
+    let digits = hex::encode(address);
+    let hash = keccak256(digits.as_bytes());
 
-        v.into()
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in digits.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+        if nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
     }
+
+    checksummed
 }
 
-impl From<ethers::types::H256> for H256 {
-    fn from(value: ethers::types::H256) -> Self {
-        Self {
-            hi: Some(ethers::types::H128::from_slice(&value[..16]).into()),
-            lo: Some(ethers::types::H128::from_slice(&value[16..]).into()),
+// Macro implementing `LowerHex` for proto hash types, moving through the byte representation.
+macro_rules! lower_hex {
+    ($proto:ident, $len:literal) => {
+        impl fmt::LowerHex for $proto {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if f.alternate() {
+                    write!(f, "0x")?;
+                }
+                let bytes = <[u8; $len]>::from(*self);
+                write!(f, "{}", hex::encode(bytes))
+            }
         }
+    };
+}
+
+lower_hex!(H128, 16);
+lower_hex!(H160, 20);
+lower_hex!(H256, 32);
+
+impl fmt::Display for H128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:#x}")
     }
 }
 
-impl From<H256> for ethers::types::H256 {
-    fn from(value: H256) -> Self {
-        type H = ethers::types::H128;
+impl FromStr for H128 {
+    type Err = ConversionError;
 
-        let mut v = [0; Self::len_bytes()];
-        v[..H::len_bytes()].copy_from_slice(H::from(value.hi.unwrap_or_default()).as_fixed_bytes());
-        v[H::len_bytes()..].copy_from_slice(H::from(value.lo.unwrap_or_default()).as_fixed_bytes());
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(decode_hex::<16>("H128", s)?))
+    }
+}
 
-        v.into()
+// Addresses are displayed with their EIP-55 checksum.
+impl fmt::Display for H160 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", to_checksum(&self.0))
     }
 }
 
-impl From<EthSignature> for ethers::types::Signature {
-    fn from(value: EthSignature) -> Self {
-        let mut bytes = [0u8; 65];
-        bytes[..32].copy_from_slice(value.r.as_slice());
-        bytes[32..64].copy_from_slice(value.s.as_slice());
-        bytes[64] = *value.v.first().unwrap();
-        ethers::types::Signature::try_from(bytes.as_slice()).unwrap()
+// All lowercase or all uppercase addresses are accepted as is, mixed case addresses must carry a
+// valid EIP-55 checksum.
+impl FromStr for H160 {
+    type Err = ConversionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = decode_hex::<20>("H160", s)?;
+
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        let is_mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+            && digits.chars().any(|c| c.is_ascii_uppercase());
+        if is_mixed_case && to_checksum(&address)[2..] != *digits {
+            return Err(ConversionError::InvalidChecksum(s.to_string()));
+        }
+
+        Ok(Self::from(address))
     }
 }
 
-impl From<ethers::types::Signature> for EthSignature {
-    fn from(value: ethers::types::Signature) -> Self {
-        // We don't want to directly encode v, as this will be encoded as a u64 where leading
-        // zeros matter (so it will be included). We know its only 1 byte, therefore only push 1 byte
-        // of data so the signature remains 65 bytes on the wire.
-        Self {
-            v: vec![value.v.to_le_bytes()[0]],
-            r: value.r.encode(),
-            s: value.s.encode(),
+impl fmt::Display for H256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:#x}")
+    }
+}
+
+impl FromStr for H256 {
+    type Err = ConversionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(decode_hex::<32>("H256", s)?))
+    }
+}
+
+// Integers are stored big-endian in the trailing bytes of a hash, any leading bytes are zero.
+fn int_to_bytes<const N: usize>(
+    field: &'static str,
+    value: u128,
+) -> Result<[u8; N], ConversionError> {
+    let bytes = value.to_be_bytes();
+    let (high, low) = bytes.split_at(bytes.len() - N.min(bytes.len()));
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(ConversionError::OutOfRange {
+            field,
+            value: value.to_string(),
+        });
+    }
+
+    let mut v = [0; N];
+    v[N - low.len()..].copy_from_slice(low);
+    Ok(v)
+}
+
+fn bytes_to_int(field: &'static str, bytes: &[u8]) -> Result<u128, ConversionError> {
+    let (high, low) = bytes.split_at(bytes.len() - bytes.len().min(16));
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(ConversionError::OutOfRange {
+            field,
+            value: format!("0x{}", hex::encode(bytes)),
+        });
+    }
+
+    let mut v = [0; 16];
+    v[16 - low.len()..].copy_from_slice(low);
+    Ok(u128::from_be_bytes(v))
+}
+
+// Macros allowing for proto types to be converted from native integers (and vice versa), moving
+// through the big-endian byte representation first. Only conversions which always fit use `From`.
+macro_rules! from_int {
+    (From<$int:ty> for $proto:ident, $len:literal) => {
+        impl From<$int> for $proto {
+            fn from(value: $int) -> Self {
+                Self::from(
+                    int_to_bytes::<$len>(stringify!($proto), value.into())
+                        .expect("integer fits within the hash"),
+                )
+            }
+        }
+    };
+    (TryFrom<$int:ty> for $proto:ident, $len:literal) => {
+        impl TryFrom<$int> for $proto {
+            type Error = ConversionError;
+
+            fn try_from(value: $int) -> Result<Self, Self::Error> {
+                Ok(Self::from(int_to_bytes::<$len>(
+                    stringify!($proto),
+                    value.into(),
+                )?))
+            }
+        }
+    };
+}
+
+macro_rules! into_int {
+    (From<$proto:ident> for $int:ty, $len:literal) => {
+        impl From<$proto> for $int {
+            fn from(value: $proto) -> Self {
+                bytes_to_int(stringify!($proto), &<[u8; $len]>::from(value))
+                    .expect("hash fits within the integer")
+            }
+        }
+    };
+    (TryFrom<$proto:ident> for $int:ty, $len:literal) => {
+        impl TryFrom<$proto> for $int {
+            type Error = ConversionError;
+
+            fn try_from(value: $proto) -> Result<Self, Self::Error> {
+                let value = bytes_to_int(stringify!($proto), &<[u8; $len]>::try_from(value)?)?;
+                <$int>::try_from(value).map_err(|_| ConversionError::OutOfRange {
+                    field: stringify!($int),
+                    value: value.to_string(),
+                })
+            }
+        }
+    };
+}
+
+from_int!(TryFrom<u64> for H40, 5);
+from_int!(TryFrom<u128> for H40, 5);
+into_int!(TryFrom<H40> for u64, 5);
+into_int!(TryFrom<H40> for u128, 5);
+
+from_int!(From<u64> for H96, 12);
+from_int!(TryFrom<u128> for H96, 12);
+into_int!(TryFrom<H96> for u64, 12);
+into_int!(From<H96> for u128, 12);
+
+from_int!(From<u64> for H128, 16);
+from_int!(From<u128> for H128, 16);
+into_int!(TryFrom<H128> for u64, 16);
+into_int!(From<H128> for u128, 16);
+
+from_int!(From<u64> for H160, 20);
+from_int!(From<u128> for H160, 20);
+into_int!(TryFrom<H160> for u64, 20);
+into_int!(TryFrom<H160> for u128, 20);
+
+from_int!(From<u64> for H256, 32);
+from_int!(From<u128> for H256, 32);
+into_int!(TryFrom<H256> for u64, 32);
+into_int!(TryFrom<H256> for u128, 32);
+
+impl EthSignature {
+    /// The validated `(r, s, v)` components of the signature.
+    ///
+    /// Signatures are received from the network, so each component is checked to be the right
+    /// length and `v` to be one of 0, 1, 27 or 28 rather than trusted. An EIP-2098 compact
+    /// signature is sent with an empty `v` and `vs` in place of `s`, in which case `v` is returned
+    /// as 27 or 28.
+    pub fn components(&self) -> Result<([u8; 32], [u8; 32], u8), ConversionError> {
+        check_length("EthSignature.r", self.r.as_slice(), 32)?;
+        check_length("EthSignature.s", self.s.as_slice(), 32)?;
+
+        if self.v.is_empty() {
+            // The top bit of `vs` is the y parity, the remaining bits are `s`.
+            let mut s = *array_ref!(self.s, 0, 32);
+            let v = 27 + (s[0] >> 7);
+            s[0] &= 0x7f;
+            return Ok((*array_ref!(self.r, 0, 32), s, v));
         }
+
+        check_length("EthSignature.v", self.v.as_slice(), 1)?;
+
+        let v = self.v[0];
+        if !matches!(v, 0 | 1 | 27 | 28) {
+            return Err(ConversionError::OutOfRange {
+                field: "EthSignature.v",
+                value: v.to_string(),
+            });
+        }
+
+        Ok((*array_ref!(self.r, 0, 32), *array_ref!(self.s, 0, 32), v))
+    }
+
+    /// The signature as sent to Seaport, i.e. `r`, `s` and `v` concatenated. This is 65 bytes,
+    /// or 64 bytes for an EIP-2098 compact signature.
+    pub fn to_vec(&self) -> Vec<u8> {
+        [self.r.as_slice(), self.s.as_slice(), self.v.as_slice()].concat()
     }
 }
 
@@ -119,3 +454,213 @@ impl From<i32> for ItemType {
         ItemType::from_i32(value).unwrap_or(ItemType::Native)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The test vectors of EIP-55.
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn address_display_is_checksummed() {
+        for address in CHECKSUMMED {
+            let parsed: H160 = address.to_lowercase().parse().unwrap();
+            assert_eq!(parsed.to_string(), address);
+            assert_eq!(address.parse::<H160>().unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn address_from_str() {
+        let address = CHECKSUMMED[0];
+        let expected: H160 = address.parse().unwrap();
+
+        // All lowercase, all uppercase and unprefixed addresses skip the checksum.
+        assert_eq!(address.to_lowercase().parse::<H160>().unwrap(), expected);
+        assert_eq!(
+            format!("0x{}", address[2..].to_uppercase())
+                .parse::<H160>()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(address[2..].parse::<H160>().unwrap(), expected);
+
+        // Flipping the case of one letter breaks the checksum.
+        let wrong = address.replacen("aA", "aa", 1);
+        assert_eq!(
+            wrong.parse::<H160>(),
+            Err(ConversionError::InvalidChecksum(wrong.clone()))
+        );
+
+        assert!(matches!(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA".parse::<H160>(),
+            Err(ConversionError::InvalidHex { field: "H160", .. })
+        ));
+        assert!(matches!(
+            "0xzzaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse::<H160>(),
+            Err(ConversionError::InvalidHex { field: "H160", .. })
+        ));
+    }
+
+    #[test]
+    fn hash_display_and_from_str_round_trip() {
+        let h128 = H128::from(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128);
+        assert_eq!(h128.to_string(), "0x0102030405060708090a0b0c0d0e0f10");
+        assert_eq!(format!("{h128:x}"), "0102030405060708090a0b0c0d0e0f10");
+        assert_eq!(h128.to_string().parse::<H128>().unwrap(), h128);
+
+        let h256 = H256::from(1u64);
+        assert_eq!(h256.to_string(), format!("0x{}01", "0".repeat(62)));
+        assert_eq!(h256.to_string().parse::<H256>().unwrap(), h256);
+        assert_eq!(format!("{:x}", H256::from([0xab; 32])), "ab".repeat(32));
+
+        // Hashes must have their full width.
+        assert!("0x01".parse::<H256>().is_err());
+        assert!(format!("0x{}", "00".repeat(17)).parse::<H128>().is_err());
+    }
+
+    fn signature(r: &[u8], s: &[u8], v: &[u8]) -> EthSignature {
+        EthSignature {
+            r: r.to_vec(),
+            s: s.to_vec(),
+            v: v.to_vec(),
+        }
+    }
+
+    #[test]
+    fn signature_components() {
+        let (r, s) = ([0x11; 32], [0x22; 32]);
+        for v in [0, 1, 27, 28] {
+            let signature = signature(&r, &s, &[v]);
+            assert_eq!(signature.components(), Ok((r, s, v)));
+            assert_eq!(signature.to_vec().len(), 65);
+        }
+
+        // An EIP-2098 compact signature carries the y parity in the top bit of `vs`.
+        let signature = signature(&r, &s, &[]);
+        assert_eq!(signature.components(), Ok((r, s, 27)));
+        assert_eq!(signature.to_vec(), [r, s].concat());
+
+        let mut vs = s;
+        vs[0] |= 0x80;
+        let signature = self::signature(&r, &vs, &[]);
+        assert_eq!(signature.components(), Ok((r, s, 28)));
+        assert_eq!(signature.to_vec().len(), 64);
+    }
+
+    #[test]
+    fn malformed_signatures() {
+        let invalid_length = |field, expected, actual| {
+            Err(ConversionError::InvalidLength {
+                field,
+                expected,
+                actual,
+            })
+        };
+
+        assert_eq!(
+            signature(&[0x11; 31], &[0x22; 32], &[27]).components(),
+            invalid_length("EthSignature.r", 32, 31)
+        );
+        assert_eq!(
+            signature(&[0x11; 32], &[0x22; 33], &[27]).components(),
+            invalid_length("EthSignature.s", 32, 33)
+        );
+        assert_eq!(
+            signature(&[0x11; 32], &[], &[]).components(),
+            invalid_length("EthSignature.s", 32, 0)
+        );
+        assert_eq!(
+            signature(&[0x11; 32], &[0x22; 32], &[0, 27]).components(),
+            invalid_length("EthSignature.v", 1, 2)
+        );
+        assert_eq!(
+            EthSignature::default().components(),
+            invalid_length("EthSignature.r", 32, 0)
+        );
+        assert_eq!(
+            signature(&[0x11; 32], &[0x22; 32], &[29]).components(),
+            Err(ConversionError::OutOfRange {
+                field: "EthSignature.v",
+                value: "29".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn h40_round_trip() {
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let h40 = H40::from(bytes);
+        assert_eq!(
+            h40,
+            H40 {
+                hi: 0x0102_0304,
+                lo: 0x05
+            }
+        );
+        assert_eq!(<[u8; 5]>::try_from(&h40), Ok(bytes));
+        assert_eq!(u64::try_from(h40.clone()), Ok(0x01_0203_0405));
+        assert_eq!(H40::try_from(0x01_0203_0405u64), Ok(h40));
+        assert_eq!(
+            H40::try_from(0xff_ffff_ffffu128).map(u128::try_from),
+            Ok(Ok(0xff_ffff_ffff))
+        );
+
+        // `lo` only carries a byte, and the integers must fit within 40 bits.
+        let invalid = H40 { hi: 0, lo: 0x100 };
+        assert_eq!(
+            <[u8; 5]>::try_from(&invalid),
+            Err(ConversionError::OutOfRange {
+                field: "H40.lo",
+                value: "256".to_string(),
+            })
+        );
+        assert!(u64::try_from(invalid).is_err());
+        assert_eq!(
+            H40::try_from(1u64 << 40),
+            Err(ConversionError::OutOfRange {
+                field: "H40",
+                value: (1u64 << 40).to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn h96_round_trip() {
+        let bytes = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+        ];
+        let h96 = H96::from(bytes);
+        assert_eq!(
+            h96,
+            H96 {
+                hi: 0x0102_0304_0506_0708,
+                lo: 0x090a_0b0c,
+            }
+        );
+        assert_eq!(<[u8; 12]>::from(h96.clone()), bytes);
+        assert_eq!(u128::from(h96.clone()), 0x0102_0304_0506_0708_090a_0b0cu128);
+        assert_eq!(
+            H96::try_from(0x0102_0304_0506_0708_090a_0b0cu128),
+            Ok(h96.clone())
+        );
+        assert!(u64::try_from(h96).is_err());
+
+        let small = H96::from(u64::MAX);
+        assert_eq!(
+            small,
+            H96 {
+                hi: 0xffff_ffff,
+                lo: 0xffff_ffff
+            }
+        );
+        assert_eq!(u64::try_from(small), Ok(u64::MAX));
+        assert!(H96::try_from(1u128 << 96).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+// Helpers for the standard gRPC Health and Reflection services exposed by the Valorem Trade API.
+//
+// Health checks are available per service by passing the fully qualified Valorem service name,
+// see the service name constants below. An empty service name queries the overall server health.
+use crate::health_codegen::{
+    health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
+    HealthCheckResponse,
+};
+use crate::reflection_codegen::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+use prost::Message;
+use prost_types::FileDescriptorProto;
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::{Code, Status, Streaming};
+
+/// The service name used to query the overall health of the server.
+pub const SERVER: &str = "";
+pub const AUTH_SERVICE: &str = "valorem.trade.v1.Auth";
+pub const FEES_SERVICE: &str = "valorem.trade.v1.Fees";
+pub const RFQ_SERVICE: &str = "valorem.trade.v1.RFQ";
+pub const SOFT_QUOTE_SERVICE: &str = "valorem.trade.v1.SoftQuote";
+pub const SPOT_SERVICE: &str = "valorem.trade.v1.Spot";
+
+/// A readiness probe backed by the `grpc.health.v1.Health` service.
+#[derive(Debug, Clone)]
+pub struct HealthProbe<T> {
+    client: HealthClient<T>,
+}
+
+impl<T> HealthProbe<T>
+where
+    T: tonic::client::GrpcService<tonic::body::BoxBody>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    pub fn new(inner: T) -> Self {
+        Self {
+            client: HealthClient::new(inner),
+        }
+    }
+
+    /// Check the current serving status of `service`.
+    pub async fn check(&mut self, service: &str) -> Result<ServingStatus, Status> {
+        let response = self
+            .client
+            .check(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await?;
+
+        Ok(serving_status(response.get_ref()))
+    }
+
+    /// Returns true if `service` is currently serving.
+    pub async fn is_serving(&mut self, service: &str) -> Result<bool, Status> {
+        Ok(self.check(service).await? == ServingStatus::Serving)
+    }
+
+    /// Watch the serving status of `service`, receiving an update whenever it changes.
+    pub async fn watch(&mut self, service: &str) -> Result<HealthWatch, Status> {
+        let stream = self
+            .client
+            .watch(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await?
+            .into_inner();
+
+        Ok(HealthWatch { stream })
+    }
+
+    /// Wait until every service in `services` reports that it is serving.
+    ///
+    /// There is no timeout, a service which never becomes ready is waited on for as long as the
+    /// server keeps its watch open. Wrap the call in `tokio::time::timeout` to bound the wait.
+    pub async fn wait_until_serving(&mut self, services: &[&str]) -> Result<(), Status> {
+        for service in services {
+            let mut watch = self.watch(service).await?;
+            loop {
+                match watch.next().await? {
+                    Some(ServingStatus::Serving) => break,
+                    Some(_) => continue,
+                    None => {
+                        return Err(Status::unavailable(format!(
+                            "Health watch for {service} ended before the service was serving"
+                        )))
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A stream of serving status updates for a single service.
+pub struct HealthWatch {
+    stream: Streaming<HealthCheckResponse>,
+}
+
+impl HealthWatch {
+    /// Wait for the next serving status. Returns `Ok(None)` once the stream has ended.
+    pub async fn next(&mut self) -> Result<Option<ServingStatus>, Status> {
+        Ok(self.stream.message().await?.as_ref().map(serving_status))
+    }
+}
+
+fn serving_status(response: &HealthCheckResponse) -> ServingStatus {
+    ServingStatus::from_i32(response.status).unwrap_or(ServingStatus::Unknown)
+}
+
+/// A client for the `grpc.reflection.v1alpha.ServerReflection` service.
+#[derive(Debug, Clone)]
+pub struct ReflectionClient<T> {
+    client: ServerReflectionClient<T>,
+}
+
+impl<T> ReflectionClient<T>
+where
+    T: tonic::client::GrpcService<tonic::body::BoxBody>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    pub fn new(inner: T) -> Self {
+        Self {
+            client: ServerReflectionClient::new(inner),
+        }
+    }
+
+    /// List the fully qualified names of all services registered on the server.
+    pub async fn list_services(&mut self) -> Result<Vec<String>, Status> {
+        match self
+            .request(MessageRequest::ListServices(String::new()))
+            .await?
+        {
+            MessageResponse::ListServicesResponse(response) => Ok(response
+                .service
+                .into_iter()
+                .map(|service| service.name)
+                .collect()),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    /// Fetch the file descriptors which declare the fully qualified `symbol`, along with their
+    /// transitive dependencies.
+    pub async fn file_containing_symbol(
+        &mut self,
+        symbol: &str,
+    ) -> Result<Vec<FileDescriptorProto>, Status> {
+        let response = self
+            .request(MessageRequest::FileContainingSymbol(symbol.to_string()))
+            .await?;
+        decode_file_descriptors(response)
+    }
+
+    /// Fetch the file descriptors for the proto file `filename`, along with their transitive
+    /// dependencies.
+    pub async fn file_by_filename(
+        &mut self,
+        filename: &str,
+    ) -> Result<Vec<FileDescriptorProto>, Status> {
+        let response = self
+            .request(MessageRequest::FileByFilename(filename.to_string()))
+            .await?;
+        decode_file_descriptors(response)
+    }
+
+    // Reflection is a bidirectional stream, we only ever send a single request per stream.
+    async fn request(
+        &mut self,
+        message_request: MessageRequest,
+    ) -> Result<MessageResponse, Status> {
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(message_request),
+        };
+
+        let mut stream = self
+            .client
+            .server_reflection_info(tokio_stream::once(request))
+            .await?
+            .into_inner();
+
+        match stream.message().await? {
+            Some(response) => match response.message_response {
+                Some(MessageResponse::ErrorResponse(error)) => Err(Status::new(
+                    Code::from_i32(error.error_code),
+                    error.error_message,
+                )),
+                Some(message_response) => Ok(message_response),
+                None => Err(Status::internal("Empty reflection response")),
+            },
+            None => Err(Status::internal(
+                "Reflection stream ended without a response",
+            )),
+        }
+    }
+}
+
+// `tonic::Status` is large, however it is the error type returned by every other call here.
+#[allow(clippy::result_large_err)]
+fn decode_file_descriptors(response: MessageResponse) -> Result<Vec<FileDescriptorProto>, Status> {
+    match response {
+        MessageResponse::FileDescriptorResponse(response) => response
+            .file_descriptor_proto
+            .iter()
+            .map(|bytes| {
+                FileDescriptorProto::decode(bytes.as_slice()).map_err(|error| {
+                    Status::internal(format!("Unable to decode file descriptor: {error}"))
+                })
+            })
+            .collect(),
+        response => Err(unexpected_response(response)),
+    }
+}
+
+fn unexpected_response(response: MessageResponse) -> Status {
+    Status::internal(format!("Unexpected reflection response: {response:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health_codegen::health_server::{Health, HealthServer};
+    use std::collections::HashMap;
+    use std::pin::Pin;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::ReceiverStream;
+    use tokio_stream::{Stream, StreamExt};
+    use tonic::transport::{Channel, Server};
+    use tonic::{Request, Response};
+
+    // A Health service which reports the statuses given for each service in turn. The watch is
+    // held open after the last status if the service is `open`, otherwise it ends.
+    #[derive(Clone, Default)]
+    struct MockHealth {
+        statuses: HashMap<&'static str, (Vec<i32>, bool)>,
+    }
+
+    impl MockHealth {
+        fn with(mut self, service: &'static str, statuses: &[ServingStatus], open: bool) -> Self {
+            let statuses = statuses.iter().map(|status| *status as i32).collect();
+            self.statuses.insert(service, (statuses, open));
+            self
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Health for MockHealth {
+        async fn check(
+            &self,
+            request: Request<HealthCheckRequest>,
+        ) -> Result<Response<HealthCheckResponse>, Status> {
+            let service = &request.get_ref().service;
+            let (statuses, _) = self
+                .statuses
+                .get(service.as_str())
+                .ok_or_else(|| Status::not_found(service))?;
+            Ok(Response::new(HealthCheckResponse {
+                status: statuses.last().copied().unwrap_or_default(),
+            }))
+        }
+
+        type WatchStream =
+            Pin<Box<dyn Stream<Item = Result<HealthCheckResponse, Status>> + Send + 'static>>;
+
+        async fn watch(
+            &self,
+            request: Request<HealthCheckRequest>,
+        ) -> Result<Response<Self::WatchStream>, Status> {
+            let service = &request.get_ref().service;
+            let (statuses, open) = self
+                .statuses
+                .get(service.as_str())
+                .ok_or_else(|| Status::not_found(service))?
+                .clone();
+            let statuses = tokio_stream::iter(statuses)
+                .map(|status| HealthCheckResponse { status })
+                .map(Ok)
+                .chain(tokio_stream::pending().take(usize::from(open)));
+            Ok(Response::new(Box::pin(statuses)))
+        }
+    }
+
+    async fn serve(health: MockHealth) -> HealthProbe<Channel> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(async move {
+            while sender
+                .send(listener.accept().await.map(|(stream, _)| stream))
+                .await
+                .is_ok()
+            {}
+        });
+        tokio::spawn(
+            Server::builder()
+                .add_service(HealthServer::new(health))
+                .serve_with_incoming(ReceiverStream::new(receiver)),
+        );
+
+        let channel = Channel::from_shared(endpoint)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        HealthProbe::new(channel)
+    }
+
+    #[test]
+    fn status_mapping() {
+        for (status, expected) in [
+            (0, ServingStatus::Unknown),
+            (1, ServingStatus::Serving),
+            (2, ServingStatus::NotServing),
+            (3, ServingStatus::ServiceUnknown),
+            (42, ServingStatus::Unknown),
+            (-1, ServingStatus::Unknown),
+        ] {
+            assert_eq!(serving_status(&HealthCheckResponse { status }), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn check() {
+        let mut probe = serve(
+            MockHealth::default()
+                .with(SERVER, &[ServingStatus::Serving], false)
+                .with(RFQ_SERVICE, &[ServingStatus::NotServing], false),
+        )
+        .await;
+
+        assert_eq!(probe.check(SERVER).await.unwrap(), ServingStatus::Serving);
+        assert!(probe.is_serving(SERVER).await.unwrap());
+        assert!(!probe.is_serving(RFQ_SERVICE).await.unwrap());
+        assert_eq!(
+            probe.check(SPOT_SERVICE).await.unwrap_err().code(),
+            Code::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_until_serving() {
+        let mut probe = serve(
+            MockHealth::default()
+                .with(SERVER, &[ServingStatus::Serving], true)
+                .with(
+                    RFQ_SERVICE,
+                    &[
+                        ServingStatus::Unknown,
+                        ServingStatus::NotServing,
+                        ServingStatus::Serving,
+                    ],
+                    true,
+                )
+                .with(SPOT_SERVICE, &[ServingStatus::NotServing], false)
+                .with(FEES_SERVICE, &[ServingStatus::NotServing], true),
+        )
+        .await;
+
+        probe
+            .wait_until_serving(&[SERVER, RFQ_SERVICE])
+            .await
+            .unwrap();
+
+        let error = probe.wait_until_serving(&[SPOT_SERVICE]).await.unwrap_err();
+        assert_eq!(error.code(), Code::Unavailable);
+
+        // A service which never becomes ready is waited on until the caller gives up.
+        let wait = probe.wait_until_serving(&[FEES_SERVICE]);
+        assert!(tokio::time::timeout(Duration::from_millis(100), wait)
+            .await
+            .is_err());
+    }
+}
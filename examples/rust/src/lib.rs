@@ -1,8 +1,56 @@
+#[cfg(feature = "alloy")]
+pub mod alloy_adapters;
+#[cfg(feature = "ethers")]
 pub mod bindings;
+pub mod chains;
+#[cfg(feature = "ethers")]
+pub mod client;
+pub mod error;
+#[cfg(feature = "ethers")]
+pub mod ethers_adapters;
+#[cfg(feature = "ethers")]
+pub mod fee_compliance;
+#[cfg(feature = "ethers")]
+pub mod fees;
 pub mod grpc_adapters;
+pub mod health;
+pub mod order_builder;
+#[cfg(feature = "ethers")]
+pub mod order_status;
+pub mod quote_request;
+#[cfg(feature = "ethers")]
+pub mod remote_signer;
+pub mod salt;
+#[cfg(feature = "ethers")]
+pub mod seaport;
+#[cfg(feature = "serde")]
+pub mod serde_adapters;
+#[cfg(feature = "ethers")]
+pub mod session_store;
+#[cfg(feature = "ethers")]
+pub mod siwe_config;
+#[cfg(feature = "ethers")]
+pub mod spot;
+pub mod types;
 pub mod utils;
 
 pub mod grpc_codegen {
     #![allow(clippy::derive_partial_eq_without_eq)]
+    pub use crate::types::{H128, H160, H256};
     tonic::include_proto!("valorem.trade.v1");
 }
+
+pub mod health_codegen {
+    #![allow(clippy::derive_partial_eq_without_eq)]
+    tonic::include_proto!("grpc.health.v1");
+}
+
+pub mod reflection_codegen {
+    #![allow(clippy::derive_partial_eq_without_eq)]
+    tonic::include_proto!("grpc.reflection.v1alpha");
+}
+
+pub mod rpc_codegen {
+    #![allow(clippy::derive_partial_eq_without_eq)]
+    tonic::include_proto!("google.rpc");
+}
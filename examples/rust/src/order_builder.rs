@@ -0,0 +1,532 @@
+// A builder for the Seaport orders makers return in response to a `QuoteRequest`.
+//
+// Valorem option trades always have two legs, the ERC-1155 option token and the USDC premium.
+// When the taker buys, the maker offers the option in exchange for the premium, and when the
+// taker sells the maker offers the premium in exchange for the option. The builder lays out the
+// legs from the `Action` of the request, so quoting code only needs to supply the price and size.
+use crate::chains::Chain;
+use crate::grpc_codegen::{
+    Action, ConsiderationItem, ItemType, OfferItem, Order, OrderType, QuoteRequest, H160, H256,
+};
+use crate::salt::ValoremSalt;
+use std::fmt;
+use std::iter;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long an order is valid for if no validity is given, 20 minutes.
+pub const DEFAULT_ORDER_VALIDITY: Duration = Duration::from_secs(20 * 60);
+
+/// An error returned when building an invalid order.
+#[derive(Debug)]
+pub enum OrderBuilderError {
+    /// A required field was not set on the builder.
+    MissingField(&'static str),
+    /// The amount of options was zero.
+    ZeroAmount,
+    /// The action was not `Buy` or `Sell`.
+    InvalidAction(Action),
+    /// The order would not be valid for any time.
+    InvalidValidity,
+    /// The order could not be signed.
+    Signing(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for OrderBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderBuilderError::MissingField(field) => write!(f, "Order is missing the {field}"),
+            OrderBuilderError::ZeroAmount => write!(f, "Order amount must not be zero"),
+            OrderBuilderError::InvalidAction(action) => {
+                write!(f, "Order action must be Buy or Sell, not {action:?}")
+            }
+            OrderBuilderError::InvalidValidity => {
+                write!(f, "Order must be valid for a non-zero amount of time")
+            }
+            OrderBuilderError::Signing(error) => write!(f, "Unable to sign the order: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for OrderBuilderError {}
+
+// One side of a trade, with a fixed amount over the life of the order.
+#[derive(Clone, Copy)]
+struct Leg {
+    item_type: ItemType,
+    token: H160,
+    identifier_or_criteria: Option<H256>,
+    amount: H256,
+}
+
+/// Builds the `Order` for a trade of Valorem Clear options against USDC.
+#[derive(Clone, Debug)]
+pub struct OrderBuilder {
+    action: Option<Action>,
+    clear_address: Option<H160>,
+    usdc_address: Option<H160>,
+    option_id: Option<H256>,
+    amount: Option<H256>,
+    price: Option<H256>,
+    offerer: Option<H160>,
+    recipient: Option<H160>,
+    order_type: OrderType,
+    start_time: Option<u64>,
+    validity: Duration,
+    salt: Option<H256>,
+    additional_offer: Vec<OfferItem>,
+    additional_consideration: Vec<ConsiderationItem>,
+}
+
+impl Default for OrderBuilder {
+    fn default() -> Self {
+        Self {
+            action: None,
+            clear_address: None,
+            usdc_address: None,
+            option_id: None,
+            amount: None,
+            price: None,
+            offerer: None,
+            recipient: None,
+            order_type: OrderType::FullOpen,
+            start_time: None,
+            validity: DEFAULT_ORDER_VALIDITY,
+            salt: None,
+            additional_offer: Vec::new(),
+            additional_consideration: Vec::new(),
+        }
+    }
+}
+
+impl OrderBuilder {
+    /// A builder for an order on `chain`, trading the Clear option tokens against USDC.
+    pub fn new(chain: Chain) -> Self {
+        Self {
+            clear_address: Some(chain.clear_address()),
+            usdc_address: Some(chain.usdc_address()),
+            ..Self::default()
+        }
+    }
+
+    /// A builder for the order quoted in response to `request`, taking the action, option, amount
+    /// and Clear address from the request. The USDC address is taken from the `chains` registry
+    /// when the request is for a known chain, and must otherwise be set on the builder.
+    pub fn from_request(request: &QuoteRequest) -> Self {
+        let mut builder = request
+            .chain_id
+            .and_then(|chain_id| Chain::try_from(chain_id).ok())
+            .map_or_else(Self::default, Self::new)
+            .action(request.action.into());
+        if request.token_address.is_some() {
+            builder.clear_address = request.token_address;
+        }
+        builder.option_id = request.identifier_or_criteria;
+        builder.amount = request.amount;
+        builder
+    }
+
+    /// The action of the taker, i.e. `Buy` if the maker is offering the option.
+    pub fn action(mut self, action: Action) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// The id of the Valorem Clear long option token.
+    pub fn option_id(mut self, option_id: impl Into<H256>) -> Self {
+        self.option_id = Some(option_id.into());
+        self
+    }
+
+    /// The number of options traded.
+    pub fn amount(mut self, amount: impl Into<H256>) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    /// The premium for the options, in the smallest unit of USDC.
+    pub fn price(mut self, price: impl Into<H256>) -> Self {
+        self.price = Some(price.into());
+        self
+    }
+
+    /// The maker signing the order.
+    pub fn offerer(mut self, offerer: impl Into<H160>) -> Self {
+        self.offerer = Some(offerer.into());
+        self
+    }
+
+    /// The recipient of the consideration, the offerer by default.
+    pub fn recipient(mut self, recipient: impl Into<H160>) -> Self {
+        self.recipient = Some(recipient.into());
+        self
+    }
+
+    /// The Seaport order type, `FullOpen` by default so the order cannot be partially filled.
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = order_type;
+        self
+    }
+
+    /// The unix timestamp the order is valid from, the current time by default.
+    pub fn start_time(mut self, start_time: u64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// How long the order is valid for from its start time, see `DEFAULT_ORDER_VALIDITY`.
+    pub fn valid_for(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    /// The order salt, a random `ValoremSalt` by default.
+    pub fn salt(mut self, salt: impl Into<H256>) -> Self {
+        self.salt = Some(salt.into());
+        self
+    }
+
+    /// The Clear contract, the chain's deployment by default.
+    pub fn clear_address(mut self, clear_address: impl Into<H160>) -> Self {
+        self.clear_address = Some(clear_address.into());
+        self
+    }
+
+    /// The USDC token, the chain's USDC by default.
+    pub fn usdc_address(mut self, usdc_address: impl Into<H160>) -> Self {
+        self.usdc_address = Some(usdc_address.into());
+        self
+    }
+
+    /// Offer items to follow the option or premium leg, e.g. the maker fee.
+    pub fn additional_offer(mut self, items: impl IntoIterator<Item = OfferItem>) -> Self {
+        self.additional_offer.extend(items);
+        self
+    }
+
+    /// Consideration items to follow the option or premium leg, e.g. the taker fee.
+    pub fn additional_consideration(
+        mut self,
+        items: impl IntoIterator<Item = ConsiderationItem>,
+    ) -> Self {
+        self.additional_consideration.extend(items);
+        self
+    }
+
+    /// The unsigned order, as returned for a soft quote.
+    pub fn build(self) -> Result<Order, OrderBuilderError> {
+        let action = self
+            .action
+            .ok_or(OrderBuilderError::MissingField("action"))?;
+        let option_id = self
+            .option_id
+            .ok_or(OrderBuilderError::MissingField("option id"))?;
+        let amount = self
+            .amount
+            .ok_or(OrderBuilderError::MissingField("amount"))?;
+        if amount == H256::default() {
+            return Err(OrderBuilderError::ZeroAmount);
+        }
+        let price = self.price.ok_or(OrderBuilderError::MissingField("price"))?;
+        let offerer = self
+            .offerer
+            .ok_or(OrderBuilderError::MissingField("offerer"))?;
+        let recipient = self.recipient.unwrap_or(offerer);
+        let clear_address = self
+            .clear_address
+            .ok_or(OrderBuilderError::MissingField("clear address"))?;
+        let usdc_address = self
+            .usdc_address
+            .ok_or(OrderBuilderError::MissingField("usdc address"))?;
+
+        let start_time = self.start_time.unwrap_or_else(now);
+        let end_time = start_time
+            .checked_add(self.validity.as_secs())
+            .filter(|end_time| *end_time > start_time)
+            .ok_or(OrderBuilderError::InvalidValidity)?;
+
+        let option = Leg {
+            item_type: ItemType::Erc1155,
+            token: clear_address,
+            identifier_or_criteria: Some(option_id),
+            amount,
+        };
+        let premium = Leg {
+            item_type: ItemType::Erc20,
+            token: usdc_address,
+            identifier_or_criteria: None,
+            amount: price,
+        };
+        let (offer, consideration) = match action {
+            Action::Buy => (option, premium),
+            Action::Sell => (premium, option),
+            Action::Invalid => return Err(OrderBuilderError::InvalidAction(action)),
+        };
+
+        Ok(Order {
+            offerer: Some(offerer),
+            zone: None,
+            offer: iter::once(OfferItem {
+                item_type: offer.item_type as i32,
+                token: Some(offer.token),
+                identifier_or_criteria: offer.identifier_or_criteria,
+                start_amount: Some(offer.amount),
+                end_amount: Some(offer.amount),
+            })
+            .chain(self.additional_offer)
+            .collect(),
+            consideration: iter::once(ConsiderationItem {
+                item_type: consideration.item_type as i32,
+                token: Some(consideration.token),
+                identifier_or_criteria: consideration.identifier_or_criteria,
+                start_amount: Some(consideration.amount),
+                end_amount: Some(consideration.amount),
+                recipient: Some(recipient),
+            })
+            .chain(self.additional_consideration)
+            .collect(),
+            order_type: self.order_type as i32,
+            start_time: Some(H256::from(start_time)),
+            end_time: Some(H256::from(end_time)),
+            zone_hash: None,
+            salt: Some(self.salt.unwrap_or_else(|| ValoremSalt::random().into())),
+            conduit_key: None,
+        })
+    }
+}
+
+#[cfg(feature = "ethers")]
+impl OrderBuilder {
+    /// The order signed by `signer` for the Seaport deployment described by `domain`, where
+    /// `counter` is the offerer's current counter on that deployment.
+    pub async fn sign<S>(
+        self,
+        signer: &S,
+        domain: crate::seaport::SeaportDomain,
+        counter: ethers::types::U256,
+    ) -> Result<crate::grpc_codegen::SignedOrder, OrderBuilderError>
+    where
+        S: crate::seaport::OrderSigner,
+        S::Error: 'static,
+    {
+        crate::seaport::sign_order(signer, domain, self.build()?, counter)
+            .await
+            .map_err(|error| OrderBuilderError::Signing(Box::new(error)))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFFERER: H160 = H160([0x01; 20]);
+
+    fn builder(action: Action) -> OrderBuilder {
+        OrderBuilder::new(Chain::ArbitrumOne)
+            .action(action)
+            .option_id(7u64)
+            .amount(5u64)
+            .price(10_000_000u64)
+            .offerer(OFFERER)
+    }
+
+    fn fee_item(amount: u64) -> OfferItem {
+        OfferItem {
+            item_type: ItemType::Erc20 as i32,
+            token: Some(Chain::ArbitrumOne.usdc_address()),
+            identifier_or_criteria: None,
+            start_amount: Some(amount.into()),
+            end_amount: Some(amount.into()),
+        }
+    }
+
+    fn timestamp(value: Option<H256>) -> u64 {
+        u64::from_be_bytes(value.unwrap().0[24..].try_into().unwrap())
+    }
+
+    #[test]
+    fn legs() {
+        let option = OfferItem {
+            item_type: ItemType::Erc1155 as i32,
+            token: Some(Chain::ArbitrumOne.clear_address()),
+            identifier_or_criteria: Some(7u64.into()),
+            start_amount: Some(5u64.into()),
+            end_amount: Some(5u64.into()),
+        };
+        let premium = OfferItem {
+            item_type: ItemType::Erc20 as i32,
+            token: Some(Chain::ArbitrumOne.usdc_address()),
+            identifier_or_criteria: None,
+            start_amount: Some(10_000_000u64.into()),
+            end_amount: Some(10_000_000u64.into()),
+        };
+        let consideration = |item: &OfferItem| ConsiderationItem {
+            item_type: item.item_type,
+            token: item.token,
+            identifier_or_criteria: item.identifier_or_criteria,
+            start_amount: item.start_amount,
+            end_amount: item.end_amount,
+            recipient: Some(OFFERER),
+        };
+
+        // The taker buys, so the maker offers the option for the premium.
+        let order = builder(Action::Buy).build().unwrap();
+        assert_eq!(order.offerer, Some(OFFERER));
+        assert_eq!(order.offer, vec![option.clone()]);
+        assert_eq!(order.consideration, vec![consideration(&premium)]);
+        assert_eq!(order.order_type, OrderType::FullOpen as i32);
+
+        // The taker sells, so the maker offers the premium for the option.
+        let order = builder(Action::Sell).build().unwrap();
+        assert_eq!(order.offer, vec![premium]);
+        assert_eq!(order.consideration, vec![consideration(&option)]);
+
+        let recipient = H160([0x02; 20]);
+        let order = builder(Action::Sell).recipient(recipient).build().unwrap();
+        assert_eq!(order.consideration[0].recipient, Some(recipient));
+
+        assert!(matches!(
+            builder(Action::Invalid).build(),
+            Err(OrderBuilderError::InvalidAction(Action::Invalid))
+        ));
+        assert!(matches!(
+            builder(Action::Buy).amount(0u64).build(),
+            Err(OrderBuilderError::ZeroAmount)
+        ));
+        assert!(matches!(
+            OrderBuilder::default().action(Action::Buy).build(),
+            Err(OrderBuilderError::MissingField("option id"))
+        ));
+    }
+
+    #[test]
+    fn default_validity() {
+        let before = now();
+        let order = builder(Action::Buy).build().unwrap();
+        let after = now();
+
+        let start_time = timestamp(order.start_time);
+        assert!(start_time >= before && start_time <= after);
+        assert_eq!(timestamp(order.end_time), start_time + 20 * 60);
+
+        let order = builder(Action::Buy)
+            .start_time(1_700_000_000)
+            .valid_for(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(order.start_time, Some(1_700_000_000u64.into()));
+        assert_eq!(order.end_time, Some(1_700_000_060u64.into()));
+
+        assert!(matches!(
+            builder(Action::Buy).valid_for(Duration::ZERO).build(),
+            Err(OrderBuilderError::InvalidValidity)
+        ));
+        assert!(matches!(
+            builder(Action::Buy).start_time(u64::MAX).build(),
+            Err(OrderBuilderError::InvalidValidity)
+        ));
+    }
+
+    #[test]
+    fn salt() {
+        let first = builder(Action::Buy).build().unwrap().salt.unwrap();
+        let second = builder(Action::Buy).build().unwrap().salt.unwrap();
+        assert!(ValoremSalt::is_tagged(&first));
+        assert!(ValoremSalt::is_tagged(&second));
+        assert_ne!(first, second);
+
+        let salt = ValoremSalt::random().with_sub_tag(0xf1);
+        let order = builder(Action::Buy).salt(salt).build().unwrap();
+        assert_eq!(order.salt, Some(salt.into()));
+    }
+
+    #[test]
+    fn additional_items() {
+        let fee = ConsiderationItem {
+            item_type: ItemType::Erc20 as i32,
+            token: Some(Chain::ArbitrumOne.usdc_address()),
+            identifier_or_criteria: None,
+            start_amount: Some(100_000u64.into()),
+            end_amount: Some(100_000u64.into()),
+            recipient: Some(H160([0xfe; 20])),
+        };
+        let order = builder(Action::Buy)
+            .additional_offer([fee_item(50_000)])
+            .additional_offer([fee_item(1)])
+            .additional_consideration([fee.clone()])
+            .build()
+            .unwrap();
+
+        assert_eq!(order.offer.len(), 3);
+        assert_eq!(order.offer[0].item_type, ItemType::Erc1155 as i32);
+        assert_eq!(order.offer[1..], [fee_item(50_000), fee_item(1)]);
+        assert_eq!(order.consideration.len(), 2);
+        assert_eq!(order.consideration[0].item_type, ItemType::Erc20 as i32);
+        assert_eq!(order.consideration[1], fee);
+    }
+
+    #[test]
+    fn from_request() {
+        let request = QuoteRequest::builder()
+            .chain(Chain::ArbitrumSepolia)
+            .action(Action::Sell)
+            .option_id(7u64)
+            .amount(5u64)
+            .build()
+            .unwrap();
+        let order = OrderBuilder::from_request(&request)
+            .price(10_000_000u64)
+            .offerer(OFFERER)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            order.offer[0].token,
+            Some(Chain::ArbitrumSepolia.usdc_address())
+        );
+        assert_eq!(
+            order.consideration[0].token,
+            Some(Chain::ArbitrumSepolia.clear_address())
+        );
+        assert_eq!(
+            order.consideration[0].identifier_or_criteria,
+            Some(7u64.into())
+        );
+        assert_eq!(order.consideration[0].start_amount, Some(5u64.into()));
+    }
+
+    #[cfg(feature = "ethers")]
+    #[tokio::test]
+    async fn sign() {
+        use crate::seaport::{verify_signed_order, SeaportDomain};
+        use ethers::signers::{LocalWallet, Signer};
+
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                .parse()
+                .unwrap();
+        let domain = SeaportDomain::new(Chain::ArbitrumOne.id());
+        let counter = ethers::types::U256::from(3);
+        let signed_order = builder(Action::Buy)
+            .offerer(wallet.address())
+            .sign(&wallet, domain, counter)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            signed_order
+                .parameters
+                .as_ref()
+                .and_then(|order| order.offerer),
+            Some(wallet.address().into())
+        );
+        verify_signed_order(&signed_order, domain, counter, Some(wallet.address())).unwrap();
+        assert!(verify_signed_order(&signed_order, domain, counter + 1, None).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+// Helpers modelling how Seaport prices an order at a given block timestamp.
+//
+// Seaport only fulfills an order while `start_time <= timestamp < end_time`, and linearly
+// interpolates each item's amount from `start_amount` to `end_amount` over that window. Offer
+// amounts are rounded down and consideration amounts rounded up, so rounding always favours the
+// offerer. See `AmountDeriver` in the Seaport contracts for the reference implementation.
+use crate::grpc_adapters::{required, ConversionError};
+use crate::grpc_codegen::Order;
+use ethers::types::U256;
+use std::fmt;
+
+/// Where a block timestamp falls relative to an order's `start_time..end_time` window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderStatus {
+    /// The order cannot be fulfilled yet, it starts in the given number of seconds.
+    NotStarted { starts_in: U256 },
+    /// The order can be fulfilled for the given number of seconds.
+    Active { remaining: U256 },
+    /// The order can no longer be fulfilled.
+    Expired,
+}
+
+impl OrderStatus {
+    /// The status of an order valid for `start_time..end_time` at `timestamp`.
+    pub fn at(start_time: U256, end_time: U256, timestamp: U256) -> Self {
+        if timestamp < start_time {
+            OrderStatus::NotStarted {
+                starts_in: start_time - timestamp,
+            }
+        } else if timestamp < end_time {
+            OrderStatus::Active {
+                remaining: end_time - timestamp,
+            }
+        } else {
+            OrderStatus::Expired
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self, OrderStatus::Active { .. })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        matches!(self, OrderStatus::Expired)
+    }
+
+    /// The number of seconds the order can still be fulfilled for, zero unless it is active.
+    pub fn seconds_remaining(&self) -> U256 {
+        match self {
+            OrderStatus::Active { remaining } => *remaining,
+            _ => U256::zero(),
+        }
+    }
+}
+
+/// The amounts Seaport would transfer for each item of an order, in the same order as the items.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CurrentAmounts {
+    pub offer: Vec<U256>,
+    pub consideration: Vec<U256>,
+}
+
+/// An error returned when calculating the current amounts of an order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderAmountError {
+    /// The order is missing a required field.
+    Conversion(ConversionError),
+    /// The order is outside of its time window, so would be rejected by Seaport.
+    Inactive(OrderStatus),
+    /// Interpolating an amount overflowed, so the order would be rejected by Seaport.
+    Overflow,
+}
+
+impl fmt::Display for OrderAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderAmountError::Conversion(error) => write!(f, "Invalid order: {error}"),
+            OrderAmountError::Inactive(OrderStatus::NotStarted { starts_in }) => {
+                write!(f, "Order is not active for another {starts_in} seconds")
+            }
+            OrderAmountError::Inactive(_) => write!(f, "Order has expired"),
+            OrderAmountError::Overflow => write!(f, "Order amount overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for OrderAmountError {}
+
+impl From<ConversionError> for OrderAmountError {
+    fn from(value: ConversionError) -> Self {
+        OrderAmountError::Conversion(value)
+    }
+}
+
+/// The amount Seaport would transfer at `timestamp` for an item moving from `start_amount` to
+/// `end_amount` over `start_time..end_time`, rounding up if `round_up` is set and down otherwise.
+///
+/// Returns `None` if `timestamp` is outside of the window or the interpolation overflows, in
+/// which case Seaport would reject the order.
+pub fn current_amount(
+    start_amount: U256,
+    end_amount: U256,
+    start_time: U256,
+    end_time: U256,
+    timestamp: U256,
+    round_up: bool,
+) -> Option<U256> {
+    if !OrderStatus::at(start_time, end_time, timestamp).is_active() {
+        return None;
+    }
+    if start_amount == end_amount {
+        return Some(end_amount);
+    }
+
+    let duration = end_time - start_time;
+    let elapsed = timestamp - start_time;
+    let remaining = duration - elapsed;
+    let total = start_amount
+        .checked_mul(remaining)?
+        .checked_add(end_amount.checked_mul(elapsed)?)?;
+
+    // Matches Seaport's branchless rounding, where a total of zero is always zero.
+    if total.is_zero() {
+        Some(U256::zero())
+    } else if round_up {
+        Some((total - 1) / duration + 1)
+    } else {
+        Some(total / duration)
+    }
+}
+
+impl Order {
+    /// The status of the order at the block `timestamp`.
+    pub fn status(&self, timestamp: U256) -> Result<OrderStatus, ConversionError> {
+        let (start_time, end_time) = self.time_window()?;
+        Ok(OrderStatus::at(start_time, end_time, timestamp))
+    }
+
+    /// The amounts Seaport would transfer for each offer and consideration item if the order was
+    /// fulfilled at the block `timestamp`.
+    pub fn current_amounts(&self, timestamp: U256) -> Result<CurrentAmounts, OrderAmountError> {
+        let (start_time, end_time) = self.time_window()?;
+        let status = OrderStatus::at(start_time, end_time, timestamp);
+        if !status.is_active() {
+            return Err(OrderAmountError::Inactive(status));
+        }
+
+        let amount = |start_amount, end_amount, round_up| {
+            current_amount(
+                start_amount,
+                end_amount,
+                start_time,
+                end_time,
+                timestamp,
+                round_up,
+            )
+            .ok_or(OrderAmountError::Overflow)
+        };
+
+        Ok(CurrentAmounts {
+            offer: self
+                .offer
+                .iter()
+                .map(|item| {
+                    amount(
+                        required(item.start_amount, "OfferItem.start_amount")?.into(),
+                        required(item.end_amount, "OfferItem.end_amount")?.into(),
+                        false,
+                    )
+                })
+                .collect::<Result<_, _>>()?,
+            consideration: self
+                .consideration
+                .iter()
+                .map(|item| {
+                    amount(
+                        required(item.start_amount, "ConsiderationItem.start_amount")?.into(),
+                        required(item.end_amount, "ConsiderationItem.end_amount")?.into(),
+                        true,
+                    )
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn time_window(&self) -> Result<(U256, U256), ConversionError> {
+        Ok((
+            required(self.start_time, "Order.start_time")?.into(),
+            required(self.end_time, "Order.end_time")?.into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc_codegen::{ConsiderationItem, OfferItem};
+
+    fn amount(start: u64, end: u64, duration: u64, elapsed: u64, round_up: bool) -> Option<U256> {
+        current_amount(
+            start.into(),
+            end.into(),
+            U256::from(1_000u64),
+            U256::from(1_000 + duration),
+            U256::from(1_000 + elapsed),
+            round_up,
+        )
+    }
+
+    #[test]
+    fn status_boundaries() {
+        let at = |timestamp: u64| OrderStatus::at(10u64.into(), 20u64.into(), timestamp.into());
+
+        assert_eq!(
+            at(7),
+            OrderStatus::NotStarted {
+                starts_in: 3.into()
+            }
+        );
+        assert_eq!(
+            at(10),
+            OrderStatus::Active {
+                remaining: 10.into()
+            }
+        );
+        assert_eq!(at(19).seconds_remaining(), U256::one());
+        assert!(at(20).is_expired());
+        assert_eq!(at(25).seconds_remaining(), U256::zero());
+    }
+
+    #[test]
+    fn interpolation_rounding() {
+        // A third of the way from 100 to 200 is 133.33...
+        assert_eq!(amount(100, 200, 3, 1, false), Some(133.into()));
+        assert_eq!(amount(100, 200, 3, 1, true), Some(134.into()));
+
+        // A descending amount rounds the same way, 10 to 0 at three quarters being 2.5.
+        assert_eq!(amount(10, 0, 4, 3, false), Some(2.into()));
+        assert_eq!(amount(10, 0, 4, 3, true), Some(3.into()));
+
+        // Exact amounts are not rounded either way.
+        assert_eq!(amount(100, 200, 4, 2, false), Some(150.into()));
+        assert_eq!(amount(100, 200, 4, 2, true), Some(150.into()));
+        assert_eq!(amount(100, 200, 4, 0, true), Some(100.into()));
+
+        // The last second is still short of the end amount.
+        assert_eq!(amount(0, 1_000, 1_000, 999, false), Some(999.into()));
+        assert_eq!(amount(0, 3, 1_000, 999, false), Some(2.into()));
+        assert_eq!(amount(0, 3, 1_000, 999, true), Some(3.into()));
+
+        // A total of zero is zero even when rounding up.
+        assert_eq!(amount(0, 5, 10, 0, true), Some(U256::zero()));
+
+        // Constant amounts are returned as is.
+        assert_eq!(amount(7, 7, 3, 1, true), Some(7.into()));
+    }
+
+    #[test]
+    fn interpolation_outside_window_or_overflowing() {
+        assert_eq!(amount(100, 200, 3, 3, false), None);
+        assert_eq!(
+            current_amount(
+                1.into(),
+                2.into(),
+                10u64.into(),
+                20u64.into(),
+                9u64.into(),
+                false
+            ),
+            None
+        );
+        assert_eq!(
+            current_amount(
+                U256::MAX,
+                U256::zero(),
+                U256::zero(),
+                2u64.into(),
+                U256::zero(),
+                false
+            ),
+            None
+        );
+    }
+
+    fn order(offer: (u64, u64), consideration: (u64, u64)) -> Order {
+        Order {
+            offer: vec![OfferItem {
+                start_amount: Some(offer.0.into()),
+                end_amount: Some(offer.1.into()),
+                ..Default::default()
+            }],
+            consideration: vec![ConsiderationItem {
+                start_amount: Some(consideration.0.into()),
+                end_amount: Some(consideration.1.into()),
+                ..Default::default()
+            }],
+            start_time: Some(0u64.into()),
+            end_time: Some(3u64.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn order_amounts_favour_the_offerer() {
+        let order = order((100, 200), (100, 200));
+
+        assert_eq!(
+            order.current_amounts(1u64.into()),
+            Ok(CurrentAmounts {
+                offer: vec![133.into()],
+                consideration: vec![134.into()],
+            })
+        );
+        assert_eq!(
+            order.status(1u64.into()),
+            Ok(OrderStatus::Active {
+                remaining: 2.into()
+            })
+        );
+    }
+
+    #[test]
+    fn order_amount_errors() {
+        let order = order((1, 2), (1, 2));
+        assert_eq!(
+            order.current_amounts(3u64.into()),
+            Err(OrderAmountError::Inactive(OrderStatus::Expired))
+        );
+
+        let mut missing = order.clone();
+        missing.consideration[0].end_amount = None;
+        assert_eq!(
+            missing.current_amounts(1u64.into()),
+            Err(OrderAmountError::Conversion(ConversionError::MissingField(
+                "ConsiderationItem.end_amount"
+            )))
+        );
+
+        let mut overflowing = order;
+        overflowing.offer[0].start_amount = Some(U256::MAX.into());
+        assert_eq!(
+            overflowing.current_amounts(1u64.into()),
+            Err(OrderAmountError::Overflow)
+        );
+    }
+}
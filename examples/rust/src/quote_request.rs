@@ -0,0 +1,297 @@
+// A builder for the `QuoteRequest` sent by takers to the RFQ and soft quote services.
+//
+// The builder fills in the fields which are fixed for a chain from the `chains` registry, and
+// `build` enforces the constraints from the "Trading Valorem Clear Options" section of the
+// repository README which can be checked without calling the Clear contract: the chain must be
+// supported and the token must be its Clear deployment. Whether the option exists, has not
+// expired and is supported by a maker is still only known once it is quoted.
+//
+// A chain outside of the registry, e.g. a local devnet, can only be used through
+// `QuoteRequestBuilder::devnet_chain`, which skips the chain and Clear address checks.
+use crate::chains::{Chain, SeaportVersion};
+use crate::grpc_codegen::{Action, ItemType, QuoteRequest, H128, H160, H256};
+use crate::utils::ulid;
+use std::fmt;
+
+/// An error returned when building an invalid `QuoteRequest`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteRequestError {
+    /// A required field was not set on the builder.
+    MissingField(&'static str),
+    /// The amount of options requested was zero.
+    ZeroAmount,
+    /// The action was not `Buy` or `Sell`.
+    InvalidAction(Action),
+    /// The chain is not supported by the Valorem Trade API.
+    UnsupportedChain(H256),
+    /// The Clear address is not the deployment on the chain.
+    ClearAddressMismatch { expected: H160, actual: H160 },
+}
+
+impl fmt::Display for QuoteRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteRequestError::MissingField(field) => {
+                write!(f, "Quote request is missing the {field}")
+            }
+            QuoteRequestError::ZeroAmount => write!(f, "Quote request amount must not be zero"),
+            QuoteRequestError::InvalidAction(action) => {
+                write!(
+                    f,
+                    "Quote request action must be Buy or Sell, not {action:?}"
+                )
+            }
+            QuoteRequestError::UnsupportedChain(chain_id) => {
+                write!(f, "Quote request chain {chain_id:#x} is not supported")
+            }
+            QuoteRequestError::ClearAddressMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Quote request Clear address {actual:#x} is not the deployment at {expected:#x}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuoteRequestError {}
+
+/// Builds a `QuoteRequest` for a Valorem Clear option, see `QuoteRequest::builder`.
+#[derive(Clone, Debug, Default)]
+pub struct QuoteRequestBuilder {
+    ulid: Option<H128>,
+    taker_address: Option<H160>,
+    option_id: Option<H256>,
+    amount: Option<H256>,
+    action: Option<Action>,
+    chain_id: Option<H256>,
+    clear_address: Option<H160>,
+    devnet: bool,
+    seaport_version: SeaportVersion,
+}
+
+impl QuoteRequest {
+    /// A builder for a request to buy or sell Valorem Clear options.
+    pub fn builder() -> QuoteRequestBuilder {
+        QuoteRequestBuilder::default()
+    }
+}
+
+impl QuoteRequestBuilder {
+    /// The ULID identifying the request. A new ULID is generated if this is not set.
+    pub fn ulid(mut self, ulid: impl Into<H128>) -> Self {
+        self.ulid = Some(ulid.into());
+        self
+    }
+
+    /// The address the quote is for, which is optional.
+    pub fn taker_address(mut self, taker_address: impl Into<H160>) -> Self {
+        self.taker_address = Some(taker_address.into());
+        self
+    }
+
+    /// The id of the Valorem Clear long option token.
+    pub fn option_id(mut self, option_id: impl Into<H256>) -> Self {
+        self.option_id = Some(option_id.into());
+        self
+    }
+
+    /// The number of options to buy or sell.
+    pub fn amount(mut self, amount: impl Into<H256>) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    pub fn action(mut self, action: Action) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// The chain the options are traded on, which determines the Clear and Seaport addresses.
+    pub fn chain(mut self, chain: Chain) -> Self {
+        self.chain_id = Some(chain.into());
+        self.devnet = false;
+        self
+    }
+
+    /// The id of the chain the options are traded on, which must be in the `chains` registry.
+    pub fn chain_id(mut self, chain_id: impl Into<H256>) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self.devnet = false;
+        self
+    }
+
+    /// The Clear contract, which must be the chain's deployment. This is the default, so setting
+    /// it only checks a configured address against the registry.
+    pub fn clear_address(mut self, clear_address: impl Into<H160>) -> Self {
+        self.clear_address = Some(clear_address.into());
+        self
+    }
+
+    /// A chain outside of the `chains` registry, such as a local devnet, with its own Clear
+    /// deployment. Neither is checked, so the request is only of use to a Trade API serving that
+    /// chain.
+    pub fn devnet_chain(
+        mut self,
+        chain_id: impl Into<H256>,
+        clear_address: impl Into<H160>,
+    ) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self.clear_address = Some(clear_address.into());
+        self.devnet = true;
+        self
+    }
+
+    /// The Seaport release the order will be settled on, Seaport 1.5 by default.
+    pub fn seaport_version(mut self, seaport_version: SeaportVersion) -> Self {
+        self.seaport_version = seaport_version;
+        self
+    }
+
+    pub fn build(self) -> Result<QuoteRequest, QuoteRequestError> {
+        let chain_id = self
+            .chain_id
+            .ok_or(QuoteRequestError::MissingField("chain"))?;
+        let clear_address = match (Chain::try_from(chain_id), self.clear_address) {
+            (_, Some(clear_address)) if self.devnet => clear_address,
+            (Ok(chain), Some(clear_address)) if clear_address != chain.clear_address() => {
+                return Err(QuoteRequestError::ClearAddressMismatch {
+                    expected: chain.clear_address(),
+                    actual: clear_address,
+                })
+            }
+            (Ok(chain), _) => chain.clear_address(),
+            (Err(_), _) => return Err(QuoteRequestError::UnsupportedChain(chain_id)),
+        };
+        let option_id = self
+            .option_id
+            .ok_or(QuoteRequestError::MissingField("option id"))?;
+        let amount = self
+            .amount
+            .ok_or(QuoteRequestError::MissingField("amount"))?;
+        if amount == H256::default() {
+            return Err(QuoteRequestError::ZeroAmount);
+        }
+        let action = self
+            .action
+            .ok_or(QuoteRequestError::MissingField("action"))?;
+        if !matches!(action, Action::Buy | Action::Sell) {
+            return Err(QuoteRequestError::InvalidAction(action));
+        }
+
+        Ok(QuoteRequest {
+            ulid: Some(self.ulid.unwrap_or_else(ulid::generate)),
+            taker_address: self.taker_address,
+            item_type: ItemType::Erc1155 as i32,
+            token_address: Some(clear_address),
+            identifier_or_criteria: Some(option_id),
+            amount: Some(amount),
+            action: action as i32,
+            chain_id: Some(chain_id),
+            seaport_address: Some(self.seaport_version.address()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> QuoteRequestBuilder {
+        QuoteRequest::builder()
+            .option_id(1u64)
+            .amount(5u64)
+            .action(Action::Buy)
+    }
+
+    #[test]
+    fn registered_chain() {
+        let request = builder().chain(Chain::ArbitrumOne).build().unwrap();
+
+        assert_eq!(request.chain_id, Some(H256::from(42161u64)));
+        assert_eq!(
+            request.token_address,
+            Some(Chain::ArbitrumOne.clear_address())
+        );
+        assert_eq!(
+            request.seaport_address,
+            Some(SeaportVersion::V1_5.address())
+        );
+        assert_eq!(
+            builder().chain_id(42161u64).build().unwrap().token_address,
+            request.token_address
+        );
+    }
+
+    #[test]
+    fn unsupported_chain() {
+        assert_eq!(
+            builder().chain_id(31337u64).build(),
+            Err(QuoteRequestError::UnsupportedChain(H256::from(31337u64)))
+        );
+
+        // Setting the Clear address does not make a chain supported.
+        assert_eq!(
+            builder()
+                .chain_id(31337u64)
+                .clear_address(H160([0x11; 20]))
+                .build(),
+            Err(QuoteRequestError::UnsupportedChain(H256::from(31337u64)))
+        );
+        assert_eq!(
+            builder().build(),
+            Err(QuoteRequestError::MissingField("chain"))
+        );
+    }
+
+    #[test]
+    fn clear_address_must_be_the_deployment() {
+        let request = builder()
+            .chain(Chain::ArbitrumSepolia)
+            .clear_address(Chain::ArbitrumSepolia.clear_address())
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.token_address,
+            Some(Chain::ArbitrumSepolia.clear_address())
+        );
+
+        assert_eq!(
+            builder()
+                .chain(Chain::ArbitrumSepolia)
+                .clear_address(H160([0x11; 20]))
+                .build(),
+            Err(QuoteRequestError::ClearAddressMismatch {
+                expected: Chain::ArbitrumSepolia.clear_address(),
+                actual: H160([0x11; 20]),
+            })
+        );
+    }
+
+    #[test]
+    fn devnet_chain() {
+        let clear_address = H160([0x11; 20]);
+        let request = builder()
+            .devnet_chain(31337u64, clear_address)
+            .build()
+            .unwrap();
+        assert_eq!(request.chain_id, Some(H256::from(31337u64)));
+        assert_eq!(request.token_address, Some(clear_address));
+
+        // A registered chain id is not checked either, as a devnet may fork it.
+        let request = builder()
+            .devnet_chain(Chain::ArbitrumOne, clear_address)
+            .build()
+            .unwrap();
+        assert_eq!(request.token_address, Some(clear_address));
+
+        // Setting a registered chain afterwards checks it again.
+        assert_eq!(
+            builder()
+                .devnet_chain(31337u64, clear_address)
+                .chain_id(31337u64)
+                .build(),
+            Err(QuoteRequestError::UnsupportedChain(H256::from(31337u64)))
+        );
+    }
+}
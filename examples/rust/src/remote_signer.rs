@@ -0,0 +1,428 @@
+// An ethers `Signer` which forwards signing requests to a separate signing service over JSON-RPC,
+// so the private key does not need to live in the quoting process.
+//
+// The service must serve the following methods, taking and returning hex encoded values:
+//
+// - `eth_accounts`, returning the address of the signing key first.
+// - `eth_sign` with `[address, message]`, returning the EIP-191 personal message signature.
+// - `eth_signTransaction` with `[transaction]`, including its `chainId`, returning the RLP encoded
+//   signed transaction.
+// - `eth_signTypedData_v4` with `[address, typedData]`, returning the EIP-712 signature of the
+//   full typed data, e.g. of a Seaport order. The service sees what it signs, so it can apply its
+//   own policy to the orders it is asked to sign.
+//
+// An ethers `Signer` is only given the EIP-712 hashes of a payload rather than its typed data, so
+// `Signer::sign_typed_data` needs the non-standard `valorem_signTypedData` with
+// `[address, domainSeparator, structHash]`, returning the signature over the two hashes. This
+// makes the service a blind hash signer, so it is disabled unless opted into with
+// `with_blind_signing`. Seaport orders are signed with their full typed data through
+// `OrderSigner` instead.
+//
+// Every signature is recovered locally and rejected if it was not made by the expected address.
+use crate::seaport::{OrderSigner, SeaportOrder};
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, ProviderError};
+use ethers::signers::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Address, Bytes, Signature, H256};
+use ethers::utils::{keccak256, rlp};
+use std::fmt;
+
+/// An error returned by a `RemoteSigner`.
+#[derive(Debug)]
+pub enum RemoteSignerError {
+    /// The JSON-RPC request to the signing service failed.
+    Rpc(ProviderError),
+    /// The signing service has no accounts.
+    NoAccounts,
+    /// The EIP-712 hashes of the payload could not be calculated.
+    Eip712(String),
+    /// Typed data was to be signed by its hashes alone, which is disabled unless opted into with
+    /// `with_blind_signing`.
+    BlindSigningDisabled,
+    /// The signing service returned a malformed signature or transaction.
+    InvalidResponse(String),
+    /// The signature was made by an address other than the signer's.
+    SignerMismatch { expected: Address, actual: Address },
+}
+
+impl fmt::Display for RemoteSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteSignerError::Rpc(error) => write!(f, "Signing request failed: {error}"),
+            RemoteSignerError::NoAccounts => write!(f, "Signing service has no accounts"),
+            RemoteSignerError::Eip712(error) => {
+                write!(f, "Unable to hash the typed data: {error}")
+            }
+            RemoteSignerError::BlindSigningDisabled => write!(
+                f,
+                "Signing typed data by its hashes alone is disabled, use sign_typed_data_v4"
+            ),
+            RemoteSignerError::InvalidResponse(error) => {
+                write!(f, "Invalid response from the signing service: {error}")
+            }
+            RemoteSignerError::SignerMismatch { expected, actual } => write!(
+                f,
+                "Signature was made by {actual:?} rather than {expected:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RemoteSignerError {}
+
+/// A `Signer` for an account held by a JSON-RPC signing service, e.g. over `Http`.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner<C> {
+    client: C,
+    address: Address,
+    chain_id: u64,
+    blind_signing: bool,
+}
+
+impl<C: JsonRpcClient> RemoteSigner<C> {
+    /// A signer for `address` on the signing service at `client`. As with a local wallet, the
+    /// chain id is 1 until set with `with_chain_id`.
+    pub fn new(client: C, address: Address) -> Self {
+        Self {
+            client,
+            address,
+            chain_id: 1,
+            blind_signing: false,
+        }
+    }
+
+    /// A signer for the first account of the signing service at `client`.
+    pub async fn connect(client: C) -> Result<Self, RemoteSignerError> {
+        let accounts: Vec<Address> = request(&client, "eth_accounts", ()).await?;
+        let address = *accounts.first().ok_or(RemoteSignerError::NoAccounts)?;
+        Ok(Self::new(client, address))
+    }
+
+    /// Allows `Signer::sign_typed_data` to ask the signing service to sign EIP-712 hashes with
+    /// `valorem_signTypedData`. The service cannot tell what it is signing, so anyone able to send
+    /// it requests can have it sign any typed data for the account, e.g. a token permit. Only
+    /// enable this for a service which is not reachable by anything but this signer.
+    pub fn with_blind_signing(mut self) -> Self {
+        self.blind_signing = true;
+        self
+    }
+
+    /// Signs `typed_data` with `eth_signTypedData_v4`, sending the full typed data to the signing
+    /// service.
+    pub async fn sign_typed_data_v4(
+        &self,
+        typed_data: &TypedData,
+    ) -> Result<Signature, RemoteSignerError> {
+        let digest = typed_data
+            .encode_eip712()
+            .map_err(|error| RemoteSignerError::Eip712(error.to_string()))?;
+        let signature: Bytes = request(
+            &self.client,
+            "eth_signTypedData_v4",
+            (self.address, typed_data),
+        )
+        .await?;
+        self.check_signer(decode_signature(&signature)?, H256(digest))
+    }
+
+    // Checks a signature returned by the signing service was made by our address.
+    fn check_signer(
+        &self,
+        signature: Signature,
+        hash: H256,
+    ) -> Result<Signature, RemoteSignerError> {
+        let actual = signature
+            .recover(hash)
+            .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))?;
+        if actual != self.address {
+            return Err(RemoteSignerError::SignerMismatch {
+                expected: self.address,
+                actual,
+            });
+        }
+
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl<C: JsonRpcClient> Signer for RemoteSigner<C> {
+    type Error = RemoteSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let message = message.as_ref();
+        let signature: Bytes = request(
+            &self.client,
+            "eth_sign",
+            (self.address, Bytes::from(message.to_vec())),
+        )
+        .await?;
+        self.check_signer(
+            decode_signature(&signature)?,
+            ethers::utils::hash_message(message),
+        )
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        tx.set_from(self.address);
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+
+        // ethers does not serialize the chain id of a transaction, so it is added for the service
+        // to sign for our chain rather than its own default.
+        let mut params = serde_json::to_value(&tx)
+            .map_err(|error| RemoteSignerError::Rpc(ProviderError::SerdeJson(error)))?;
+        params["chainId"] = serde_json::json!(tx.chain_id());
+
+        let raw: Bytes = request(&self.client, "eth_signTransaction", [params]).await?;
+        let (signed, signature) = TypedTransaction::decode_signed(&rlp::Rlp::new(&raw))
+            .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))?;
+        if signed.sighash() != tx.sighash() {
+            return Err(RemoteSignerError::InvalidResponse(
+                "the signed transaction differs from the request".into(),
+            ));
+        }
+
+        self.check_signer(signature, tx.sighash())
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        if !self.blind_signing {
+            return Err(RemoteSignerError::BlindSigningDisabled);
+        }
+
+        let domain_separator = payload
+            .domain_separator()
+            .map_err(|error| RemoteSignerError::Eip712(error.to_string()))?;
+        let struct_hash = payload
+            .struct_hash()
+            .map_err(|error| RemoteSignerError::Eip712(error.to_string()))?;
+
+        let signature: Bytes = request(
+            &self.client,
+            "valorem_signTypedData",
+            (self.address, H256(domain_separator), H256(struct_hash)),
+        )
+        .await?;
+
+        let digest = keccak256([&[0x19, 0x01][..], &domain_separator, &struct_hash].concat());
+        self.check_signer(decode_signature(&signature)?, H256(digest))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<C: JsonRpcClient> OrderSigner for RemoteSigner<C> {
+    async fn sign_seaport_order(&self, order: &SeaportOrder) -> Result<Signature, Self::Error> {
+        self.sign_typed_data_v4(&order.typed_data()).await
+    }
+}
+
+async fn request<C, T, R>(client: &C, method: &str, params: T) -> Result<R, RemoteSignerError>
+where
+    C: JsonRpcClient,
+    T: serde::Serialize + Send + Sync + fmt::Debug,
+    R: serde::de::DeserializeOwned + Send,
+{
+    client
+        .request(method, params)
+        .await
+        .map_err(|error| RemoteSignerError::Rpc(error.into()))
+}
+
+fn decode_signature(signature: &[u8]) -> Result<Signature, RemoteSignerError> {
+    Signature::try_from(signature)
+        .map_err(|error| RemoteSignerError::InvalidResponse(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::seaport::OrderComponents;
+    use crate::seaport::{SeaportDomain, SeaportOrder};
+    use ethers::providers::{JsonRpcError, MockError};
+    use ethers::signers::LocalWallet;
+    use ethers::types::TransactionRequest;
+    use serde_json::{json, Value};
+
+    // A signing service holding `wallet`, which signs as requested regardless of the address
+    // given in the request.
+    #[derive(Debug)]
+    struct WalletService {
+        wallet: LocalWallet,
+    }
+
+    impl WalletService {
+        fn new(key: &str) -> Self {
+            Self {
+                wallet: key.parse().unwrap(),
+            }
+        }
+
+        fn respond(&self, method: &str, params: Value) -> Result<Value, MockError> {
+            let signature = match method {
+                "eth_accounts" => return Ok(json!([self.wallet.address()])),
+                "eth_sign" => {
+                    let message: Bytes = param(&params, 1)?;
+                    self.wallet.sign_hash(ethers::utils::hash_message(message))
+                }
+                "eth_signTransaction" => {
+                    let tx: TypedTransaction = param(&params, 0)?;
+                    let signature = self.wallet.sign_transaction_sync(&tx).unwrap();
+                    return Ok(json!(tx.rlp_signed(&signature)));
+                }
+                "eth_signTypedData_v4" => {
+                    let typed_data: TypedData = param(&params, 1)?;
+                    self.wallet
+                        .sign_hash(H256(typed_data.encode_eip712().unwrap()))
+                }
+                "valorem_signTypedData" => {
+                    let domain_separator: H256 = param(&params, 1)?;
+                    let struct_hash: H256 = param(&params, 2)?;
+                    self.wallet.sign_hash(H256(keccak256(
+                        [&[0x19, 0x01][..], &domain_separator[..], &struct_hash[..]].concat(),
+                    )))
+                }
+                _ => {
+                    return Err(MockError::JsonRpcError(JsonRpcError {
+                        code: -32601,
+                        message: format!("method {method} not found"),
+                        data: None,
+                    }))
+                }
+            };
+
+            Ok(json!(Bytes::from(signature.unwrap().to_vec())))
+        }
+    }
+
+    fn param<T: serde::de::DeserializeOwned>(params: &Value, index: usize) -> Result<T, MockError> {
+        Ok(serde_json::from_value(params[index].clone())?)
+    }
+
+    #[async_trait]
+    impl JsonRpcClient for WalletService {
+        type Error = MockError;
+
+        async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+        where
+            T: fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            let response = self.respond(method, serde_json::to_value(params)?)?;
+            Ok(serde_json::from_value(response)?)
+        }
+    }
+
+    const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const OTHER_KEY: &str = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    fn typed_data() -> SeaportOrder {
+        SeaportOrder {
+            domain: SeaportDomain::new(42161u64),
+            components: OrderComponents {
+                salt: 1234u64.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn signatures_recover_to_the_account() {
+        let address = WalletService::new(KEY).wallet.address();
+        let signer = RemoteSigner::connect(WalletService::new(KEY))
+            .await
+            .unwrap()
+            .with_chain_id(42161u64);
+        assert_eq!(signer.address(), address);
+
+        let signature = signer.sign_message("Sign in to Valorem").await.unwrap();
+        assert_eq!(signature.recover("Sign in to Valorem").unwrap(), address);
+
+        let payload = typed_data();
+        let signature = signer.sign_seaport_order(&payload).await.unwrap();
+        assert_eq!(signature.recover(H256(payload.digest())).unwrap(), address);
+
+        let tx: TypedTransaction = TransactionRequest::pay(Address::repeat_byte(0x11), 1u64)
+            .nonce(0u64)
+            .gas(21_000u64)
+            .gas_price(1u64)
+            .into();
+        let signature = signer.sign_transaction(&tx).await.unwrap();
+        let mut expected = tx.clone();
+        expected.set_from(address);
+        expected.set_chain_id(42161u64);
+        assert_eq!(signature.recover(expected.sighash()).unwrap(), address);
+    }
+
+    #[tokio::test]
+    async fn signatures_by_another_key_are_rejected() {
+        let expected = WalletService::new(KEY).wallet.address();
+        let actual = WalletService::new(OTHER_KEY).wallet.address();
+        let signer = RemoteSigner::new(WalletService::new(OTHER_KEY), expected);
+
+        assert!(matches!(
+            signer.sign_message("Sign in to Valorem").await,
+            Err(RemoteSignerError::SignerMismatch { expected: e, actual: a })
+                if e == expected && a == actual
+        ));
+        assert!(matches!(
+            signer.sign_seaport_order(&typed_data()).await,
+            Err(RemoteSignerError::SignerMismatch { expected: e, actual: a })
+                if e == expected && a == actual
+        ));
+        assert!(matches!(
+            signer.with_blind_signing().sign_typed_data(&typed_data()).await,
+            Err(RemoteSignerError::SignerMismatch { expected: e, actual: a })
+                if e == expected && a == actual
+        ));
+    }
+
+    #[tokio::test]
+    async fn blind_signing_is_opt_in() {
+        let address = WalletService::new(KEY).wallet.address();
+        let signer = RemoteSigner::new(WalletService::new(KEY), address);
+        let payload = typed_data();
+        assert!(matches!(
+            signer.sign_typed_data(&payload).await,
+            Err(RemoteSignerError::BlindSigningDisabled)
+        ));
+
+        let signature = signer
+            .with_blind_signing()
+            .sign_typed_data(&payload)
+            .await
+            .unwrap();
+        assert_eq!(signature.recover(H256(payload.digest())).unwrap(), address);
+    }
+
+    #[tokio::test]
+    async fn rpc_errors_are_reported() {
+        let error = request::<_, _, Bytes>(&WalletService::new(KEY), "eth_unknown", ())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RemoteSignerError::Rpc(_)));
+    }
+}
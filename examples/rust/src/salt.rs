@@ -0,0 +1,134 @@
+// Order salts tagged with the Valorem domain tag, used to attribute Seaport orders to Valorem.
+//
+// The tag `60DD32CF` is stored in the low 32 bits of the salt, the remaining bits are entropy. A
+// firm may also stamp its own 32 bit sub-tag into the 32 bits above the domain tag to attribute
+// orders to itself, at the cost of that much entropy:
+//
+//   | entropy (192 bits) | sub-tag (32 bits, optional) | domain tag (32 bits) |
+use crate::grpc_adapters::{keccak256, ConversionError};
+use crate::grpc_codegen::{H128, H256};
+use arrayref::array_ref;
+
+/// Valorem's domain tag, as stored in the low 32 bits of the salt.
+pub const VALOREM_DOMAIN_TAG: u32 = 0x60dd32cf;
+
+/// A Seaport order salt carrying the Valorem domain tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ValoremSalt(H256);
+
+impl ValoremSalt {
+    /// A tagged salt with random entropy.
+    pub fn random() -> Self {
+        let mut entropy = [0u8; 32];
+        getrandom::getrandom(&mut entropy).expect("the OS random number generator is unavailable");
+        Self::tagged(entropy)
+    }
+
+    /// A tagged salt with entropy derived from the ULID of a quote request, so an order quoted
+    /// for the same request always has the same salt.
+    pub fn from_ulid(ulid: H128) -> Self {
+        Self::tagged(keccak256(&ulid.0))
+    }
+
+    /// The salt with the firm `sub_tag` stamped above the domain tag.
+    pub fn with_sub_tag(mut self, sub_tag: u32) -> Self {
+        self.0 .0[24..28].copy_from_slice(&sub_tag.to_be_bytes());
+        self
+    }
+
+    /// The 32 bits above the domain tag, which are the firm sub-tag if one was stamped and
+    /// entropy otherwise.
+    pub fn sub_tag(&self) -> u32 {
+        u32::from_be_bytes(*array_ref!(self.0 .0, 24, 4))
+    }
+
+    /// Whether the salt carries the firm `sub_tag`.
+    pub fn has_sub_tag(&self, sub_tag: u32) -> bool {
+        self.sub_tag() == sub_tag
+    }
+
+    /// Whether `salt`, e.g. the salt of a received `Order`, carries the Valorem domain tag.
+    pub fn is_tagged(salt: &H256) -> bool {
+        u32::from_be_bytes(*array_ref!(salt.0, 28, 4)) == VALOREM_DOMAIN_TAG
+    }
+
+    fn tagged(mut salt: [u8; 32]) -> Self {
+        salt[28..].copy_from_slice(&VALOREM_DOMAIN_TAG.to_be_bytes());
+        Self(H256(salt))
+    }
+}
+
+impl From<ValoremSalt> for H256 {
+    fn from(value: ValoremSalt) -> Self {
+        value.0
+    }
+}
+
+// Only salts carrying the domain tag are accepted.
+impl TryFrom<H256> for ValoremSalt {
+    type Error = ConversionError;
+
+    fn try_from(value: H256) -> Result<Self, Self::Error> {
+        if !Self::is_tagged(&value) {
+            return Err(ConversionError::OutOfRange {
+                field: "Order.salt",
+                value: format!("{value:#x}"),
+            });
+        }
+
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_salts_are_tagged() {
+        let first = ValoremSalt::random();
+        let second = ValoremSalt::random();
+        assert_ne!(first, second);
+
+        for salt in [first, second] {
+            let salt = H256::from(salt);
+            assert!(ValoremSalt::is_tagged(&salt));
+            assert_eq!(salt.0[28..], [0x60, 0xdd, 0x32, 0xcf]);
+            assert_eq!(ValoremSalt::try_from(salt).map(H256::from), Ok(salt));
+        }
+    }
+
+    #[test]
+    fn ulid_salts_are_deterministic() {
+        let ulid = H128::from([0x42; 16]);
+        let salt = ValoremSalt::from_ulid(ulid);
+        assert_eq!(salt, ValoremSalt::from_ulid(ulid));
+        assert_ne!(salt, ValoremSalt::from_ulid(H128::from([0x43; 16])));
+        assert!(ValoremSalt::is_tagged(&salt.into()));
+    }
+
+    #[test]
+    fn sub_tags() {
+        let salt = ValoremSalt::random().with_sub_tag(0xdeadbeef);
+        assert!(salt.has_sub_tag(0xdeadbeef));
+        assert_eq!(salt.sub_tag(), 0xdeadbeef);
+        assert!(ValoremSalt::is_tagged(&salt.into()));
+        assert_eq!(
+            H256::from(salt).0[24..],
+            [0xde, 0xad, 0xbe, 0xef, 0x60, 0xdd, 0x32, 0xcf]
+        );
+    }
+
+    #[test]
+    fn untagged_salts_are_rejected() {
+        let salt = H256([0x11; 32]);
+        assert!(!ValoremSalt::is_tagged(&salt));
+        assert_eq!(
+            ValoremSalt::try_from(salt),
+            Err(ConversionError::OutOfRange {
+                field: "Order.salt",
+                value: format!("{salt:#x}"),
+            })
+        );
+    }
+}
@@ -0,0 +1,899 @@
+// Offline Seaport 1.5 order hashing, signing and signature verification.
+//
+// Seaport orders are signed as EIP-712 typed data: the `OrderComponents` struct hash (returned
+// on-chain by `getOrderHash`) under the Seaport domain (whose separator is returned by
+// `information`). Both are computed locally here, so signing or verifying an order only needs
+// the offerer's counter from the chain.
+use crate::bindings::erc1271::Erc1271;
+use crate::bindings::seaport::{ConsiderationItem, OfferItem, OrderComponents};
+use crate::chains::SeaportVersion;
+use crate::grpc_adapters::{required, ConversionError};
+use crate::grpc_codegen::{EthSignature, Order, SignedOrder};
+use async_trait::async_trait;
+use ethers::abi::{encode, Token};
+use ethers::providers::Middleware;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip712::{EIP712Domain, Eip712, Eip712DomainType, TypedData};
+use ethers::types::{Address, Signature, SignatureError, H160, U256};
+use ethers::utils::keccak256;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+
+/// The EIP-712 domain name of Seaport.
+pub const SEAPORT_NAME: &str = "Seaport";
+
+/// The EIP-712 domain version of Seaport 1.5.
+pub const SEAPORT_VERSION: &str = SeaportVersion::V1_5.as_str();
+
+/// The address Seaport 1.5 is deployed at on every supported chain,
+/// `0x00000000000000ADc04C56Bf30aC9d3c0aAF14dC`.
+pub const SEAPORT_ADDRESS: Address = H160(SeaportVersion::V1_5.address().0);
+
+// The value returned by `isValidSignature` for a valid EIP-1271 signature.
+const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+const OFFER_ITEM_TYPE: &str = "OfferItem(uint8 itemType,address token,uint256 identifierOrCriteria,uint256 startAmount,uint256 endAmount)";
+const CONSIDERATION_ITEM_TYPE: &str = "ConsiderationItem(uint8 itemType,address token,uint256 identifierOrCriteria,uint256 startAmount,uint256 endAmount,address recipient)";
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ORDER_COMPONENTS_TYPE: &str = "OrderComponents(address offerer,address zone,OfferItem[] offer,ConsiderationItem[] consideration,uint8 orderType,uint256 startTime,uint256 endTime,bytes32 zoneHash,uint256 salt,bytes32 conduitKey,uint256 counter)";
+
+/// The EIP-712 domain of a Seaport 1.5 deployment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeaportDomain {
+    pub chain_id: U256,
+    pub verifying_contract: Address,
+}
+
+impl SeaportDomain {
+    /// The domain of the canonical Seaport 1.5 deployment on `chain_id`.
+    pub fn new(chain_id: impl Into<U256>) -> Self {
+        Self {
+            chain_id: chain_id.into(),
+            verifying_contract: SEAPORT_ADDRESS,
+        }
+    }
+
+    /// The domain separator, as returned by `information` on the Seaport contract.
+    pub fn separator(&self) -> [u8; 32] {
+        EIP712Domain::from(*self).separator()
+    }
+}
+
+impl From<SeaportDomain> for EIP712Domain {
+    fn from(value: SeaportDomain) -> Self {
+        Self {
+            name: Some(SEAPORT_NAME.to_string()),
+            version: Some(SEAPORT_VERSION.to_string()),
+            chain_id: Some(value.chain_id),
+            verifying_contract: Some(value.verifying_contract),
+            salt: None,
+        }
+    }
+}
+
+/// A Seaport order along with the domain it is to be signed for, implementing `Eip712` so it can
+/// be signed with any ethers `Signer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeaportOrder {
+    pub domain: SeaportDomain,
+    pub components: OrderComponents,
+}
+
+impl SeaportOrder {
+    /// Builds the order components from `order` and the offerer's current Seaport `counter`.
+    pub fn new(
+        domain: SeaportDomain,
+        order: &Order,
+        counter: U256,
+    ) -> Result<Self, ConversionError> {
+        Ok(Self {
+            domain,
+            components: OrderComponents::try_from((order, counter))?,
+        })
+    }
+
+    /// The order hash, as returned by `getOrderHash` on the Seaport contract.
+    pub fn order_hash(&self) -> [u8; 32] {
+        hash_order_components(&self.components)
+    }
+
+    /// The EIP-712 digest which is signed by the offerer.
+    pub fn digest(&self) -> [u8; 32] {
+        keccak256(
+            [
+                &[0x19, 0x01],
+                &self.domain.separator()[..],
+                &self.order_hash()[..],
+            ]
+            .concat(),
+        )
+    }
+
+    /// The order as EIP-712 typed data, as passed to `eth_signTypedData_v4` by seaport-js.
+    pub fn typed_data(&self) -> TypedData {
+        let components = &self.components;
+        let offer = components
+            .offer
+            .iter()
+            .map(|item| {
+                json!({
+                    "itemType": item.item_type,
+                    "token": format!("{:?}", item.token),
+                    "identifierOrCriteria": item.identifier_or_criteria.to_string(),
+                    "startAmount": item.start_amount.to_string(),
+                    "endAmount": item.end_amount.to_string(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let consideration = components
+            .consideration
+            .iter()
+            .map(|item| {
+                json!({
+                    "itemType": item.item_type,
+                    "token": format!("{:?}", item.token),
+                    "identifierOrCriteria": item.identifier_or_criteria.to_string(),
+                    "startAmount": item.start_amount.to_string(),
+                    "endAmount": item.end_amount.to_string(),
+                    "recipient": format!("{:?}", item.recipient),
+                })
+            })
+            .collect::<Vec<_>>();
+        let message = json!({
+            "offerer": format!("{:?}", components.offerer),
+            "zone": format!("{:?}", components.zone),
+            "offer": offer,
+            "consideration": consideration,
+            "orderType": components.order_type,
+            "startTime": components.start_time.to_string(),
+            "endTime": components.end_time.to_string(),
+            "zoneHash": format!("0x{}", hex::encode(components.zone_hash)),
+            "salt": components.salt.to_string(),
+            "conduitKey": format!("0x{}", hex::encode(components.conduit_key)),
+            "counter": components.counter.to_string(),
+        });
+
+        TypedData {
+            domain: self.domain.into(),
+            types: [
+                EIP712_DOMAIN_TYPE,
+                ORDER_COMPONENTS_TYPE,
+                OFFER_ITEM_TYPE,
+                CONSIDERATION_ITEM_TYPE,
+            ]
+            .into_iter()
+            .map(eip712_type)
+            .collect(),
+            primary_type: "OrderComponents".to_string(),
+            message: match message {
+                Value::Object(message) => message.into_iter().collect(),
+                _ => unreachable!("the message is an object"),
+            },
+        }
+    }
+}
+
+impl Eip712 for SeaportOrder {
+    type Error = Infallible;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.into())
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(order_components_type_hash())
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.order_hash())
+    }
+}
+
+/// An error returned when signing a Seaport order.
+#[derive(Debug)]
+pub enum SignOrderError<E> {
+    /// The order is missing a required field or contains an invalid value.
+    Conversion(ConversionError),
+    /// The signer failed to sign the order.
+    Signer(E),
+}
+
+impl<E: fmt::Display> fmt::Display for SignOrderError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignOrderError::Conversion(error) => write!(f, "Invalid order: {error}"),
+            SignOrderError::Signer(error) => write!(f, "Unable to sign order: {error}"),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for SignOrderError<E> {}
+
+impl<E> From<ConversionError> for SignOrderError<E> {
+    fn from(value: ConversionError) -> Self {
+        SignOrderError::Conversion(value)
+    }
+}
+
+/// A `Signer` which can sign Seaport orders.
+///
+/// A wallet holding its key signs the EIP-712 digest of the order, while a `RemoteSigner` sends
+/// the order as typed data so the signing service can see what it is signing.
+#[async_trait]
+pub trait OrderSigner: Signer {
+    /// Signs the EIP-712 typed data of `order`.
+    async fn sign_seaport_order(&self, order: &SeaportOrder) -> Result<Signature, Self::Error>;
+}
+
+#[async_trait]
+impl OrderSigner for LocalWallet {
+    async fn sign_seaport_order(&self, order: &SeaportOrder) -> Result<Signature, Self::Error> {
+        self.sign_typed_data(order).await
+    }
+}
+
+/// Signs `order` for the Seaport deployment described by `domain`, where `counter` is the
+/// offerer's current counter on that deployment (see `getCounter`).
+pub async fn sign_order<S: OrderSigner>(
+    signer: &S,
+    domain: SeaportDomain,
+    order: Order,
+    counter: U256,
+) -> Result<SignedOrder, SignOrderError<S::Error>> {
+    let payload = SeaportOrder::new(domain, &order, counter)?;
+    let signature = signer
+        .sign_seaport_order(&payload)
+        .await
+        .map_err(SignOrderError::Signer)?;
+
+    Ok(SignedOrder {
+        parameters: Some(order),
+        signature: Some(EthSignature::from(signature)),
+    })
+}
+
+/// An error returned when verifying the signature of a Seaport order.
+#[derive(Debug)]
+pub enum VerifyOrderError {
+    /// The order or signature is missing a required field or contains an invalid value.
+    Conversion(ConversionError),
+    /// No address could be recovered from the signature.
+    Recovery(SignatureError),
+    /// The signature was made by an address other than the offerer.
+    SignerMismatch { offerer: Address, signer: Address },
+    /// The order was quoted by a maker other than its offerer.
+    MakerMismatch { maker: Address, offerer: Address },
+    /// The offerer is a contract which rejected the signature.
+    Eip1271Rejected(Address),
+    /// The EIP-1271 `isValidSignature` call could not be made.
+    Eip1271Call(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for VerifyOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyOrderError::Conversion(error) => write!(f, "Invalid order: {error}"),
+            VerifyOrderError::Recovery(error) => write!(f, "Invalid signature: {error}"),
+            VerifyOrderError::SignerMismatch { offerer, signer } => write!(
+                f,
+                "Order was signed by {signer:?} rather than its offerer {offerer:?}"
+            ),
+            VerifyOrderError::MakerMismatch { maker, offerer } => write!(
+                f,
+                "Order offerer {offerer:?} does not match the maker {maker:?}"
+            ),
+            VerifyOrderError::Eip1271Rejected(offerer) => {
+                write!(f, "Offerer contract {offerer:?} rejected the signature")
+            }
+            VerifyOrderError::Eip1271Call(error) => {
+                write!(f, "Unable to call isValidSignature on the offerer: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyOrderError {}
+
+impl From<ConversionError> for VerifyOrderError {
+    fn from(value: ConversionError) -> Self {
+        VerifyOrderError::Conversion(value)
+    }
+}
+
+impl From<SignatureError> for VerifyOrderError {
+    fn from(value: SignatureError) -> Self {
+        VerifyOrderError::Recovery(value)
+    }
+}
+
+/// Recovers the address which signed `signed_order` for the Seaport deployment described by
+/// `domain`, where `counter` is the offerer's current counter on that deployment.
+pub fn recover_signer(
+    signed_order: &SignedOrder,
+    domain: SeaportDomain,
+    counter: U256,
+) -> Result<Address, VerifyOrderError> {
+    let (_, digest, signature) = signed_order_parts(signed_order, domain, counter)?;
+    recover(signature, digest)
+}
+
+/// Verifies the signature of `signed_order` recovers to its offerer, and that the offerer is
+/// `maker` (i.e. the `maker_address` of the `QuoteResponse`) if given.
+///
+/// Only signatures made by an EOA can be verified offline, see
+/// `verify_signed_order_with_eip1271` for orders offered by contracts.
+pub fn verify_signed_order(
+    signed_order: &SignedOrder,
+    domain: SeaportDomain,
+    counter: U256,
+    maker: Option<Address>,
+) -> Result<(), VerifyOrderError> {
+    let (offerer, digest, signature) = signed_order_parts(signed_order, domain, counter)?;
+    check_maker(offerer, maker)?;
+
+    let signer = recover(signature, digest)?;
+    if signer != offerer {
+        return Err(VerifyOrderError::SignerMismatch { offerer, signer });
+    }
+
+    Ok(())
+}
+
+/// As `verify_signed_order`, but if the signature does not recover to the offerer and the
+/// offerer is a contract, the signature is checked with its EIP-1271 `isValidSignature` as
+/// Seaport does on-chain.
+pub async fn verify_signed_order_with_eip1271<M: Middleware + 'static>(
+    client: Arc<M>,
+    signed_order: &SignedOrder,
+    domain: SeaportDomain,
+    counter: U256,
+    maker: Option<Address>,
+) -> Result<(), VerifyOrderError> {
+    let (offerer, digest, signature) = signed_order_parts(signed_order, domain, counter)?;
+    check_maker(offerer, maker)?;
+
+    let error = match recover(signature, digest) {
+        Ok(signer) if signer == offerer => return Ok(()),
+        Ok(signer) => VerifyOrderError::SignerMismatch { offerer, signer },
+        Err(error) => error,
+    };
+
+    // An EOA can only sign with its key, so there is nothing to fall back to.
+    let code = client
+        .get_code(offerer, None)
+        .await
+        .map_err(|error| VerifyOrderError::Eip1271Call(Box::new(error)))?;
+    if code.is_empty() {
+        return Err(error);
+    }
+
+    let magic_value = Erc1271::new(offerer, client)
+        .is_valid_signature(digest, signature.to_vec().into())
+        .call()
+        .await
+        .map_err(|error| VerifyOrderError::Eip1271Call(Box::new(error)))?;
+    if magic_value != EIP1271_MAGIC_VALUE {
+        return Err(VerifyOrderError::Eip1271Rejected(offerer));
+    }
+
+    Ok(())
+}
+
+// The offerer, EIP-712 digest and signature of `signed_order`.
+fn signed_order_parts(
+    signed_order: &SignedOrder,
+    domain: SeaportDomain,
+    counter: U256,
+) -> Result<(Address, [u8; 32], &EthSignature), ConversionError> {
+    let order = required(signed_order.parameters.as_ref(), "SignedOrder.parameters")?;
+    let signature = required(signed_order.signature.as_ref(), "SignedOrder.signature")?;
+    let offerer = required(order.offerer, "Order.offerer")?.into();
+    let digest = SeaportOrder::new(domain, order, counter)?.digest();
+
+    Ok((offerer, digest, signature))
+}
+
+fn check_maker(offerer: Address, maker: Option<Address>) -> Result<(), VerifyOrderError> {
+    match maker {
+        Some(maker) if maker != offerer => Err(VerifyOrderError::MakerMismatch { maker, offerer }),
+        _ => Ok(()),
+    }
+}
+
+fn recover(signature: &EthSignature, digest: [u8; 32]) -> Result<Address, VerifyOrderError> {
+    Ok(Signature::try_from(signature)?.recover(digest)?)
+}
+
+// The name and fields of an EIP-712 struct type, e.g. `OfferItem(uint8 itemType,...)`.
+fn eip712_type(type_string: &str) -> (String, Vec<Eip712DomainType>) {
+    let (name, fields) = type_string
+        .trim_end_matches(')')
+        .split_once('(')
+        .expect("the type has fields");
+    let fields = fields
+        .split(',')
+        .map(|field| {
+            let (r#type, name) = field.split_once(' ').expect("the field is named");
+            Eip712DomainType {
+                name: name.to_string(),
+                r#type: r#type.to_string(),
+            }
+        })
+        .collect();
+
+    (name.to_string(), fields)
+}
+
+fn order_components_type_hash() -> [u8; 32] {
+    // Referenced struct types are appended in alphabetical order.
+    keccak256(format!(
+        "{ORDER_COMPONENTS_TYPE}{CONSIDERATION_ITEM_TYPE}{OFFER_ITEM_TYPE}"
+    ))
+}
+
+fn hash_offer_item(item: &OfferItem) -> [u8; 32] {
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(OFFER_ITEM_TYPE).to_vec()),
+        Token::Uint(item.item_type.into()),
+        Token::Address(item.token),
+        Token::Uint(item.identifier_or_criteria),
+        Token::Uint(item.start_amount),
+        Token::Uint(item.end_amount),
+    ]))
+}
+
+fn hash_consideration_item(item: &ConsiderationItem) -> [u8; 32] {
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(CONSIDERATION_ITEM_TYPE).to_vec()),
+        Token::Uint(item.item_type.into()),
+        Token::Address(item.token),
+        Token::Uint(item.identifier_or_criteria),
+        Token::Uint(item.start_amount),
+        Token::Uint(item.end_amount),
+        Token::Address(item.recipient),
+    ]))
+}
+
+// Arrays of structs are hashed as the hash of their concatenated struct hashes.
+fn hash_order_components(components: &OrderComponents) -> [u8; 32] {
+    let offer = components
+        .offer
+        .iter()
+        .flat_map(hash_offer_item)
+        .collect::<Vec<_>>();
+    let consideration = components
+        .consideration
+        .iter()
+        .flat_map(hash_consideration_item)
+        .collect::<Vec<_>>();
+
+    keccak256(encode(&[
+        Token::FixedBytes(order_components_type_hash().to_vec()),
+        Token::Address(components.offerer),
+        Token::Address(components.zone),
+        Token::FixedBytes(keccak256(offer).to_vec()),
+        Token::FixedBytes(keccak256(consideration).to_vec()),
+        Token::Uint(components.order_type.into()),
+        Token::Uint(components.start_time),
+        Token::Uint(components.end_time),
+        Token::FixedBytes(components.zone_hash.to_vec()),
+        Token::Uint(components.salt),
+        Token::FixedBytes(components.conduit_key.to_vec()),
+        Token::Uint(components.counter),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::seaport::Seaport;
+    use ethers::abi::{decode, ParamType};
+    use ethers::providers::{Http, JsonRpcClient, MockError, Provider};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::Bytes;
+
+    // An order for a Valorem option, offering 1 000 USDC for 2 options on polygon with a conduit,
+    // a zone hash and a counter.
+    fn option_order() -> OrderComponents {
+        OrderComponents {
+            offerer: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap(),
+            zone: Address::zero(),
+            offer: vec![OfferItem {
+                item_type: 1,
+                token: "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".parse().unwrap(),
+                identifier_or_criteria: U256::zero(),
+                start_amount: U256::from(1_000_000_000u64),
+                end_amount: U256::from(1_000_000_000u64),
+            }],
+            consideration: vec![
+                ConsiderationItem {
+                    item_type: 3,
+                    token: "0x402A401B1944EBb5A3030F36Aa70d6b5794190c9".parse().unwrap(),
+                    identifier_or_criteria: U256::from_dec_str(
+                        "13164896130587464287516051233349537117597470810478566823009315098599094816768",
+                    )
+                    .unwrap(),
+                    start_amount: U256::from(2u64),
+                    end_amount: U256::from(2u64),
+                    recipient: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap(),
+                },
+                ConsiderationItem {
+                    item_type: 1,
+                    token: "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".parse().unwrap(),
+                    identifier_or_criteria: U256::zero(),
+                    start_amount: U256::from(2_500_000u64),
+                    end_amount: U256::from(2_500_000u64),
+                    recipient: "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC".parse().unwrap(),
+                },
+            ],
+            order_type: 2,
+            start_time: U256::from(1_700_000_000u64),
+            end_time: U256::from(1_700_003_600u64),
+            zone_hash: [0x11; 32],
+            salt: U256::from_dec_str("123456789012345678901234567890").unwrap(),
+            conduit_key: hex_bytes32(
+                "0x0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f0000",
+            ),
+            counter: U256::from(7u64),
+        }
+    }
+
+    // An order without consideration, offering a single ERC721 with a zone.
+    fn bare_order() -> OrderComponents {
+        OrderComponents {
+            offerer: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                .parse()
+                .unwrap(),
+            zone: "0x000000e7Ec00e7B300774b00001314B8610022b8"
+                .parse()
+                .unwrap(),
+            offer: vec![OfferItem {
+                item_type: 2,
+                token: "0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D"
+                    .parse()
+                    .unwrap(),
+                identifier_or_criteria: U256::from(1234u64),
+                start_amount: U256::one(),
+                end_amount: U256::one(),
+            }],
+            consideration: vec![],
+            order_type: 0,
+            start_time: U256::zero(),
+            end_time: U256::MAX,
+            zone_hash: [0; 32],
+            salt: U256::zero(),
+            conduit_key: [0; 32],
+            counter: U256::zero(),
+        }
+    }
+
+    fn hex_bytes32(value: &str) -> [u8; 32] {
+        hex::decode(value.trim_start_matches("0x"))
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    // Vectors for `bare_order` on mainnet and `option_order` on polygon: the order hash and the
+    // signed digest. They were computed from `SeaportOrder::typed_data` by ethers' EIP-712
+    // encoder, not recorded from Seaport, so `matches_deployed_seaport` checks the order hashes and
+    // the domain separator against the Seaport 1.5 deployment.
+    const VECTORS: [(u64, &str, &str); 2] = [
+        (
+            1,
+            "0x5b52bab9dc5be86b09529dcecaceb8a29b25e01dc9940305b7315e4f24b19e85",
+            "0xb2061ceba293f76d0d05217eb7938918854e2a8813ddc81463ea4eb0810b4d35",
+        ),
+        (
+            137,
+            "0x6f03281e3dd33708fa7c9d764bc55381e4a22a7f4d37a803973ac85898298cfe",
+            "0x40c520955f184fcd94aa2ba94ba74041c29dec3a95fcadde031ae0d76ce236d9",
+        ),
+    ];
+
+    #[test]
+    fn type_hash() {
+        // Seaport's `ORDER_TYPEHASH`.
+        assert_eq!(
+            order_components_type_hash(),
+            hex_bytes32("0xfa445660b7e21515a59617fcd68910b487aa5808b8abda3d78bc85df364b2c2f")
+        );
+    }
+
+    #[test]
+    fn domain_separator() {
+        assert_eq!(
+            SeaportDomain::new(1u64).separator(),
+            hex_bytes32("0x0d725b53ccd7c23735755082eee9d43d3add450d3564ad51af0d29aa16eeab3c")
+        );
+    }
+
+    #[test]
+    fn order_hashes() {
+        for ((chain_id, order_hash, digest), components) in
+            VECTORS.into_iter().zip([bare_order(), option_order()])
+        {
+            let domain = SeaportDomain::new(chain_id);
+            let order = SeaportOrder { domain, components };
+            let typed_data = order.typed_data();
+
+            assert_eq!(order.order_hash(), hex_bytes32(order_hash));
+            assert_eq!(order.digest(), hex_bytes32(digest));
+            assert_eq!(order.order_hash(), typed_data.struct_hash().unwrap());
+            assert_eq!(order.digest(), typed_data.encode_eip712().unwrap());
+            assert_eq!(order.digest(), order.encode_eip712().unwrap());
+        }
+    }
+
+    // Run with `SEAPORT_RPC_URL` set to a JSON-RPC endpoint of any chain Seaport 1.5 is deployed
+    // on, and `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs a JSON-RPC endpoint in SEAPORT_RPC_URL"]
+    async fn matches_deployed_seaport() {
+        let url = std::env::var("SEAPORT_RPC_URL").unwrap();
+        let provider = Arc::new(Provider::<Http>::try_from(url).unwrap());
+        let chain_id = provider.get_chainid().await.unwrap();
+        let seaport = Seaport::new(SEAPORT_ADDRESS, provider);
+
+        let (version, domain_separator, _) = seaport.information().call().await.unwrap();
+        assert_eq!(version, SEAPORT_VERSION);
+        assert_eq!(domain_separator, SeaportDomain::new(chain_id).separator());
+        if chain_id == U256::one() {
+            assert_eq!(
+                domain_separator,
+                hex_bytes32("0x0d725b53ccd7c23735755082eee9d43d3add450d3564ad51af0d29aa16eeab3c")
+            );
+        }
+
+        // The order hash does not depend on the chain.
+        for ((_, order_hash, _), components) in
+            VECTORS.into_iter().zip([bare_order(), option_order()])
+        {
+            let deployed = seaport.get_order_hash(components).call().await.unwrap();
+            assert_eq!(deployed, hex_bytes32(order_hash));
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_then_verify() {
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                .parse()
+                .unwrap();
+        let other = Address::repeat_byte(0x42);
+        let domain = SeaportDomain::new(137u64);
+        let counter = U256::from(7u64);
+        let order = Order::from(OrderComponents {
+            offerer: wallet.address(),
+            ..option_order()
+        });
+
+        let signed = sign_order(&wallet, domain, order, counter).await.unwrap();
+        verify_signed_order(&signed, domain, counter, None).unwrap();
+        verify_signed_order(&signed, domain, counter, Some(wallet.address())).unwrap();
+        assert_eq!(
+            recover_signer(&signed, domain, counter).unwrap(),
+            wallet.address()
+        );
+
+        assert!(matches!(
+            verify_signed_order(&signed, domain, counter, Some(other)),
+            Err(VerifyOrderError::MakerMismatch { .. })
+        ));
+
+        // The signature does not hold for another counter, chain or order.
+        assert!(matches!(
+            verify_signed_order(&signed, domain, counter + 1, None),
+            Err(VerifyOrderError::SignerMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_signed_order(&signed, SeaportDomain::new(1u64), counter, None),
+            Err(VerifyOrderError::SignerMismatch { .. })
+        ));
+        let mut tampered = signed.clone();
+        tampered.parameters.as_mut().unwrap().salt = Some(U256::zero().into());
+        assert!(matches!(
+            verify_signed_order(&tampered, domain, counter, None),
+            Err(VerifyOrderError::SignerMismatch { .. })
+        ));
+
+        // Another offerer's signature is rejected.
+        let mut forged = signed;
+        forged.parameters.as_mut().unwrap().offerer = Some(other.into());
+        assert!(matches!(
+            verify_signed_order(&forged, domain, counter, None),
+            Err(VerifyOrderError::SignerMismatch { offerer, signer })
+                if offerer == other && signer != other
+        ));
+    }
+
+    const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const OTHER_KEY: &str = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    // A chain with a single contract wallet at `address`, whose `isValidSignature` accepts
+    // signatures by `owner` as a Safe does. Every other account is an EOA.
+    #[derive(Debug)]
+    struct ContractWallet {
+        address: Address,
+        owner: Address,
+    }
+
+    impl ContractWallet {
+        fn respond(&self, method: &str, params: Value) -> Result<Value, MockError> {
+            match method {
+                "eth_getCode" => {
+                    let account: Address = serde_json::from_value(params[0].clone())?;
+                    let code = if account == self.address {
+                        vec![0x60, 0x00]
+                    } else {
+                        vec![]
+                    };
+                    Ok(json!(Bytes::from(code)))
+                }
+                "eth_call" => {
+                    let tx: TypedTransaction = serde_json::from_value(params[0].clone())?;
+                    assert_eq!(tx.to_addr(), Some(&self.address));
+                    let data = tx.data().unwrap();
+                    assert_eq!(data[..4], EIP1271_MAGIC_VALUE);
+                    let tokens =
+                        decode(&[ParamType::FixedBytes(32), ParamType::Bytes], &data[4..]).unwrap();
+                    let digest = tokens[0].clone().into_fixed_bytes().unwrap();
+                    let signature = tokens[1].clone().into_bytes().unwrap();
+
+                    // Compact signatures are accepted as well, as by OpenZeppelin's `ECDSA`.
+                    let signature = EthSignature {
+                        r: signature.get(..32).unwrap_or_default().to_vec(),
+                        s: signature.get(32..64).unwrap_or_default().to_vec(),
+                        v: signature.get(64..).unwrap_or_default().to_vec(),
+                    };
+                    let result = match recover(&signature, digest.try_into().unwrap()) {
+                        Ok(signer) if signer == self.owner => EIP1271_MAGIC_VALUE,
+                        _ => [0xff; 4],
+                    };
+                    Ok(json!(Bytes::from(encode(&[Token::FixedBytes(
+                        result.to_vec()
+                    )]))))
+                }
+                _ => panic!("unexpected call to {method}"),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl JsonRpcClient for ContractWallet {
+        type Error = MockError;
+
+        async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+        where
+            T: fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            let response = self.respond(method, serde_json::to_value(params)?)?;
+            Ok(serde_json::from_value(response)?)
+        }
+    }
+
+    fn address(key: &str) -> Address {
+        key.parse::<LocalWallet>().unwrap().address()
+    }
+
+    // `signature` in its EIP-2098 compact form, with the y parity in the top bit of `s`.
+    fn compact(signature: &EthSignature) -> EthSignature {
+        let (r, mut s, v) = signature.components().unwrap();
+        s[0] |= (v % 27) << 7;
+        EthSignature {
+            r: r.to_vec(),
+            s: s.to_vec(),
+            v: vec![],
+        }
+    }
+
+    async fn signed_order(key: &str, offerer: Address, counter: U256) -> SignedOrder {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let order = Order::from(OrderComponents {
+            offerer,
+            ..option_order()
+        });
+        sign_order(&wallet, SeaportDomain::new(137u64), order, counter)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn compact_signatures() {
+        let wallet: LocalWallet = KEY.parse().unwrap();
+        let domain = SeaportDomain::new(137u64);
+        let counter = U256::from(7u64);
+
+        // Both parities are covered, as the salt changes the digest.
+        let mut parities = std::collections::HashSet::new();
+        for salt in 0u64..8 {
+            let order = Order::from(OrderComponents {
+                offerer: wallet.address(),
+                salt: salt.into(),
+                ..option_order()
+            });
+            let mut signed = sign_order(&wallet, domain, order, counter).await.unwrap();
+            let signature = signed.signature.take().unwrap();
+            parities.insert(signature.v.clone());
+            signed.signature = Some(compact(&signature));
+            assert_eq!(signed.signature.as_ref().unwrap().to_vec().len(), 64);
+
+            verify_signed_order(&signed, domain, counter, Some(wallet.address())).unwrap();
+            assert_eq!(
+                recover_signer(&signed, domain, counter).unwrap(),
+                wallet.address()
+            );
+        }
+        assert_eq!(parities.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn signatures_by_another_key_are_rejected() {
+        let owner = address(KEY);
+        let other = address(OTHER_KEY);
+        let domain = SeaportDomain::new(137u64);
+        let counter = U256::from(7u64);
+
+        let signed = signed_order(OTHER_KEY, owner, counter).await;
+        match verify_signed_order(&signed, domain, counter, Some(owner)) {
+            Err(VerifyOrderError::SignerMismatch { offerer, signer }) => {
+                assert_eq!((offerer, signer), (owner, other))
+            }
+            result => panic!("unexpected {result:?}"),
+        }
+
+        // An EOA offerer has no EIP-1271 fallback.
+        let client = Arc::new(Provider::new(ContractWallet {
+            address: Address::repeat_byte(0x42),
+            owner,
+        }));
+        assert!(matches!(
+            verify_signed_order_with_eip1271(client, &signed, domain, counter, None).await,
+            Err(VerifyOrderError::SignerMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn eip1271_signatures() {
+        let owner = address(KEY);
+        let contract = Address::repeat_byte(0x42);
+        let client = Arc::new(Provider::new(ContractWallet {
+            address: contract,
+            owner,
+        }));
+        let domain = SeaportDomain::new(137u64);
+        let counter = U256::from(7u64);
+
+        // The contract returns the magic value for its owner's signature, in either form.
+        let mut signed = signed_order(KEY, contract, counter).await;
+        assert!(matches!(
+            verify_signed_order(&signed, domain, counter, None),
+            Err(VerifyOrderError::SignerMismatch { .. })
+        ));
+        verify_signed_order_with_eip1271(client.clone(), &signed, domain, counter, Some(contract))
+            .await
+            .unwrap();
+        signed.signature = Some(compact(signed.signature.as_ref().unwrap()));
+        verify_signed_order_with_eip1271(client.clone(), &signed, domain, counter, None)
+            .await
+            .unwrap();
+
+        // And another value for any other signature.
+        let signed = signed_order(OTHER_KEY, contract, counter).await;
+        match verify_signed_order_with_eip1271(client.clone(), &signed, domain, counter, None).await
+        {
+            Err(VerifyOrderError::Eip1271Rejected(offerer)) => assert_eq!(offerer, contract),
+            result => panic!("unexpected {result:?}"),
+        }
+
+        // The maker is checked before calling the contract.
+        assert!(matches!(
+            verify_signed_order_with_eip1271(client, &signed, domain, counter, Some(owner)).await,
+            Err(VerifyOrderError::MakerMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+// Serde support for the generated proto types, enabled with the `serde` feature.
+//
+// Hash types are represented as 0x-prefixed hex strings (EIP-55 checksummed for `H160`), numeric
+// `H256` fields as decimal strings and enums by their proto names. Which fields use which
+// representation is configured in `build.rs`.
+use crate::grpc_adapters::{decode_hex, ConversionError};
+use crate::grpc_codegen::{Action, ItemType, OrderType, H128, H160, H256, H40, H96};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The serde representation of the generated `H40` and `H96` types, a 0x-prefixed hex string.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct HexString(String);
+
+// `lo` is really a uint8, an out of range value produces a string which fails to deserialize
+// rather than being silently truncated.
+impl From<H40> for HexString {
+    fn from(value: H40) -> Self {
+        Self(format!("0x{:08x}{:02x}", value.hi, value.lo))
+    }
+}
+
+impl TryFrom<HexString> for H40 {
+    type Error = ConversionError;
+
+    fn try_from(value: HexString) -> Result<Self, Self::Error> {
+        Ok(Self::from(decode_hex::<5>("H40", &value.0)?))
+    }
+}
+
+impl From<H96> for HexString {
+    fn from(value: H96) -> Self {
+        Self(format!("0x{}", hex::encode(<[u8; 12]>::from(value))))
+    }
+}
+
+impl TryFrom<HexString> for H96 {
+    type Error = ConversionError;
+
+    fn try_from(value: HexString) -> Result<Self, Self::Error> {
+        Ok(Self::from(decode_hex::<12>("H96", &value.0)?))
+    }
+}
+
+// Macro (de)serializing a fixed size hash newtype using its `Display` and `FromStr`.
+macro_rules! hex_string {
+    ($proto:ident) => {
+        impl Serialize for $proto {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $proto {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+hex_string!(H128);
+hex_string!(H160);
+hex_string!(H256);
+
+// Decimal conversions are done with big-endian byte arithmetic so they work with either the ethers
+// or alloy feature.
+fn to_decimal(mut bytes: [u8; 32]) -> String {
+    let mut digits = Vec::new();
+    loop {
+        // Divide the whole number by ten, keeping the remainder as the next digit.
+        let mut remainder = 0;
+        for byte in bytes.iter_mut() {
+            let value = (remainder << 8) | u32::from(*byte);
+            *byte = (value / 10) as u8;
+            remainder = value % 10;
+        }
+        digits.push(char::from(b'0' + remainder as u8));
+
+        if bytes.iter().all(|byte| *byte == 0) {
+            break;
+        }
+    }
+
+    digits.iter().rev().collect()
+}
+
+fn from_decimal(value: &str) -> Option<[u8; 32]> {
+    if value.is_empty() || !value.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut bytes = [0u8; 32];
+    for digit in value.bytes() {
+        // Multiply the whole number by ten and add the digit, failing on overflow.
+        let mut carry = u32::from(digit - b'0');
+        for byte in bytes.iter_mut().rev() {
+            let value = u32::from(*byte) * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+
+        if carry != 0 {
+            return None;
+        }
+    }
+
+    Some(bytes)
+}
+
+/// (De)serializes an optional `H256` as a decimal string, used for amounts and other numbers.
+pub mod decimal {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<H256>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .map(|value| to_decimal(value.into()))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<H256>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                from_decimal(&value)
+                    .map(H256::from)
+                    .ok_or_else(|| D::Error::custom(format!("invalid decimal value {value}")))
+            })
+            .transpose()
+    }
+}
+
+/// (De)serializes a bytes field as a 0x-prefixed hex string.
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        hex::decode(value.strip_prefix("0x").unwrap_or(&value))
+            .map_err(|_| D::Error::custom(format!("invalid hex value {value}")))
+    }
+}
+
+// Enums are accepted either by name or by number, unknown values are serialized as their number
+// so they still round trip.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EnumValue {
+    Name(String),
+    Number(i32),
+}
+
+// Macro generating a module which (de)serializes an `i32` enum field by its proto name.
+macro_rules! enum_name {
+    ($module:ident, $enum:ident) => {
+        #[doc = concat!("(De)serializes an `i32` field holding a `", stringify!($enum), "` by name.")]
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(value: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+                match $enum::from_i32(*value) {
+                    Some(value) => serializer.serialize_str(value.as_str_name()),
+                    None => serializer.serialize_i32(*value),
+                }
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<i32, D::Error> {
+                match EnumValue::deserialize(deserializer)? {
+                    EnumValue::Name(name) => $enum::from_str_name(&name)
+                        .map(|value| value as i32)
+                        .ok_or_else(|| {
+                            D::Error::custom(format!("unknown {} {name}", stringify!($enum)))
+                        }),
+                    EnumValue::Number(value) => Ok(value),
+                }
+            }
+        }
+    };
+}
+
+enum_name!(action, Action);
+enum_name!(item_type, ItemType);
+enum_name!(order_type, OrderType);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc_codegen::{EthSignature, OfferItem, QuoteRequest};
+    use serde_json::json;
+
+    #[test]
+    fn decimal_round_trip() {
+        for value in ["0", "1", "255", "256", "1000000000000000000"] {
+            assert_eq!(to_decimal(from_decimal(value).unwrap()), value);
+        }
+
+        let max = to_decimal([0xff; 32]);
+        assert_eq!(
+            max,
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(from_decimal(&max), Some([0xff; 32]));
+    }
+
+    #[test]
+    fn decimal_rejects_invalid() {
+        // 2^256 overflows by one.
+        assert_eq!(
+            from_decimal(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+            ),
+            None
+        );
+        assert_eq!(from_decimal(""), None);
+        assert_eq!(from_decimal("-1"), None);
+        assert_eq!(from_decimal("0x10"), None);
+        assert_eq!(from_decimal("1.5"), None);
+    }
+
+    #[test]
+    fn quote_request_round_trip() {
+        let request = QuoteRequest {
+            ulid: Some(H128::from(42u64)),
+            taker_address: Some(
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+                    .parse()
+                    .unwrap(),
+            ),
+            item_type: ItemType::Erc1155 as i32,
+            token_address: None,
+            identifier_or_criteria: Some(H256::from(7u64)),
+            amount: Some(H256::from(1_000_000u64)),
+            action: Action::Sell as i32,
+            chain_id: Some(H256::from(421614u64)),
+            seaport_address: None,
+        };
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value["taker_address"],
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        assert_eq!(value["item_type"], "ERC1155");
+        assert_eq!(value["action"], "SELL");
+        assert_eq!(value["amount"], "1000000");
+        assert_eq!(value["chain_id"], "421614");
+        assert_eq!(value["ulid"], "0x0000000000000000000000000000002a");
+
+        let decoded: QuoteRequest = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, request);
+    }
+
+    #[test]
+    fn enums_by_name_or_number() {
+        let item: OfferItem = serde_json::from_value(json!({ "item_type": 2 })).unwrap();
+        assert_eq!(item.item_type, ItemType::Erc721 as i32);
+
+        // Unknown values are kept as numbers so they still round trip.
+        let item: OfferItem = serde_json::from_value(json!({ "item_type": 99 })).unwrap();
+        assert_eq!(serde_json::to_value(&item).unwrap()["item_type"], 99);
+
+        assert!(serde_json::from_value::<OfferItem>(json!({ "item_type": "NFT" })).is_err());
+    }
+
+    #[test]
+    fn bytes_and_small_hashes() {
+        let signature = EthSignature {
+            r: vec![0xab; 32],
+            s: vec![0x01, 0x02],
+            v: vec![0x1b],
+        };
+        let value = serde_json::to_value(&signature).unwrap();
+        assert_eq!(value["v"], "0x1b");
+        assert_eq!(value["s"], "0x0102");
+        assert_eq!(
+            serde_json::from_value::<EthSignature>(value).unwrap(),
+            signature
+        );
+
+        let h40 = H40::from([1, 2, 3, 4, 5]);
+        let value = serde_json::to_value(h40.clone()).unwrap();
+        assert_eq!(value, "0x0102030405");
+        assert_eq!(serde_json::from_value::<H40>(value).unwrap(), h40);
+
+        let h96 = H96::from([0xee; 12]);
+        let value = serde_json::to_value(h96.clone()).unwrap();
+        assert_eq!(serde_json::from_value::<H96>(value).unwrap(), h96);
+    }
+
+    #[test]
+    fn mixed_case_address_must_be_checksummed() {
+        assert!(serde_json::from_value::<H160>(json!(
+            "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        ))
+        .is_err());
+        assert!(serde_json::from_value::<H160>(json!(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+        ))
+        .is_ok());
+    }
+}
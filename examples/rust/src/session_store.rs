@@ -0,0 +1,276 @@
+// An on-disk store of authenticated sessions, so a restarted client can resume its session rather
+// than signing in again, which would end the stored session when the new nonce is fetched.
+//
+// Each session is kept in its own JSON file in the store directory, named after the wallet address
+// and a hash of the endpoint. The files hold the session cookie, so on unix the directory is only
+// accessible by its owner and the files are only readable and writable by their owner.
+use crate::utils::cookie::SessionCookie;
+use ethers::types::{Address, U256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tonic::transport::Uri;
+
+/// An error returned when reading or writing a `SessionStore`.
+#[derive(Debug)]
+pub enum SessionStoreError {
+    /// The session file could not be read or written.
+    Io(io::Error),
+    /// The session file does not hold a valid session.
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for SessionStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionStoreError::Io(error) => write!(f, "Unable to access the session: {error}"),
+            SessionStoreError::Format(error) => write!(f, "Invalid stored session: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionStoreError {}
+
+impl From<io::Error> for SessionStoreError {
+    fn from(value: io::Error) -> Self {
+        SessionStoreError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SessionStoreError {
+    fn from(value: serde_json::Error) -> Self {
+        SessionStoreError::Format(value)
+    }
+}
+
+/// An authenticated session, along with the `SiweSession` the server holds for it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub cookie: SessionCookie,
+    /// The address the session is authenticated as.
+    pub address: Address,
+    /// The chain id the session is authenticated on.
+    pub chain_id: U256,
+}
+
+/// A directory of stored sessions, keyed by wallet address and endpoint.
+#[derive(Clone, Debug)]
+pub struct SessionStore {
+    directory: PathBuf,
+}
+
+impl SessionStore {
+    /// A store keeping its sessions in `directory`, which is created when the first session is
+    /// saved.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// The directory the sessions are kept in.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The session stored for `address` at `endpoint`, if any. A session whose cookie cannot be
+    /// sent in a header is treated as not stored.
+    pub fn load(
+        &self,
+        endpoint: &Uri,
+        address: Address,
+    ) -> Result<Option<StoredSession>, SessionStoreError> {
+        let contents = match fs::read(self.path(endpoint, address)) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let session: StoredSession = serde_json::from_slice(&contents)?;
+        match session.cookie.metadata_value() {
+            Ok(_) => Ok(Some(session)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Stores `session` for its address at `endpoint`, replacing any session already stored.
+    pub fn save(&self, endpoint: &Uri, session: &StoredSession) -> Result<(), SessionStoreError> {
+        create_private_dir(&self.directory)?;
+
+        // The session is written to a temporary file first, so a failed write never leaves a
+        // truncated session behind.
+        let path = self.path(endpoint, session.address);
+        let temporary = path.with_extension("tmp");
+        let mut file = create_private_file(&temporary)?;
+        file.write_all(&serde_json::to_vec_pretty(session)?)?;
+        file.sync_all()?;
+        fs::rename(&temporary, path)?;
+
+        Ok(())
+    }
+
+    /// Removes the session stored for `address` at `endpoint`, if any.
+    pub fn remove(&self, endpoint: &Uri, address: Address) -> Result<(), SessionStoreError> {
+        match fs::remove_file(self.path(endpoint, address)) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, endpoint: &Uri, address: Address) -> PathBuf {
+        let endpoint_hash = keccak256(endpoint.to_string());
+        self.directory.join(format!(
+            "{}-{}.json",
+            hex::encode(address),
+            hex::encode(&endpoint_hash[..8])
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn create_private_dir(directory: &Path) -> io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(directory: &Path) -> io::Result<()> {
+    fs::create_dir_all(directory)
+}
+
+#[cfg(unix)]
+fn create_private_file(path: &Path) -> io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+
+    // The mode only applies to new files, so a leftover temporary file is restricted as well.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create_private_file(path: &Path) -> io::Result<fs::File> {
+    fs::File::create(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(value: &str) -> StoredSession {
+        StoredSession {
+            cookie: SessionCookie {
+                name: "session".to_string(),
+                value: value.to_string(),
+                expires_at: None,
+            },
+            address: Address::repeat_byte(0x11),
+            chain_id: U256::from(421_613),
+        }
+    }
+
+    // A store in a directory of the temporary directory unique to the test `name`.
+    fn store(name: &str) -> SessionStore {
+        let directory = std::env::temp_dir().join(format!(
+            "valorem-session-store-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        SessionStore::new(directory)
+    }
+
+    fn endpoint() -> Uri {
+        "https://trade.valorem.xyz".parse().unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let store = store("round-trip");
+        let endpoint = endpoint();
+        let address = Address::repeat_byte(0x11);
+        assert!(store.load(&endpoint, address).unwrap().is_none());
+
+        let stored = session("abc");
+        store.save(&endpoint, &stored).unwrap();
+        assert_eq!(store.load(&endpoint, address).unwrap(), Some(stored));
+
+        // A cookie which cannot be sent back is treated as no stored session.
+        store.save(&endpoint, &session("a\rb")).unwrap();
+        assert!(store.load(&endpoint, address).unwrap().is_none());
+
+        store.remove(&endpoint, address).unwrap();
+        assert!(store.load(&endpoint, address).unwrap().is_none());
+        fs::remove_dir_all(store.directory()).unwrap();
+    }
+
+    #[test]
+    fn missing_and_corrupt_sessions() {
+        let store = store("corrupt");
+        let endpoint = endpoint();
+        let address = Address::repeat_byte(0x11);
+
+        // Neither the directory nor the file exist yet.
+        assert!(store.load(&endpoint, address).unwrap().is_none());
+        store.remove(&endpoint, address).unwrap();
+
+        store.save(&endpoint, &session("abc")).unwrap();
+        let path = store.path(&endpoint, address);
+        for contents in ["", "{\"cookie\":", "[]"] {
+            fs::write(&path, contents).unwrap();
+            assert!(matches!(
+                store.load(&endpoint, address),
+                Err(SessionStoreError::Format(_))
+            ));
+        }
+
+        // Saving replaces the corrupt session.
+        store.save(&endpoint, &session("abc")).unwrap();
+        assert_eq!(
+            store.load(&endpoint, address).unwrap(),
+            Some(session("abc"))
+        );
+
+        fs::remove_file(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+        assert!(matches!(
+            store.load(&endpoint, address),
+            Err(SessionStoreError::Io(_))
+        ));
+        fs::remove_dir_all(store.directory()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let store = store("permissions");
+        let endpoint = endpoint();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        store.save(&endpoint, &session("abc")).unwrap();
+        let path = store.path(&endpoint, Address::repeat_byte(0x11));
+        assert_eq!(mode(store.directory()), 0o700);
+        assert_eq!(mode(&path), 0o600);
+
+        // A leftover temporary file readable by others is restricted before it is written to.
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, "").unwrap();
+        fs::set_permissions(&temporary, fs::Permissions::from_mode(0o644)).unwrap();
+        store.save(&endpoint, &session("def")).unwrap();
+        assert!(!temporary.exists());
+        assert_eq!(mode(&path), 0o600);
+        fs::remove_dir_all(store.directory()).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+// The Sign-In with Ethereum (EIP-4361) message signed when authenticating with Valorem.
+//
+// The address and chain id of the message come from the signer and the nonce from `Auth::Nonce`,
+// while the remaining fields are set by a `SiweConfig`. The message is parsed back before it is
+// signed, so a malformed nonce or field is caught locally rather than by the server.
+use ethers::types::{Address, U256};
+use iri_string::types::UriString;
+use std::fmt;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tonic::codegen::http::uri::Authority;
+use tonic::transport::Uri;
+
+/// The statement of the SIWE message, accepting the Valorem terms of service.
+pub const TOS_ACCEPTANCE: &str = "I accept the Valorem Terms of Service at https://app.valorem.xyz/tos and Privacy Policy at https://app.valorem.xyz/privacy";
+
+const DEFAULT_DOMAIN: &str = "trade.valorem.xyz";
+
+// EIP-4361 requires at least 8 alphanumeric characters.
+const MIN_NONCE_LENGTH: usize = 8;
+
+/// An error returned when building or checking a SIWE message.
+#[derive(Debug)]
+pub enum SiweError {
+    /// The nonce issued by the server is not a valid EIP-4361 nonce.
+    InvalidNonce(String),
+    /// A field of the message is not valid, e.g. a resource which is not an RFC 3986 URI.
+    InvalidMessage(String),
+    /// The message is outside of its `not_before` and expiration time.
+    NotValidNow,
+    /// The server authenticated a different address than the signer's.
+    AddressMismatch { expected: Address, actual: Address },
+    /// The server authenticated a different chain than the signer's.
+    ChainMismatch { expected: U256, actual: U256 },
+}
+
+impl fmt::Display for SiweError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SiweError::InvalidNonce(nonce) => write!(f, "Invalid nonce {nonce}"),
+            SiweError::InvalidMessage(error) => write!(f, "Invalid SIWE message: {error}"),
+            SiweError::NotValidNow => write!(f, "SIWE message is not valid at the current time"),
+            SiweError::AddressMismatch { expected, actual } => {
+                write!(f, "Authenticated as {actual:?} rather than {expected:?}")
+            }
+            SiweError::ChainMismatch { expected, actual } => {
+                write!(f, "Authenticated on chain {actual} rather than {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SiweError {}
+
+/// The fields of the SIWE message set by the client rather than the signer or the server.
+#[derive(Clone, Debug)]
+pub struct SiweConfig {
+    /// The RFC 3986 authority requesting the sign in.
+    pub domain: Authority,
+    /// The statement the signer agrees to, accepting the terms of service by default.
+    pub statement: Option<String>,
+    /// The RFC 3986 URI which is the subject of the sign in.
+    pub uri: Uri,
+    /// How long the message is valid for once issued, or `None` if it does not expire. This is
+    /// relative so that the message signed when the session is refreshed is valid as well.
+    pub expires_after: Option<Duration>,
+    /// When the message becomes valid, if not when it is issued.
+    pub not_before: Option<OffsetDateTime>,
+    /// An identifier for the sign in request.
+    pub request_id: Option<String>,
+    /// RFC 3986 URIs the signer wants resolved as part of the sign in.
+    pub resources: Vec<Uri>,
+}
+
+impl SiweConfig {
+    /// The default message for signing in at `endpoint`, whose authority is the domain and which
+    /// is the URI of the message.
+    pub fn for_endpoint(endpoint: &Uri) -> Self {
+        Self {
+            domain: endpoint
+                .authority()
+                .cloned()
+                .unwrap_or(Authority::from_static(DEFAULT_DOMAIN)),
+            statement: Some(TOS_ACCEPTANCE.into()),
+            uri: endpoint.clone(),
+            expires_after: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        }
+    }
+
+    /// The message signing in as `address` on `chain_id` with the `nonce` issued by the server,
+    /// issued now.
+    pub fn message(
+        &self,
+        address: Address,
+        chain_id: u64,
+        nonce: &str,
+    ) -> Result<siwe::Message, SiweError> {
+        if nonce.len() < MIN_NONCE_LENGTH || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SiweError::InvalidNonce(nonce.into()));
+        }
+
+        let issued_at = OffsetDateTime::now_utc();
+        let message = siwe::Message {
+            domain: self.domain.clone(),
+            address: address.0,
+            statement: self.statement.clone(),
+            uri: parse_uri(&self.uri)?,
+            version: siwe::Version::V1,
+            chain_id,
+            nonce: nonce.into(),
+            issued_at: issued_at.into(),
+            expiration_time: self
+                .expires_after
+                .map(|expires_after| (issued_at + expires_after).into()),
+            not_before: self.not_before.map(Into::into),
+            request_id: self.request_id.clone(),
+            resources: self
+                .resources
+                .iter()
+                .map(parse_uri)
+                .collect::<Result<_, _>>()?,
+        };
+
+        // The server parses the signed text, so it must parse back to the message we built,
+        // including the nonce and the domain and URI of the endpoint. A field broken over several
+        // lines may parse as other fields, e.g. a request id followed by resources.
+        let parsed = message
+            .to_string()
+            .parse::<siwe::Message>()
+            .map_err(|error| SiweError::InvalidMessage(error.to_string()))?;
+        if parsed != message {
+            return Err(SiweError::InvalidMessage(
+                "the message does not parse back to the same fields".into(),
+            ));
+        }
+        if !message.valid_now() {
+            return Err(SiweError::NotValidNow);
+        }
+
+        Ok(message)
+    }
+}
+
+fn parse_uri(uri: &Uri) -> Result<UriString, SiweError> {
+    UriString::try_from(uri.to_string())
+        .map_err(|error| SiweError::InvalidMessage(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONCE: &str = "abcdefgh12345678";
+
+    fn config() -> SiweConfig {
+        SiweConfig::for_endpoint(&"https://trade.valorem.xyz/".parse().unwrap())
+    }
+
+    #[test]
+    fn message_for_endpoint() {
+        let address = Address::repeat_byte(0x11);
+        let message = config().message(address, 421_613, NONCE).unwrap();
+        assert_eq!(message.domain.as_str(), "trade.valorem.xyz");
+        assert_eq!(message.uri.as_str(), "https://trade.valorem.xyz/");
+        assert_eq!(message.address, address.0);
+        assert_eq!(message.chain_id, 421_613);
+        assert_eq!(message.nonce, NONCE);
+        assert_eq!(message.statement.as_deref(), Some(TOS_ACCEPTANCE));
+    }
+
+    #[test]
+    fn invalid_nonce() {
+        for nonce in ["", "abcdefg", "abcdefgh-1234567", "abcdefgh\nUnexpected"] {
+            assert!(matches!(
+                config().message(Address::zero(), 1, nonce),
+                Err(SiweError::InvalidNonce(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn fields_which_do_not_parse_back() {
+        // Parses as a request id of `1` followed by a resource, rather than failing to parse.
+        let mut config = config();
+        config.request_id = Some("1\nResources:\n- https://example.com".into());
+        match config.message(Address::zero(), 1, NONCE) {
+            Err(SiweError::InvalidMessage(error)) => {
+                assert_eq!(error, "the message does not parse back to the same fields")
+            }
+            result => panic!("unexpected {result:?}"),
+        }
+
+        for statement in ["", "I accept\n\nURI: https://example.com"] {
+            let mut config = self::config();
+            config.statement = Some(statement.into());
+            assert!(matches!(
+                config.message(Address::zero(), 1, NONCE),
+                Err(SiweError::InvalidMessage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn not_valid_now() {
+        let mut config = config();
+        config.not_before = Some(OffsetDateTime::now_utc() + Duration::from_secs(3_600));
+        assert!(matches!(
+            config.message(Address::zero(), 1, NONCE),
+            Err(SiweError::NotValidNow)
+        ));
+    }
+}
@@ -0,0 +1,418 @@
+// Helpers for consuming the Valorem `Spot` service.
+//
+// Spot prices are streamed as `H256` values in a 60x18 unsigned fixed point format, i.e. 42 integer
+// digits and 18 decimals. `SpotPrice` wraps the raw value and provides the conversions and
+// arithmetic needed to price off of it.
+use crate::grpc_codegen::{self, spot_client::SpotClient, SpotPriceInfo, SpotPriceRequest};
+use ethers::types::{Address, U256, U512};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::{Status, Streaming};
+
+/// The number of decimals in a 60x18 fixed point value.
+pub const SPOT_PRICE_DECIMALS: usize = 18;
+
+/// A spot price in USD represented as a 60x18 unsigned fixed point number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpotPrice(U256);
+
+impl SpotPrice {
+    pub const ZERO: Self = Self(U256::zero());
+
+    /// The fixed point scaling factor (1e18).
+    pub fn scale() -> U256 {
+        U256::exp10(SPOT_PRICE_DECIMALS)
+    }
+
+    /// One whole unit, i.e. 1.0.
+    pub fn one() -> Self {
+        Self(Self::scale())
+    }
+
+    /// Create a spot price from the raw 60x18 representation.
+    pub const fn from_raw(raw: U256) -> Self {
+        Self(raw)
+    }
+
+    /// The raw 60x18 representation of the spot price.
+    pub const fn into_raw(self) -> U256 {
+        self.0
+    }
+
+    /// Create a spot price from a whole number of USD, returning `None` on overflow.
+    pub fn from_integer(value: U256) -> Option<Self> {
+        value.checked_mul(Self::scale()).map(Self)
+    }
+
+    /// The integer part of the spot price.
+    pub fn trunc(&self) -> U256 {
+        self.0 / Self::scale()
+    }
+
+    /// The fractional part of the spot price, in units of 1e-18.
+    pub fn fract(&self) -> U256 {
+        self.0 % Self::scale()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// Fixed point multiplication, the result is truncated to 18 decimals.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let product = self.0.full_mul(rhs.0) / U512::from(Self::scale());
+        U256::try_from(product).ok().map(Self)
+    }
+
+    /// Fixed point division, the result is truncated to 18 decimals.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+
+        let quotient = self.0.full_mul(Self::scale()) / U512::from(rhs.0);
+        U256::try_from(quotient).ok().map(Self)
+    }
+
+    /// Multiply a raw token amount by the spot price, e.g. to value `amount` of a token with
+    /// `decimals` decimals in USD. The result is returned as a spot price, or `None` on overflow
+    /// or if `10^decimals` does not fit into a `U256`.
+    pub fn checked_mul_amount(self, amount: U256, decimals: usize) -> Option<Self> {
+        let unit = U256::from(10).checked_pow(U256::from(decimals))?;
+        let product = self.0.full_mul(amount) / U512::from(unit);
+        U256::try_from(product).ok().map(Self)
+    }
+
+    /// Lossy conversion into an `f64`, intended for display and heuristics only.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl From<grpc_codegen::H256> for SpotPrice {
+    fn from(value: grpc_codegen::H256) -> Self {
+        Self(U256::from(value))
+    }
+}
+
+impl From<SpotPrice> for grpc_codegen::H256 {
+    fn from(value: SpotPrice) -> Self {
+        grpc_codegen::H256::from(value.0)
+    }
+}
+
+// The operators panic on overflow, underflow or division by zero like the integer operators do.
+// Prices received from the network should use the `checked_*` methods instead, as the fee
+// calculations do.
+impl Add for SpotPrice {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).expect("SpotPrice addition overflow")
+    }
+}
+
+impl Sub for SpotPrice {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("SpotPrice subtraction underflow")
+    }
+}
+
+impl Mul for SpotPrice {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs)
+            .expect("SpotPrice multiplication overflow")
+    }
+}
+
+impl Div for SpotPrice {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs)
+            .expect("SpotPrice division by zero or overflow")
+    }
+}
+
+// Formats as a decimal string with trailing zeros removed, unless a precision is given in which
+// case the fractional part is truncated or padded to that many digits.
+impl fmt::Display for SpotPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `U256` does not honour width flags, so pad the fractional digits by hand.
+        let fract = format!(
+            "{:0>width$}",
+            self.fract().to_string(),
+            width = SPOT_PRICE_DECIMALS
+        );
+        let fract = match f.precision() {
+            Some(precision) if precision <= SPOT_PRICE_DECIMALS => fract[..precision].to_string(),
+            Some(precision) => format!("{fract:0<precision$}"),
+            None => fract.trim_end_matches('0').to_string(),
+        };
+
+        if fract.is_empty() {
+            write!(f, "{}", self.trunc())
+        } else {
+            write!(f, "{}.{}", self.trunc(), fract)
+        }
+    }
+}
+
+/// Errors which can occur when parsing a `SpotPrice` from a decimal string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseSpotPriceError {
+    Empty,
+    InvalidDigit,
+    TooManyDecimals,
+    Overflow,
+}
+
+impl fmt::Display for ParseSpotPriceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSpotPriceError::Empty => write!(f, "cannot parse spot price from empty string"),
+            ParseSpotPriceError::InvalidDigit => write!(f, "invalid digit found in spot price"),
+            ParseSpotPriceError::TooManyDecimals => write!(
+                f,
+                "spot price has more than {SPOT_PRICE_DECIMALS} decimal places"
+            ),
+            ParseSpotPriceError::Overflow => write!(f, "spot price is too large for 60x18"),
+        }
+    }
+}
+
+impl std::error::Error for ParseSpotPriceError {}
+
+impl FromStr for SpotPrice {
+    type Err = ParseSpotPriceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (integer, fract) = s.split_once('.').unwrap_or((s, ""));
+        if integer.is_empty() && fract.is_empty() {
+            return Err(ParseSpotPriceError::Empty);
+        }
+
+        if !integer
+            .chars()
+            .chain(fract.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(ParseSpotPriceError::InvalidDigit);
+        }
+
+        if fract.len() > SPOT_PRICE_DECIMALS {
+            return Err(ParseSpotPriceError::TooManyDecimals);
+        }
+
+        let integer = if integer.is_empty() {
+            U256::zero()
+        } else {
+            U256::from_dec_str(integer).map_err(|_| ParseSpotPriceError::Overflow)?
+        };
+        let fract = if fract.is_empty() {
+            U256::zero()
+        } else {
+            U256::from_dec_str(fract).map_err(|_| ParseSpotPriceError::Overflow)?
+                * U256::exp10(SPOT_PRICE_DECIMALS - fract.len())
+        };
+
+        Self::from_integer(integer)
+            .and_then(|integer| integer.0.checked_add(fract))
+            .map(Self)
+            .ok_or(ParseSpotPriceError::Overflow)
+    }
+}
+
+/// A spot price update received from the `Spot` service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpotPriceUpdate {
+    pub chain_id: u64,
+    pub token: Address,
+    pub price: SpotPrice,
+}
+
+/// A subscription to the `Spot::GetSpotPrice` stream.
+pub struct SpotPriceSubscription {
+    stream: Streaming<SpotPriceInfo>,
+}
+
+impl SpotPriceSubscription {
+    /// Subscribe to spot price updates for the given `(chain_id, token)` pairs.
+    pub async fn subscribe<T>(
+        client: &mut SpotClient<T>,
+        tokens: impl IntoIterator<Item = (u64, Address)>,
+    ) -> Result<Self, Status>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        let request = SpotPriceRequest {
+            spot_price_info: tokens
+                .into_iter()
+                .map(|(chain_id, token)| SpotPriceInfo {
+                    chain_id,
+                    token_address: Some(token.into()),
+                    spot_price: None,
+                })
+                .collect(),
+        };
+
+        let stream = client.get_spot_price(request).await?.into_inner();
+        Ok(Self { stream })
+    }
+
+    /// Wait for the next spot price update. Returns `Ok(None)` once the stream has ended.
+    ///
+    /// Messages without a token address or spot price are skipped.
+    pub async fn next(&mut self) -> Result<Option<SpotPriceUpdate>, Status> {
+        while let Some(info) = self.stream.message().await? {
+            if let (Some(token), Some(price)) = (info.token_address, info.spot_price) {
+                return Ok(Some(SpotPriceUpdate {
+                    chain_id: info.chain_id,
+                    token: token.into(),
+                    price: price.into(),
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Unwrap the underlying gRPC stream.
+    pub fn into_inner(self) -> Streaming<SpotPriceInfo> {
+        self.stream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(s: &str) -> SpotPrice {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for s in [
+            "0",
+            "1",
+            "1850.25",
+            "0.000000000000000001",
+            "115792089237316195423570985008687907853269984665640564039457.584007913129639935",
+        ] {
+            assert_eq!(price(s).to_string(), s);
+        }
+
+        assert_eq!(price("1.50").to_string(), "1.5");
+        assert_eq!(price(".5"), price("0.5"));
+        assert_eq!(price("2."), price("2"));
+        assert_eq!(price("1.5").into_raw(), U256::exp10(17) * 15);
+    }
+
+    #[test]
+    fn display_precision() {
+        assert_eq!(format!("{:.2}", price("1850.256")), "1850.25");
+        assert_eq!(format!("{:.0}", price("1850.9")), "1850");
+        assert_eq!(format!("{:.4}", price("1.5")), "1.5000");
+        assert_eq!(
+            format!("{:.20}", price("0.000000000000000001")),
+            "0.00000000000000000100"
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<SpotPrice>(), Err(ParseSpotPriceError::Empty));
+        assert_eq!(".".parse::<SpotPrice>(), Err(ParseSpotPriceError::Empty));
+        assert_eq!(
+            "1.2.3".parse::<SpotPrice>(),
+            Err(ParseSpotPriceError::InvalidDigit)
+        );
+        assert_eq!(
+            "-1".parse::<SpotPrice>(),
+            Err(ParseSpotPriceError::InvalidDigit)
+        );
+        assert_eq!(
+            "0.0000000000000000001".parse::<SpotPrice>(),
+            Err(ParseSpotPriceError::TooManyDecimals)
+        );
+        assert_eq!(
+            "115792089237316195423570985008687907853269984665640564039457.584007913129639936"
+                .parse::<SpotPrice>(),
+            Err(ParseSpotPriceError::Overflow)
+        );
+        assert_eq!(
+            "115792089237316195423570985008687907853269984665640564039458".parse::<SpotPrice>(),
+            Err(ParseSpotPriceError::Overflow)
+        );
+    }
+
+    #[test]
+    fn fractions_are_truncated() {
+        let third = SpotPrice::one().checked_div(price("3")).unwrap();
+        assert_eq!(third.to_string(), "0.333333333333333333");
+        assert_eq!(third.trunc(), U256::zero());
+        assert_eq!(third.fract(), U256::from(333_333_333_333_333_333u64));
+
+        let tiny = price("0.000000000000000001");
+        assert_eq!(tiny.checked_mul(price("0.5")), Some(SpotPrice::ZERO));
+        assert_eq!(
+            price("2").checked_div(price("3")),
+            Some(price("0.666666666666666666"))
+        );
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(price("1.5") + price("2.25"), price("3.75"));
+        assert_eq!(price("3.75") - price("1.5"), price("2.25"));
+        assert_eq!(price("1.5") * price("2.5"), price("3.75"));
+        assert_eq!(price("3.75") / price("2.5"), price("1.5"));
+
+        let max = SpotPrice::from_raw(U256::MAX);
+        assert_eq!(max.checked_add(price("0.000000000000000001")), None);
+        assert_eq!(SpotPrice::ZERO.checked_sub(price("1")), None);
+        assert_eq!(max.checked_mul(price("2")), None);
+        assert_eq!(price("1").checked_div(SpotPrice::ZERO), None);
+        assert_eq!(max.checked_mul(SpotPrice::one()), Some(max));
+    }
+
+    #[test]
+    #[should_panic(expected = "SpotPrice subtraction underflow")]
+    fn operators_panic() {
+        let _ = SpotPrice::ZERO - SpotPrice::one();
+    }
+
+    #[test]
+    fn mul_amount() {
+        let eth = price("1850.5");
+        let one_eth = U256::exp10(18);
+        assert_eq!(eth.checked_mul_amount(one_eth, 18), Some(eth));
+        assert_eq!(
+            eth.checked_mul_amount(U256::from(50_000_000u64), 8),
+            Some(price("925.25"))
+        );
+        assert_eq!(eth.checked_mul_amount(U256::MAX, 0), None);
+        assert_eq!(eth.checked_mul_amount(U256::exp10(77), 77), Some(eth));
+        assert_eq!(eth.checked_mul_amount(one_eth, 78), None);
+        assert_eq!(eth.checked_mul_amount(one_eth, usize::MAX), None);
+    }
+}
@@ -0,0 +1,493 @@
+// Hand written replacements for the `H128`, `H160` and `H256` messages in `types.proto`.
+//
+// The generated messages nest each hash as `Option`s of smaller hashes, e.g.
+// `H256 { hi: Option<H128>, lo: Option<H128> }`. Codegen maps these messages to the fixed size
+// newtypes below instead (see `build.rs`), which implement `prost::Message` by hand so the wire
+// format is unchanged. A missing `hi` or `lo` on the wire decodes as zero.
+use arrayref::{array_mut_ref, array_ref};
+use prost::bytes::{Buf, BufMut};
+use prost::encoding::{
+    check_wire_type, decode_key, decode_varint, encode_key, encode_varint, encoded_len_varint,
+    key_len, skip_field, uint32, DecodeContext, WireType,
+};
+use prost::DecodeError;
+use std::fmt;
+
+/// A 128 bit hash, encoded as the `H128 { uint64 hi = 1; uint64 lo = 2; }` message.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct H128(pub [u8; 16]);
+
+/// A 160 bit hash (typically an address), encoded as the `H160 { H128 hi = 1; uint32 lo = 2; }`
+/// message.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct H160(pub [u8; 20]);
+
+/// A 256 bit hash or number, encoded as the `H256 { H128 hi = 1; H128 lo = 2; }` message.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct H256(pub [u8; 32]);
+
+// The `H128` message is encoded and decoded directly on byte arrays, so `H160` and `H256` can
+// handle their halves in place rather than going through an intermediate `H128`.
+#[inline]
+fn h128_halves(bytes: &[u8; 16]) -> (u64, u64) {
+    (
+        u64::from_be_bytes(*array_ref!(bytes, 0, 8)),
+        u64::from_be_bytes(*array_ref!(bytes, 8, 8)),
+    )
+}
+
+#[inline]
+fn h128_len(bytes: &[u8; 16]) -> usize {
+    // Scalars are omitted when zero, as for any proto3 field.
+    let (hi, lo) = h128_halves(bytes);
+    (if hi != 0 {
+        key_len(1) + encoded_len_varint(hi)
+    } else {
+        0
+    }) + (if lo != 0 {
+        key_len(2) + encoded_len_varint(lo)
+    } else {
+        0
+    })
+}
+
+#[inline]
+fn h128_encode<B: BufMut>(bytes: &[u8; 16], buf: &mut B) {
+    let (hi, lo) = h128_halves(bytes);
+    if hi != 0 {
+        encode_key(1, WireType::Varint, buf);
+        encode_varint(hi, buf);
+    }
+    if lo != 0 {
+        encode_key(2, WireType::Varint, buf);
+        encode_varint(lo, buf);
+    }
+}
+
+#[inline]
+fn h128_merge_field<B: Buf>(
+    bytes: &mut [u8; 16],
+    tag: u32,
+    wire_type: WireType,
+    buf: &mut B,
+    ctx: DecodeContext,
+) -> Result<(), DecodeError> {
+    match tag {
+        1 | 2 => {
+            let field = if tag == 1 { "hi" } else { "lo" };
+            check_wire_type(WireType::Varint, wire_type)
+                .and_then(|_| decode_varint(buf))
+                .map(|value| {
+                    *array_mut_ref!(bytes, (tag as usize - 1) * 8, 8) = value.to_be_bytes()
+                })
+                .map_err(|mut error| {
+                    error.push("H128", field);
+                    error
+                })
+        }
+        _ => skip_field(wire_type, tag, buf, ctx),
+    }
+}
+
+// Equivalent to `message::encoded_len` for an `H128` field.
+#[inline]
+fn h128_field_len(tag: u32, bytes: &[u8; 16]) -> usize {
+    let len = h128_len(bytes);
+    key_len(tag) + encoded_len_varint(len as u64) + len
+}
+
+// Equivalent to `message::encode` for an `H128` field.
+#[inline]
+fn h128_field_encode<B: BufMut>(tag: u32, bytes: &[u8; 16], buf: &mut B) {
+    encode_key(tag, WireType::LengthDelimited, buf);
+    encode_varint(h128_len(bytes) as u64, buf);
+    h128_encode(bytes, buf);
+}
+
+// Equivalent to `message::merge` for an `H128` field.
+#[inline]
+fn h128_field_merge<B: Buf>(
+    wire_type: WireType,
+    bytes: &mut [u8; 16],
+    buf: &mut B,
+    ctx: DecodeContext,
+) -> Result<(), DecodeError> {
+    check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let len = decode_varint(buf)?;
+    let remaining = buf.remaining();
+    if len > remaining as u64 {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+
+    let limit = remaining - len as usize;
+    while buf.remaining() > limit {
+        let (tag, wire_type) = decode_key(buf)?;
+        h128_merge_field(bytes, tag, wire_type, buf, ctx.clone())?;
+    }
+
+    if buf.remaining() != limit {
+        return Err(DecodeError::new("delimited length exceeded"));
+    }
+    Ok(())
+}
+
+impl prost::Message for H128 {
+    fn encode_raw<B: BufMut>(&self, buf: &mut B) {
+        h128_encode(&self.0, buf);
+    }
+
+    fn merge_field<B: Buf>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        h128_merge_field(&mut self.0, tag, wire_type, buf, ctx)
+    }
+
+    fn encoded_len(&self) -> usize {
+        h128_len(&self.0)
+    }
+
+    fn clear(&mut self) {
+        self.0 = [0; 16];
+    }
+}
+
+impl H160 {
+    fn lo(&self) -> u32 {
+        u32::from_be_bytes(*array_ref!(self.0, 16, 4))
+    }
+}
+
+impl prost::Message for H160 {
+    fn encode_raw<B: BufMut>(&self, buf: &mut B) {
+        // Sub-messages are always sent, matching the generated messages which were always
+        // populated with `Some`.
+        h128_field_encode(1, array_ref!(self.0, 0, 16), buf);
+        let lo = self.lo();
+        if lo != 0 {
+            uint32::encode(2, &lo, buf);
+        }
+    }
+
+    fn merge_field<B: Buf>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        match tag {
+            1 => h128_field_merge(wire_type, array_mut_ref!(self.0, 0, 16), buf, ctx).map_err(
+                |mut error| {
+                    error.push("H160", "hi");
+                    error
+                },
+            ),
+            2 => {
+                let mut lo = self.lo();
+                uint32::merge(wire_type, &mut lo, buf, ctx).map_err(|mut error| {
+                    error.push("H160", "lo");
+                    error
+                })?;
+                *array_mut_ref!(self.0, 16, 4) = lo.to_be_bytes();
+                Ok(())
+            }
+            _ => skip_field(wire_type, tag, buf, ctx),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        let lo = self.lo();
+        h128_field_len(1, array_ref!(self.0, 0, 16))
+            + if lo != 0 {
+                uint32::encoded_len(2, &lo)
+            } else {
+                0
+            }
+    }
+
+    fn clear(&mut self) {
+        self.0 = [0; 20];
+    }
+}
+
+impl prost::Message for H256 {
+    fn encode_raw<B: BufMut>(&self, buf: &mut B) {
+        // Sub-messages are always sent, matching the generated messages which were always
+        // populated with `Some`.
+        h128_field_encode(1, array_ref!(self.0, 0, 16), buf);
+        h128_field_encode(2, array_ref!(self.0, 16, 16), buf);
+    }
+
+    fn merge_field<B: Buf>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        match tag {
+            1 => h128_field_merge(wire_type, array_mut_ref!(self.0, 0, 16), buf, ctx).map_err(
+                |mut error| {
+                    error.push("H256", "hi");
+                    error
+                },
+            ),
+            2 => h128_field_merge(wire_type, array_mut_ref!(self.0, 16, 16), buf, ctx).map_err(
+                |mut error| {
+                    error.push("H256", "lo");
+                    error
+                },
+            ),
+            _ => skip_field(wire_type, tag, buf, ctx),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        h128_field_len(1, array_ref!(self.0, 0, 16)) + h128_field_len(2, array_ref!(self.0, 16, 16))
+    }
+
+    fn clear(&mut self) {
+        self.0 = [0; 32];
+    }
+}
+
+// Compact `Debug` output, i.e. `H256(0x…)` rather than the nested `hi`/`lo` structure.
+impl fmt::Debug for H128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "H128({self:#x})")
+    }
+}
+
+impl fmt::Debug for H160 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "H160({self})")
+    }
+}
+
+impl fmt::Debug for H256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "H256({self:#x})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    // The messages as generated from `types.proto`, which the newtypes must stay wire compatible
+    // with.
+    mod generated {
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct H128 {
+            #[prost(uint64, tag = "1")]
+            pub hi: u64,
+            #[prost(uint64, tag = "2")]
+            pub lo: u64,
+        }
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct H160 {
+            #[prost(message, optional, tag = "1")]
+            pub hi: Option<H128>,
+            #[prost(uint32, tag = "2")]
+            pub lo: u32,
+        }
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct H256 {
+            #[prost(message, optional, tag = "1")]
+            pub hi: Option<H128>,
+            #[prost(message, optional, tag = "2")]
+            pub lo: Option<H128>,
+        }
+    }
+
+    fn generated_h128(bytes: &[u8; 16]) -> generated::H128 {
+        let (hi, lo) = h128_halves(bytes);
+        generated::H128 { hi, lo }
+    }
+
+    fn generated_h160(value: &H160) -> generated::H160 {
+        generated::H160 {
+            hi: Some(generated_h128(array_ref!(value.0, 0, 16))),
+            lo: value.lo(),
+        }
+    }
+
+    fn generated_h256(value: &H256) -> generated::H256 {
+        generated::H256 {
+            hi: Some(generated_h128(array_ref!(value.0, 0, 16))),
+            lo: Some(generated_h128(array_ref!(value.0, 16, 16))),
+        }
+    }
+
+    // Values with each half zero, set, or using the full varint range.
+    fn samples<const N: usize>() -> Vec<[u8; N]> {
+        let mut samples = vec![[0; N], [0xff; N]];
+        for half in [0..N / 2, N / 2..N] {
+            let mut ones = [0; N];
+            ones[half.end - 1] = 1;
+            samples.push(ones);
+
+            let mut full = [0; N];
+            full[half].fill(0xab);
+            samples.push(full);
+        }
+        samples
+    }
+
+    #[test]
+    fn h128_matches_generated() {
+        for bytes in samples::<16>() {
+            let value = H128(bytes);
+            let generated = generated_h128(&bytes);
+            let encoded = value.encode_to_vec();
+            assert_eq!(encoded, generated.encode_to_vec());
+            assert_eq!(value.encoded_len(), encoded.len());
+            assert_eq!(H128::decode(encoded.as_slice()).unwrap(), value);
+            assert_eq!(
+                generated::H128::decode(encoded.as_slice()).unwrap(),
+                generated
+            );
+        }
+    }
+
+    #[test]
+    fn h160_matches_generated() {
+        for bytes in samples::<20>() {
+            let value = H160(bytes);
+            let generated = generated_h160(&value);
+            let encoded = value.encode_to_vec();
+            assert_eq!(encoded, generated.encode_to_vec());
+            assert_eq!(value.encoded_len(), encoded.len());
+            assert_eq!(H160::decode(encoded.as_slice()).unwrap(), value);
+            assert_eq!(
+                generated::H160::decode(encoded.as_slice()).unwrap(),
+                generated
+            );
+        }
+    }
+
+    #[test]
+    fn h256_matches_generated() {
+        for bytes in samples::<32>() {
+            let value = H256(bytes);
+            let generated = generated_h256(&value);
+            let encoded = value.encode_to_vec();
+            assert_eq!(encoded, generated.encode_to_vec());
+            assert_eq!(value.encoded_len(), encoded.len());
+            assert_eq!(H256::decode(encoded.as_slice()).unwrap(), value);
+            assert_eq!(
+                generated::H256::decode(encoded.as_slice()).unwrap(),
+                generated
+            );
+        }
+    }
+
+    #[test]
+    fn zero_halves() {
+        // The sub-messages are always sent, the zero scalars never are.
+        assert_eq!(H128::default().encode_to_vec(), Vec::<u8>::new());
+        assert_eq!(H160::default().encode_to_vec(), vec![0x0a, 0x00]);
+        assert_eq!(
+            H256::default().encode_to_vec(),
+            vec![0x0a, 0x00, 0x12, 0x00]
+        );
+
+        // An address with an all-zero `lo` omits it.
+        let mut address = [0; 20];
+        address[15] = 1;
+        assert_eq!(H160(address).encode_to_vec(), vec![0x0a, 0x02, 0x10, 0x01]);
+
+        // Missing halves decode as zero.
+        assert_eq!(H160::decode(&[][..]).unwrap(), H160::default());
+        assert_eq!(H256::decode(&[0x12, 0x00][..]).unwrap(), H256::default());
+        let generated = generated::H256 {
+            hi: None,
+            lo: Some(generated::H128 { hi: 0, lo: 7 }),
+        };
+        assert_eq!(
+            H256::decode(generated.encode_to_vec().as_slice()).unwrap(),
+            H256::from(7u64)
+        );
+    }
+
+    #[test]
+    fn duplicate_fields() {
+        // Repeated scalars keep the last value, repeated sub-messages are merged, as for the
+        // generated messages.
+        let h128 = [0x08, 0x01, 0x08, 0x02];
+        let value = H128::decode(&h128[..]).unwrap();
+        assert_eq!(
+            generated_h128(&value.0),
+            generated::H128::decode(&h128[..]).unwrap()
+        );
+        assert_eq!(value, H128::from(2u128 << 64));
+
+        let h160 = [
+            0x0a, 0x02, 0x08, 0x01, 0x10, 0x05, 0x0a, 0x02, 0x10, 0x03, 0x10, 0x06,
+        ];
+        let value = H160::decode(&h160[..]).unwrap();
+        assert_eq!(
+            generated_h160(&value),
+            generated::H160::decode(&h160[..]).unwrap()
+        );
+        assert_eq!(&value.0[..8], &1u64.to_be_bytes());
+        assert_eq!(&value.0[8..16], &3u64.to_be_bytes());
+        assert_eq!(value.lo(), 6);
+
+        let h256 = [
+            0x12, 0x02, 0x08, 0x04, 0x0a, 0x02, 0x10, 0x01, 0x12, 0x02, 0x10, 0x09,
+        ];
+        let value = H256::decode(&h256[..]).unwrap();
+        assert_eq!(
+            generated_h256(&value),
+            generated::H256::decode(&h256[..]).unwrap()
+        );
+        let mut expected = [0; 32];
+        expected[15] = 1;
+        expected[23] = 4;
+        expected[31] = 9;
+        assert_eq!(value, H256(expected));
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped() {
+        let value = H256(samples::<32>()[1]);
+        let mut encoded = Vec::new();
+        // An unknown varint field, then an unknown length delimited field inside `hi`.
+        encoded.extend([0x18, 0x96, 0x01]);
+        let mut hi = H128(*array_ref!(value.0, 0, 16)).encode_to_vec();
+        hi.extend([0x22, 0x02, 0xaa, 0xbb]);
+        encoded.push(0x0a);
+        encoded.push(hi.len() as u8);
+        encoded.extend(hi);
+        h128_field_encode(2, array_ref!(value.0, 16, 16), &mut encoded);
+
+        assert_eq!(H256::decode(encoded.as_slice()).unwrap(), value);
+        assert_eq!(
+            generated::H256::decode(encoded.as_slice()).unwrap(),
+            generated_h256(&value)
+        );
+
+        let mut encoded = H160(samples::<20>()[1]).encode_to_vec();
+        encoded.extend([0x2d, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(
+            H160::decode(encoded.as_slice()).unwrap(),
+            H160(samples::<20>()[1])
+        );
+    }
+
+    #[test]
+    fn wrong_wire_types_are_rejected() {
+        assert!(H128::decode(&[0x0a, 0x00][..]).is_err());
+        assert!(H256::decode(&[0x08, 0x01][..]).is_err());
+        // A sub-message longer than the buffer.
+        assert!(H160::decode(&[0x0a, 0x05, 0x08, 0x01][..]).is_err());
+    }
+}
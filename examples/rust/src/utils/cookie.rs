@@ -0,0 +1,353 @@
+// Parsing of the `set-cookie` header returned by `Auth::Nonce`.
+//
+// Only the `name=value` pair is sent back in the `cookie` header, the attributes are used to track
+// when the session expires. `Max-Age` takes precedence over `Expires`, as in RFC 6265, and an
+// `Expires` date which cannot be parsed is ignored.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tonic::metadata::AsciiMetadataValue;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// An error returned when parsing an invalid `set-cookie` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CookieError {
+    /// The header does not start with a `name=value` pair.
+    MissingNameValue(String),
+    /// The `name=value` pair cannot be sent back in a `cookie` header.
+    InvalidHeaderValue(String),
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CookieError::MissingNameValue(value) => {
+                write!(f, "Cookie {value} does not start with a name=value pair")
+            }
+            CookieError::InvalidHeaderValue(value) => {
+                write!(f, "Cookie {value} is not a valid header value")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CookieError {}
+
+/// A session cookie, as set by a `set-cookie` header.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionCookie {
+    pub name: String,
+    pub value: String,
+    /// When the cookie expires, or `None` if it lasts for the session.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl SessionCookie {
+    /// The value of the `cookie` header sending the cookie back.
+    pub fn header_value(&self) -> String {
+        format!("{}={}", self.name, self.value)
+    }
+
+    /// The `cookie` header value as request metadata.
+    pub fn metadata_value(&self) -> Result<AsciiMetadataValue, CookieError> {
+        let header_value = self.header_value();
+        AsciiMetadataValue::try_from(header_value.as_str())
+            .map_err(|_| CookieError::InvalidHeaderValue(header_value))
+    }
+
+    /// Whether the cookie has expired by `time`.
+    pub fn is_expired_at(&self, time: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= time)
+    }
+}
+
+impl FromStr for SessionCookie {
+    type Err = CookieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';').map(str::trim);
+        let (name, value) = parts
+            .next()
+            .and_then(|pair| pair.split_once('='))
+            .filter(|(name, _)| !name.trim().is_empty())
+            .ok_or_else(|| CookieError::MissingNameValue(s.to_string()))?;
+
+        let mut max_age = None;
+        let mut expires = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            match key.trim().to_ascii_lowercase().as_str() {
+                "max-age" => max_age = value.trim().parse::<i64>().ok(),
+                "expires" => expires = parse_http_date(value.trim()),
+                _ => {}
+            }
+        }
+
+        // A non-positive `Max-Age` expires the cookie immediately, while one too large to
+        // represent never expires.
+        let expires_at = match max_age {
+            Some(max_age) => {
+                SystemTime::now().checked_add(Duration::from_secs(max_age.max(0) as u64))
+            }
+            None => expires,
+        };
+
+        let cookie = Self {
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+            expires_at,
+        };
+
+        // The cookie is sent back with every request, so it is checked once here.
+        cookie.metadata_value()?;
+        Ok(cookie)
+    }
+}
+
+// Parses an HTTP date such as `Wed, 21 Oct 2015 07:28:00 GMT`, also accepting the dashed
+// `21-Oct-2015` form used by some servers.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    // The leading day of the week is skipped.
+    let mut fields = value
+        .split(|c: char| c.is_whitespace() || c == '-' || c == ',')
+        .filter(|field| !field.is_empty())
+        .skip_while(|field| field.parse::<u32>().is_err());
+
+    let day: u32 = fields.next()?.parse().ok()?;
+    let month = fields.next()?.to_ascii_lowercase();
+    let month = MONTHS.iter().position(|name| month.starts_with(name))? as u32 + 1;
+    let year: i64 = fields.next()?.parse().ok()?;
+    let mut time = fields.next()?.split(':').map(str::parse::<u64>);
+    let (hours, minutes, seconds) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=31).contains(&day) || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)?).ok()?;
+    let seconds = days
+        .checked_mul(86_400)?
+        .checked_add(hours * 3_600 + minutes * 60 + seconds)?;
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
+
+// The number of days from the unix epoch to a date in the proleptic Gregorian calendar, see
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil, or `None` if the year is
+// too large.
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    let year = if month <= 2 {
+        year.checked_sub(1)?
+    } else {
+        year
+    };
+    let era = if year >= 0 {
+        year
+    } else {
+        year.checked_sub(399)?
+    } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((i64::from(month) + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146_097)?.checked_add(day_of_era - 719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn rfc_1123_dates() {
+        assert_eq!(
+            parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            at(1_445_412_480)
+        );
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            at(784_111_777)
+        );
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), at(0));
+        assert_eq!(
+            parse_http_date("Tue, 29 Feb 2000 12:00:00 GMT"),
+            at(951_825_600)
+        );
+        assert_eq!(
+            parse_http_date("Tue, 19 Jan 2038 03:14:08 GMT"),
+            at(2_147_483_648)
+        );
+
+        // The dashed form, and full month names, are accepted as well.
+        assert_eq!(
+            parse_http_date("Wed, 21-Oct-2015 07:28:00 GMT"),
+            at(1_445_412_480)
+        );
+        assert_eq!(
+            parse_http_date("21 October 2015 07:28:00 GMT"),
+            at(1_445_412_480)
+        );
+    }
+
+    #[test]
+    fn malformed_dates() {
+        for date in [
+            "",
+            "tomorrow",
+            "Wed, 21 Foo 2015 07:28:00 GMT",
+            "Wed, 32 Oct 2015 07:28:00 GMT",
+            "Wed, 21 Oct 2015 24:00:00 GMT",
+            "Wed, 21 Oct 2015 07:60:00 GMT",
+            "Wed, 21 Oct 2015 07:28 GMT",
+            "Wed, 21 Oct 2015",
+            "Wed, 21 Oct 1969 07:28:00 GMT",
+        ] {
+            assert_eq!(parse_http_date(date), None, "{date}");
+        }
+    }
+
+    #[test]
+    fn days_from_epoch() {
+        let days = |year, month, day| days_from_civil(year, month, day).unwrap();
+        assert_eq!(days(1970, 1, 1), 0);
+        assert_eq!(days(1969, 12, 31), -1);
+        assert_eq!(days(2000, 3, 1), 11_017);
+        assert_eq!(days(2000, 2, 29) + 1, days(2000, 3, 1));
+        assert_eq!(days(1900, 2, 28) + 1, days(1900, 3, 1));
+
+        assert_eq!(days_from_civil(i64::MAX, 12, 31), None);
+        assert_eq!(days_from_civil(i64::MIN, 1, 1), None);
+    }
+
+    #[test]
+    fn extreme_years() {
+        assert_eq!(
+            parse_http_date("Sat, 01 Jan 10000 00:00:00 GMT"),
+            at(253_402_300_800)
+        );
+        assert_eq!(
+            parse_http_date("Fri, 31 Dec 99999 23:59:59 GMT"),
+            at(3_093_527_980_799)
+        );
+
+        // Years too large to represent are ignored rather than overflowing.
+        for year in [
+            "9223372036854775807",
+            "92233720368547758",
+            "99999999999999999999",
+        ] {
+            let date = format!("Wed, 21 Oct {year} 07:28:00 GMT");
+            assert_eq!(parse_http_date(&date), None, "{date}");
+        }
+    }
+
+    #[test]
+    fn name_and_value() {
+        let cookie: SessionCookie = " session = abc=def ; Path=/; HttpOnly; Secure"
+            .parse()
+            .unwrap();
+        assert_eq!(cookie.name, "session");
+        assert_eq!(cookie.value, "abc=def");
+        assert_eq!(cookie.expires_at, None);
+        assert_eq!(cookie.header_value(), "session=abc=def");
+
+        for header in ["", "session", "=value", " =value; Max-Age=60"] {
+            assert_eq!(
+                header.parse::<SessionCookie>(),
+                Err(CookieError::MissingNameValue(header.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_header_value() {
+        for header in [
+            "session=a\nb",
+            "sess\0ion=abc; Max-Age=60",
+            "session=a\u{7f}b",
+        ] {
+            let name_value = header.split(';').next().unwrap().to_string();
+            assert_eq!(
+                header.parse::<SessionCookie>(),
+                Err(CookieError::InvalidHeaderValue(name_value))
+            );
+        }
+
+        let cookie = SessionCookie {
+            name: "session".to_string(),
+            value: "a\nb".to_string(),
+            expires_at: None,
+        };
+        assert!(cookie.metadata_value().is_err());
+    }
+
+    #[test]
+    fn max_age_takes_precedence_over_expires() {
+        let before = SystemTime::now();
+        let cookie: SessionCookie =
+            "session=abc; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=3600"
+                .parse()
+                .unwrap();
+        let expires_at = cookie.expires_at.unwrap();
+        assert!(expires_at >= before + Duration::from_secs(3_600));
+        assert!(expires_at <= SystemTime::now() + Duration::from_secs(3_600));
+
+        // Regardless of the order of the attributes, or their case.
+        let cookie: SessionCookie =
+            "session=abc; max-age=3600; EXPIRES=Wed, 21 Oct 2015 07:28:00 GMT"
+                .parse()
+                .unwrap();
+        assert!(cookie.expires_at.unwrap() >= before + Duration::from_secs(3_600));
+
+        let cookie: SessionCookie = "session=abc; Expires=Wed, 21 Oct 2015 07:28:00 GMT"
+            .parse()
+            .unwrap();
+        assert_eq!(cookie.expires_at, at(1_445_412_480));
+        assert!(cookie.is_expired_at(SystemTime::now()));
+    }
+
+    #[test]
+    fn huge_max_age() {
+        // A `Max-Age` beyond what `SystemTime` can represent never expires, rather than panicking.
+        let cookie: SessionCookie = "session=abc; Max-Age=9223372036854775807".parse().unwrap();
+        assert!(!cookie.is_expired_at(SystemTime::now()));
+
+        // One beyond an `i64` is ignored.
+        let cookie: SessionCookie = format!("session=abc; Max-Age={}", u64::MAX)
+            .parse()
+            .unwrap();
+        assert_eq!(cookie.expires_at, None);
+    }
+
+    #[test]
+    fn malformed_attributes() {
+        // A non-positive `Max-Age` expires the cookie immediately.
+        for max_age in ["0", "-1"] {
+            let cookie: SessionCookie = format!("session=abc; Max-Age={max_age}").parse().unwrap();
+            assert!(cookie.is_expired_at(SystemTime::now()));
+        }
+
+        // Attributes which cannot be parsed are ignored, falling back to `Expires` or to a
+        // session cookie.
+        let cookie: SessionCookie =
+            "session=abc; Max-Age=soon; Expires=Wed, 21 Oct 2015 07:28:00 GMT"
+                .parse()
+                .unwrap();
+        assert_eq!(cookie.expires_at, at(1_445_412_480));
+
+        for header in [
+            "session=abc; Expires=never",
+            "session=abc; Max-Age",
+            "session=abc; Expires",
+            "session=abc;;; Max-Age=",
+        ] {
+            let cookie: SessionCookie = header.parse().unwrap();
+            assert_eq!(cookie.expires_at, None, "{header}");
+            assert_eq!(cookie.header_value(), "session=abc");
+        }
+    }
+}
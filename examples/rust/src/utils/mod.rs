@@ -1 +1,3 @@
+pub mod cookie;
 pub mod session_interceptor;
+pub mod ulid;
@@ -1,27 +1,117 @@
-use tonic::metadata::AsciiMetadataValue;
+use crate::utils::cookie::SessionCookie;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
 use tonic::service::Interceptor;
 use tonic::{Request, Status};
 
 const COOKIE_HEADER_KEY: &str = "cookie";
 
+/// A handle to the session cookie, shared by the interceptors of every client using the session.
+/// Replacing the cookie, e.g. after re-authenticating, applies to all later requests without
+/// recreating the clients.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    cookie: Arc<RwLock<Option<SetCookie>>>,
+}
+
+// A session cookie along with when it was set, from which the lifetime of the session is known.
+#[derive(Clone, Debug)]
+struct SetCookie {
+    cookie: SessionCookie,
+    set_at: SystemTime,
+}
+
+impl SetCookie {
+    fn new(cookie: SessionCookie) -> Self {
+        Self {
+            cookie,
+            set_at: SystemTime::now(),
+        }
+    }
+}
+
+impl Session {
+    pub fn new(cookie: SessionCookie) -> Self {
+        Self {
+            cookie: Arc::new(RwLock::new(Some(SetCookie::new(cookie)))),
+        }
+    }
+
+    /// The current session cookie, or `None` if no session has been established.
+    pub fn cookie(&self) -> Option<SessionCookie> {
+        self.cookie
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|set_cookie| set_cookie.cookie.clone())
+    }
+
+    /// Replaces the session cookie for all clients using the session.
+    pub fn set_cookie(&self, cookie: SessionCookie) {
+        *self.cookie.write().unwrap() = Some(SetCookie::new(cookie));
+    }
+
+    /// When the session cookie expires, or `None` if it has no expiry or no session has been
+    /// established.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.cookie
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|set_cookie| set_cookie.cookie.expires_at)
+    }
+
+    /// How long the session cookie lasts from when it was set, or `None` if it has no expiry or
+    /// no session has been established.
+    pub fn lifetime(&self) -> Option<Duration> {
+        self.cookie.read().unwrap().as_ref().and_then(|set_cookie| {
+            let expires_at = set_cookie.cookie.expires_at?;
+            Some(
+                expires_at
+                    .duration_since(set_cookie.set_at)
+                    .unwrap_or_default(),
+            )
+        })
+    }
+
+    /// Whether the session cookie has expired, in which case the session needs to be
+    /// re-authenticated.
+    pub fn is_expired(&self) -> bool {
+        self.cookie
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|set_cookie| set_cookie.cookie.is_expired_at(SystemTime::now()))
+    }
+}
+
+impl From<SessionCookie> for Session {
+    fn from(value: SessionCookie) -> Self {
+        Self::new(value)
+    }
+}
+
 /// The Session Interceptor is a gRPC interceptor for the client to add session
 /// authentication details into the `request` header information such that the server can
 /// validate/confirm the client is using a valid session.
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct SessionInterceptor {
-    pub session_cookie: String,
+    pub session: Session,
 }
 
 impl Interceptor for SessionInterceptor {
     fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        // The cookie is read on every call, so requests always use the latest session.
         // If we have established a session set the appropriate session headers before the request
-        // goes to the server.
-        let request = if !self.session_cookie.is_empty() {
+        // goes to the server. Only the `name=value` pair is sent, without the cookie attributes.
+        let request = if let Some(session_cookie) = self.session.cookie() {
             let mut request = request;
 
-            // We should always be able to transform a String into an `AsciiMetadataValue` so its
-            // safe to unwrap without checking.
-            let cookie_value = AsciiMetadataValue::try_from(&self.session_cookie).unwrap();
+            // Parsed and stored cookies are checked already, so this only fails for a cookie
+            // built by hand.
+            let cookie_value = session_cookie
+                .metadata_value()
+                .map_err(|error| Status::internal(error.to_string()))?;
 
             // Insert the session cookie.
             request
@@ -35,3 +125,37 @@ impl Interceptor for SessionInterceptor {
         Ok(request)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_the_session_cookie() {
+        let mut interceptor = SessionInterceptor::default();
+        let request = interceptor.call(Request::new(())).unwrap();
+        assert!(request.metadata().get(COOKIE_HEADER_KEY).is_none());
+
+        interceptor
+            .session
+            .set_cookie("session=abc; Max-Age=60; HttpOnly".parse().unwrap());
+        let request = interceptor.call(Request::new(())).unwrap();
+        assert_eq!(
+            request.metadata().get(COOKIE_HEADER_KEY).unwrap(),
+            "session=abc"
+        );
+    }
+
+    #[test]
+    fn rejects_an_invalid_session_cookie() {
+        let mut interceptor = SessionInterceptor {
+            session: Session::new(SessionCookie {
+                name: "session".to_string(),
+                value: "a\rb".to_string(),
+                expires_at: None,
+            }),
+        };
+        let status = interceptor.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Internal);
+    }
+}
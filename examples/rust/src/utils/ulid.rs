@@ -0,0 +1,26 @@
+// ULID generation for request identifiers, see https://github.com/ulid/spec.
+//
+// A ULID is a 48 bit millisecond timestamp followed by 80 random bits, which fits the `H128`
+// used for the `ulid` fields of the API.
+use crate::grpc_codegen::H128;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Generates a new ULID for the current time.
+pub fn generate() -> H128 {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&timestamp.to_be_bytes()[2..]);
+    getrandom::getrandom(&mut bytes[6..]).expect("the OS random number generator is unavailable");
+    H128(bytes)
+}
+
+/// The millisecond unix timestamp encoded in `ulid`.
+pub fn timestamp_ms(ulid: &H128) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes[2..].copy_from_slice(&ulid.0[..6]);
+    u64::from_be_bytes(bytes)
+}